@@ -299,6 +299,14 @@ pub fn compiletest(build: &Build,
         cmd.env("SANITIZER_SUPPORT", "1");
     }
 
+    if build.config.profiler {
+        cmd.env("PROFILER_SUPPORT", "1");
+        // Profiles are merged and read with the tools of the same LLVM build,
+        // which are next to `FileCheck`.
+        let llvm_bin_dir = build.llvm_filecheck(&build.config.build);
+        cmd.env("LLVM_BIN_DIR", llvm_bin_dir.parent().unwrap());
+    }
+
     cmd.arg("--adb-path").arg("adb");
     cmd.arg("--adb-test-dir").arg(ADB_TEST_DIR);
     if target.contains("android") {
@@ -48,6 +48,15 @@ pub fn std(build: &Build, target: &str, compiler: &Compiler) {
     let out_dir = build.cargo_out(compiler, Mode::Libstd, target);
     build.clear_if_dirty(&out_dir, &build.compiler_path(compiler));
     let mut cargo = build.cargo(compiler, Mode::Libstd, target, "build");
+    std_cargo(build, target, compiler, &mut cargo);
+    run_cargo(build,
+              &mut cargo,
+              &libstd_stamp(build, &compiler, target));
+}
+
+/// Configure a Cargo invocation over the standard library, shared by
+/// building and checking it.
+fn std_cargo(build: &Build, target: &str, compiler: &Compiler, cargo: &mut Command) {
     let mut features = build.std_features();
 
     if let Ok(target) = env::var("MACOSX_STD_DEPLOYMENT_TARGET") {
@@ -87,10 +96,6 @@ pub fn std(build: &Build, target: &str, compiler: &Compiler) {
             cargo.env("MUSL_ROOT", p);
         }
     }
-
-    run_cargo(build,
-              &mut cargo,
-              &libstd_stamp(build, &compiler, target));
 }
 
 /// Link all libstd rlibs/dylibs into the sysroot location.
@@ -199,14 +204,19 @@ pub fn test(build: &Build, target: &str, compiler: &Compiler) {
     let out_dir = build.cargo_out(compiler, Mode::Libtest, target);
     build.clear_if_dirty(&out_dir, &libstd_stamp(build, compiler, target));
     let mut cargo = build.cargo(compiler, Mode::Libtest, target, "build");
+    test_cargo(build, &mut cargo);
+    run_cargo(build,
+              &mut cargo,
+              &libtest_stamp(build, compiler, target));
+}
+
+/// Same as `std_cargo`, only for libtest
+fn test_cargo(build: &Build, cargo: &mut Command) {
     if let Ok(target) = env::var("MACOSX_STD_DEPLOYMENT_TARGET") {
         cargo.env("MACOSX_DEPLOYMENT_TARGET", target);
     }
     cargo.arg("--manifest-path")
          .arg(build.src.join("src/libtest/Cargo.toml"));
-    run_cargo(build,
-              &mut cargo,
-              &libtest_stamp(build, compiler, target));
 }
 
 /// Same as `std_link`, only for libtest
@@ -238,6 +248,14 @@ pub fn rustc(build: &Build, target: &str, compiler: &Compiler) {
     build.clear_if_dirty(&out_dir, &libtest_stamp(build, compiler, target));
 
     let mut cargo = build.cargo(compiler, Mode::Librustc, target, "build");
+    rustc_cargo(build, target, compiler, &mut cargo);
+    run_cargo(build,
+              &mut cargo,
+              &librustc_stamp(build, compiler, target));
+}
+
+/// Same as `std_cargo`, only for librustc
+fn rustc_cargo(build: &Build, target: &str, compiler: &Compiler, cargo: &mut Command) {
     cargo.arg("--features").arg(build.rustc_features())
          .arg("--manifest-path")
          .arg(build.src.join("src/rustc/Cargo.toml"));
@@ -302,9 +320,6 @@ pub fn rustc(build: &Build, target: &str, compiler: &Compiler) {
     if let Some(ref s) = build.config.rustc_default_ar {
         cargo.env("CFG_DEFAULT_AR", s);
     }
-    run_cargo(build,
-              &mut cargo,
-              &librustc_stamp(build, compiler, target));
 }
 
 /// Same as `std_link`, only for librustc
@@ -322,6 +337,77 @@ pub fn rustc_link(build: &Build,
                    &librustc_stamp(build, compiler, target));
 }
 
+/// Type-check the standard library, libtest or the compiler without doing
+/// any code generation.
+///
+/// This runs `cargo check`, and hence `rustc --emit=metadata`, over the crates
+/// of `mode` with the same configuration as a real build. The output goes to
+/// separate `stageN-*-check` directories and sysroot so it never mixes with
+/// the artifacts of `x.py build`. The metadata of each checked set of crates
+/// is linked into that sysroot so the next set can be checked against it.
+pub fn check(build: &Build, target: &str, compiler: &Compiler, mode: Mode) {
+    let (name, previous) = match mode {
+        Mode::Libstd => ("std", build.compiler_path(compiler)),
+        Mode::Libtest => ("test", check_stamp(build, compiler, Mode::Libstd, target)),
+        Mode::Librustc => ("rustc", check_stamp(build, compiler, Mode::Libtest, target)),
+        Mode::Tool => panic!("tools cannot be checked"),
+    };
+    let _folder = build.fold_output(|| format!("stage{}-{}-check", compiler.stage, name));
+    println!("Checking stage{} {} artifacts ({} -> {})", compiler.stage, name,
+             compiler.host, target);
+
+    let out_dir = check_stage_out(build, compiler, mode);
+    build.clear_if_dirty(&out_dir, &previous);
+
+    let sysroot = check_sysroot(build, compiler);
+    let libdir = sysroot.join("lib").join("rustlib").join(target).join("lib");
+    if mode == Mode::Libstd {
+        // Metadata from an earlier check may have a different hash, and
+        // leaving it around would make crate loading ambiguous.
+        let _ = fs::remove_dir_all(&libdir);
+    }
+
+    let mut cargo = build.cargo(compiler, mode, target, "check");
+    match mode {
+        Mode::Libstd => std_cargo(build, target, compiler, &mut cargo),
+        Mode::Libtest => test_cargo(build, &mut cargo),
+        Mode::Librustc => rustc_cargo(build, target, compiler, &mut cargo),
+        Mode::Tool => unreachable!(),
+    }
+    cargo.env("CARGO_TARGET_DIR", &out_dir)
+         .env("RUSTC_SYSROOT", &sysroot);
+
+    let stamp = check_stamp(build, compiler, mode, target);
+    run_cargo(build, &mut cargo, &stamp);
+    add_to_sysroot(&libdir, &stamp);
+}
+
+/// Root directory for the output of `x.py check` for `mode`, mirroring
+/// `Build::stage_out`.
+fn check_stage_out(build: &Build, compiler: &Compiler, mode: Mode) -> PathBuf {
+    let name = match mode {
+        Mode::Libstd => "std",
+        Mode::Libtest => "test",
+        Mode::Librustc => "rustc",
+        Mode::Tool => "tools",
+    };
+    build.out.join(compiler.host)
+             .join(format!("stage{}-{}-check", compiler.stage, name))
+}
+
+/// Sysroot that the metadata produced by `x.py check` is linked into.
+fn check_sysroot(build: &Build, compiler: &Compiler) -> PathBuf {
+    build.out.join(compiler.host)
+             .join(format!("stage{}-check-sysroot", compiler.stage))
+}
+
+/// Cargo's output path for the metadata of the crates of `mode` checked by a
+/// particular compiler for the specified target.
+fn check_stamp(build: &Build, compiler: &Compiler, mode: Mode, target: &str) -> PathBuf {
+    check_stage_out(build, compiler, mode).join(target).join(build.cargo_dir())
+                                          .join(".check.stamp")
+}
+
 /// Cargo's output path for the standard library in a given stage, compiled
 /// by a particular compiler for the specified target.
 fn libstd_stamp(build: &Build, compiler: &Compiler, target: &str) -> PathBuf {
@@ -520,6 +606,7 @@ fn run_cargo(build: &Build, cargo: &mut Command, stamp: &Path) {
             // Skip files like executables
             if !filename.ends_with(".rlib") &&
                !filename.ends_with(".lib") &&
+               !filename.ends_with(".rmeta") &&
                !is_dylib(&filename) {
                 continue
             }
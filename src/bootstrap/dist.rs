@@ -556,6 +556,7 @@ pub fn rust_src(build: &Build) {
         "src/liblibc",
         "src/libpanic_abort",
         "src/libpanic_unwind",
+        "src/libprofiler_builtins",
         "src/librand",
         "src/librustc_asan",
         "src/librustc_lsan",
@@ -55,6 +55,9 @@ pub enum Subcommand {
     Build {
         paths: Vec<PathBuf>,
     },
+    Check {
+        paths: Vec<PathBuf>,
+    },
     Doc {
         paths: Vec<PathBuf>,
     },
@@ -84,6 +87,7 @@ Usage: x.py <subcommand> [options] [<paths>...]
 
 Subcommands:
     build       Compile either the compiler or libraries
+    check       Type-check the compiler and libraries without building them
     test        Build and run some test suites
     bench       Build and run some benchmarks
     doc         Build documentation
@@ -125,6 +129,7 @@ To learn more about a subcommand, run `./x.py <subcommand> -h`");
         let mut possible_subcommands = args.iter().collect::<Vec<_>>();
         possible_subcommands.retain(|&s|
                                            (s == "build")
+                                        || (s == "check")
                                         || (s == "test")
                                         || (s == "bench")
                                         || (s == "doc")
@@ -204,6 +209,21 @@ Arguments:
     arguments would), and then use the compiler built in stage 0 to build
     src/libtest and its dependencies.
     Once this is done, build/$ARCH/stage1 contains a usable compiler.");
+            }
+            "check" => {
+                subcommand_help.push_str("\n
+Arguments:
+    This subcommand accepts a number of paths to directories to the crates
+    to type-check. Only metadata is emitted and the stage0 compiler is always
+    used, so nothing needs to be built first. For example:
+
+        ./x.py check src/libstd
+        ./x.py check src/librustc
+
+    If no arguments are passed then the standard library and the compiler are
+    all checked.
+
+        ./x.py check");
             }
             "test" => {
                 subcommand_help.push_str("\n
@@ -268,6 +288,9 @@ Arguments:
             "build" => {
                 Subcommand::Build { paths: paths }
             }
+            "check" => {
+                Subcommand::Check { paths: paths }
+            }
             "test" => {
                 Subcommand::Test {
                     paths: paths,
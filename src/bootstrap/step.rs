@@ -298,6 +298,36 @@ pub fn build_rules<'a>(build: &'a Build) -> Rules {
          .dep(|s| s.name("create-sysroot").target(s.host))
          .run(move |s| compile::build_startup_objects(build, &s.compiler(), s.target));
 
+    // ========================================================================
+    // Check targets
+    //
+    // Type-check the standard library and the compiler without generating any
+    // code. This always uses the stage0 compiler of the build triple, so it
+    // needs nothing to be built first and is the quickest way to find out
+    // whether a change compiles.
+    rules.check("typeck-std", "src/libstd")
+         .only_host_build(true)
+         .default(true)
+         .run(move |s| {
+             compile::check(build, s.target, &s.compiler(), Mode::Libstd)
+         });
+    rules.check("typeck-test", "src/libtest")
+         .dep(|s| s.name("typeck-std"))
+         .only_host_build(true)
+         .default(true)
+         .run(move |s| {
+             compile::check(build, s.target, &s.compiler(), Mode::Libtest)
+         });
+    rules.check("typeck-rustc", "src/librustc")
+         .dep(|s| s.name("typeck-test"))
+         .dep(move |s| s.name("llvm").host(&build.config.build).stage(0))
+         .host(true)
+         .only_host_build(true)
+         .default(true)
+         .run(move |s| {
+             compile::check(build, s.target, &s.compiler(), Mode::Librustc)
+         });
+
     // ========================================================================
     // Test targets
     //
@@ -945,6 +975,7 @@ struct Rule<'a> {
 #[derive(PartialEq)]
 enum Kind {
     Build,
+    Check,
     Test,
     Bench,
     Dist,
@@ -1035,10 +1066,15 @@ pub struct Rules<'a> {
 
 impl<'a> Rules<'a> {
     fn new(build: &'a Build) -> Rules<'a> {
+        // `x.py check` only ever uses the stage0 compiler.
+        let stage = match build.flags.cmd {
+            Subcommand::Check { .. } => 0,
+            _ => build.flags.stage.unwrap_or(2),
+        };
         Rules {
             build: build,
             sbuild: Step {
-                stage: build.flags.stage.unwrap_or(2),
+                stage: stage,
                 target: &build.config.build,
                 host: &build.config.build,
                 name: "",
@@ -1057,6 +1093,12 @@ impl<'a> Rules<'a> {
         self.rule(name, path, Kind::Build)
     }
 
+    /// Same as `build`, but for `Kind::Check`.
+    fn check<'b>(&'b mut self, name: &'a str, path: &'a str)
+                 -> RuleBuilder<'a, 'b> {
+        self.rule(name, path, Kind::Check)
+    }
+
     /// Same as `build`, but for `Kind::Test`.
     fn test<'b>(&'b mut self, name: &'a str, path: &'a str)
                 -> RuleBuilder<'a, 'b> {
@@ -1123,6 +1165,7 @@ invalid rule dependency graph detected, was a rule added and maybe typo'd?
     pub fn get_help(&self, command: &str) -> Option<String> {
         let kind = match command {
             "build" => Kind::Build,
+            "check" => Kind::Check,
             "doc" => Kind::Doc,
             "test" => Kind::Test,
             "bench" => Kind::Bench,
@@ -1174,6 +1217,7 @@ invalid rule dependency graph detected, was a rule added and maybe typo'd?
         // flag on the command line.
         let (kind, paths) = match self.build.flags.cmd {
             Subcommand::Build { ref paths } => (Kind::Build, &paths[..]),
+            Subcommand::Check { ref paths } => (Kind::Check, &paths[..]),
             Subcommand::Doc { ref paths } => (Kind::Doc, &paths[..]),
             Subcommand::Test { ref paths, .. } => (Kind::Test, &paths[..]),
             Subcommand::Bench { ref paths, .. } => (Kind::Bench, &paths[..]),
@@ -1768,4 +1812,27 @@ mod tests {
         assert!(!plan.iter().any(|s| s.name.contains("tidy")));
         assert!(plan.iter().any(|s| s.name.contains("valgrind")));
     }
+
+    #[test]
+    fn check_default() {
+        let build = build(&["check", "--stage=2"], &["B"], &["C"]);
+        let rules = super::build_rules(&build);
+        let plan = rules.plan();
+        println!("rules: {:#?}", plan);
+        assert!(plan.iter().all(|s| s.stage == 0));
+        assert!(plan.iter().all(|s| s.host == "A"));
+
+        let step = super::Step {
+            name: "",
+            stage: 0,
+            host: &build.config.build,
+            target: &build.config.build,
+        };
+
+        assert!(plan.contains(&step.name("typeck-std")));
+        assert!(plan.contains(&step.name("typeck-rustc")));
+        assert!(plan.contains(&step.target("B").name("typeck-rustc")));
+        assert!(plan.contains(&step.target("C").name("typeck-std")));
+        assert!(!plan.contains(&step.target("C").name("typeck-rustc")));
+    }
 }
@@ -0,0 +1,121 @@
+# Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+# file at the top-level directory of this distribution and at
+# http://rust-lang.org/COPYRIGHT.
+#
+# Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+# http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+# <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+# option. This file may not be copied, modified, or distributed
+# except according to those terms.
+
+"""
+Summarizes the traces written by `rustc -Z self-profile=trace.json`.
+
+    python summarize-self-profile.py [--by-name] [--limit N] trace.json...
+
+For every activity, prints how often it ran, its self time (excluding the
+activities nested in it) and its total time, averaged over the traces given,
+along with the smallest and largest total time of a single trace. Passing the
+traces of several runs of the same compilation smooths out noise.
+
+Dep-graph tasks are grouped by the kind of their dep-node, such as
+`TypeckTables`, unless `--by-name` is given. Other activities are grouped by
+name. Times are in milliseconds.
+"""
+
+from __future__ import print_function
+
+import argparse
+import json
+import sys
+from collections import defaultdict
+
+
+def activity_key(event, by_name):
+    name = event['name']
+    if event.get('cat') == 'task' and not by_name:
+        name = name.split('(', 1)[0]
+    return (event.get('cat', ''), name)
+
+
+def summarize(path, by_name):
+    """Returns the count, self time and total time of each activity in the
+    trace at `path`."""
+    with open(path) as f:
+        trace = json.load(f)
+    events = trace['traceEvents'] if isinstance(trace, dict) else trace
+
+    count = defaultdict(int)
+    self_time = defaultdict(float)
+    total_time = defaultdict(float)
+
+    # The activities running on each thread, innermost last, as
+    # `[key, start, time spent in nested activities]`.
+    stacks = defaultdict(list)
+    for event in events:
+        phase = event.get('ph')
+        stack = stacks[(event.get('pid'), event.get('tid'))]
+        if phase == 'B':
+            stack.append([activity_key(event, by_name), event['ts'], 0.0])
+        elif phase == 'E':
+            if not stack:
+                raise ValueError('{}: unmatched end of `{}`'.format(path, event['name']))
+            key, start, nested = stack.pop()
+            elapsed = (event['ts'] - start) / 1000.0
+            count[key] += 1
+            self_time[key] += elapsed - nested
+            # Recursive activities only count towards their own total once.
+            if all(outer[0] != key for outer in stack):
+                total_time[key] += elapsed
+            if stack:
+                stack[-1][2] += elapsed
+
+    for (tid, stack) in stacks.items():
+        if stack:
+            print('warning: {}: activities on thread {} never ended'.format(path, tid[1]),
+                  file=sys.stderr)
+    return count, self_time, total_time
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__,
+                                     formatter_class=argparse.RawDescriptionHelpFormatter)
+    parser.add_argument('traces', metavar='TRACE', nargs='+',
+                        help='a trace written by `-Z self-profile`')
+    parser.add_argument('--by-name', action='store_true',
+                        help='do not group dep-graph tasks by the kind of their dep-node')
+    parser.add_argument('--limit', type=int, default=50,
+                        help='how many activities to print, by decreasing self time '
+                             '(0 for all; default: 50)')
+    args = parser.parse_args()
+
+    runs = [summarize(path, args.by_name) for path in args.traces]
+    keys = set()
+    for (count, _, _) in runs:
+        keys.update(count)
+
+    n = float(len(runs))
+    rows = []
+    for key in keys:
+        totals = [total_time.get(key, 0.0) for (_, _, total_time) in runs]
+        rows.append((key,
+                     sum(count.get(key, 0) for (count, _, _) in runs) / n,
+                     sum(self_time.get(key, 0.0) for (_, self_time, _) in runs) / n,
+                     sum(totals) / n,
+                     min(totals),
+                     max(totals)))
+    rows.sort(key=lambda row: row[2], reverse=True)
+    if args.limit > 0:
+        rows = rows[:args.limit]
+
+    header = ('Category', 'Activity', 'Count', 'Self', 'Total', 'Min total', 'Max total')
+    print('{:<8} {:<50} {:>10} {:>10} {:>10} {:>10} {:>10}'.format(*header))
+    for ((category, name), count, self_ms, total, low, high) in rows:
+        if len(name) > 50:
+            name = name[:47] + '...'
+        print('{:<8} {:<50} {:>10.1f} {:>10.1f} {:>10.1f} {:>10.1f} {:>10.1f}'
+              .format(category, name, count, self_ms, total, low, high))
+
+
+if __name__ == '__main__':
+    main()
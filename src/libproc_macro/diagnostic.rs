@@ -0,0 +1,111 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use syntax::codemap::MultiSpan;
+use syntax::errors::{self, DiagnosticBuilder};
+
+use Span;
+use __internal;
+
+/// The level of a `Diagnostic`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[unstable(feature = "proc_macro", issue = "38356")]
+pub enum Level {
+    /// An error.
+    Error,
+    /// A warning.
+    Warning,
+    /// A note.
+    Note,
+    /// A help message.
+    Help,
+}
+
+impl Level {
+    fn to_internal(self) -> errors::Level {
+        match self {
+            Level::Error => errors::Level::Error,
+            Level::Warning => errors::Level::Warning,
+            Level::Note => errors::Level::Note,
+            Level::Help => errors::Level::Help,
+        }
+    }
+}
+
+/// A message reported by a procedural macro, optionally pointing at a span
+/// and followed by notes and help messages.
+#[derive(Clone, Debug)]
+#[unstable(feature = "proc_macro", issue = "38356")]
+pub struct Diagnostic {
+    level: Level,
+    message: String,
+    span: Option<Span>,
+    children: Vec<Diagnostic>,
+}
+
+macro_rules! diagnostic_child_methods {
+    ($spanned:ident, $regular:ident, $level:expr) => (
+        /// Adds a child diagnostic with the given message at the given span.
+        pub fn $spanned<T: Into<String>>(mut self, span: Span, message: T) -> Diagnostic {
+            self.children.push(Diagnostic::spanned(span, $level, message));
+            self
+        }
+
+        /// Adds a child diagnostic with the given message.
+        pub fn $regular<T: Into<String>>(mut self, message: T) -> Diagnostic {
+            self.children.push(Diagnostic::new($level, message));
+            self
+        }
+    )
+}
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl Diagnostic {
+    /// Creates a diagnostic with the given level and message.
+    pub fn new<T: Into<String>>(level: Level, message: T) -> Diagnostic {
+        Diagnostic { level: level, message: message.into(), span: None, children: vec![] }
+    }
+
+    /// Creates a diagnostic with the given level and message at the given
+    /// span.
+    pub fn spanned<T: Into<String>>(span: Span, level: Level, message: T) -> Diagnostic {
+        Diagnostic {
+            level: level,
+            message: message.into(),
+            span: Some(span),
+            children: vec![],
+        }
+    }
+
+    diagnostic_child_methods!(span_warning, warning, Level::Warning);
+    diagnostic_child_methods!(span_note, note, Level::Note);
+    diagnostic_child_methods!(span_help, help, Level::Help);
+
+    /// The level of this diagnostic.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Reports this diagnostic and its children.
+    pub fn emit(self) {
+        __internal::with_parse_sess(|sess| {
+            let handler = &sess.span_diagnostic;
+            let mut diag = DiagnosticBuilder::new(handler, self.level.to_internal(), &self.message);
+            if let Some(span) = self.span {
+                diag.set_span(span.0);
+            }
+            for child in self.children {
+                let span = child.span.map_or(MultiSpan::new(), |span| span.0.into());
+                diag.sub(child.level.to_internal(), &child.message, span, None);
+            }
+            diag.emit();
+        });
+    }
+}
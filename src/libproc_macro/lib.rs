@@ -15,11 +15,13 @@
 //! Currently the primary use of this crate is to provide the ability to define
 //! new custom derive modes through `#[proc_macro_derive]`.
 //!
-//! Note that this crate is intentionally very bare-bones currently. The main
-//! type, `TokenStream`, only supports `fmt::Display` and `FromStr`
-//! implementations, indicating that it can only go to and come from a string.
-//! This functionality is intended to be expanded over time as more surface
-//! area for macro authors is stabilized.
+//! The main type, `TokenStream`, can go to and come from a string through its
+//! `fmt::Display` and `FromStr` implementations. On nightly it can also be
+//! iterated over and built up as a sequence of `TokenTree`s, each of which
+//! carries the `Span` of the code it came from, and the `Diagnostic` API can
+//! report errors and warnings on those spans. This functionality is intended
+//! to be expanded over time as more surface area for macro authors is
+//! stabilized.
 //!
 //! See [the book](../book/procedural-macros.html) for more.
 
@@ -43,12 +45,25 @@
 
 extern crate syntax;
 
-use std::fmt;
+mod diagnostic;
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+pub use diagnostic::{Diagnostic, Level};
+
+use std::{ascii, cmp, fmt, iter};
+use std::rc::Rc;
 use std::str::FromStr;
 
+use syntax::ast;
+use syntax::codemap::{self, DUMMY_SP};
 use syntax::errors::DiagnosticBuilder;
-use syntax::parse;
-use syntax::tokenstream::TokenStream as TokenStream_;
+use syntax::ext::base::Annotatable;
+use syntax::ext::expand::stream_for_item;
+use syntax::parse::{self, token};
+use syntax::parse::lexer::comments;
+use syntax::print::pprust;
+use syntax::symbol::Symbol;
+use syntax::tokenstream::{self, TokenStream as TokenStream_};
 
 /// The main type provided by this crate, representing an abstract stream of
 /// tokens.
@@ -88,6 +103,7 @@ pub mod __internal {
 
     use syntax::ast;
     use syntax::ptr::P;
+    use syntax::codemap::{Span, DUMMY_SP};
     use syntax::parse::{self, token, ParseSess};
     use syntax::tokenstream::{TokenTree, TokenStream as TokenStream_};
 
@@ -140,13 +156,16 @@ pub mod __internal {
 
     // Emulate scoped_thread_local!() here essentially
     thread_local! {
-        static CURRENT_SESS: Cell<*const ParseSess> = Cell::new(0 as *const _);
+        static CURRENT_SESS: Cell<(*const ParseSess, Span)> =
+            Cell::new((0 as *const _, DUMMY_SP));
     }
 
-    pub fn set_parse_sess<F, R>(sess: &ParseSess, f: F) -> R
+    /// Runs `f` with `sess` as the session of the macro being expanded, which
+    /// was invoked at `call_site`.
+    pub fn set_parse_sess<F, R>(sess: &ParseSess, call_site: Span, f: F) -> R
         where F: FnOnce() -> R
     {
-        struct Reset { prev: *const ParseSess }
+        struct Reset { prev: (*const ParseSess, Span) }
 
         impl Drop for Reset {
             fn drop(&mut self) {
@@ -156,15 +175,21 @@ pub mod __internal {
 
         CURRENT_SESS.with(|p| {
             let _reset = Reset { prev: p.get() };
-            p.set(sess);
+            p.set((sess, call_site));
             f()
         })
     }
 
+    /// The span of the invocation of the macro being expanded, or `DUMMY_SP`
+    /// outside of an expansion.
+    pub fn call_site() -> Span {
+        CURRENT_SESS.with(|p| p.get().1)
+    }
+
     pub fn with_parse_sess<F, R>(f: F) -> R
         where F: FnOnce(&ParseSess) -> R
     {
-        let p = CURRENT_SESS.with(|p| p.get());
+        let p = CURRENT_SESS.with(|p| p.get().0);
         assert!(!p.is_null(), "proc_macro::__internal::with_parse_sess() called \
                                before set_parse_sess()!");
         f(unsafe { &*p })
@@ -196,3 +221,509 @@ impl fmt::Display for TokenStream {
         self.inner.fmt(f)
     }
 }
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl Clone for TokenStream {
+    fn clone(&self) -> TokenStream {
+        TokenStream { inner: self.inner.clone() }
+    }
+}
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl fmt::Debug for TokenStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("TokenStream ")?;
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl TokenStream {
+    /// Returns an empty `TokenStream`.
+    pub fn empty() -> TokenStream {
+        TokenStream { inner: TokenStream_::empty() }
+    }
+
+    /// Whether this `TokenStream` contains no token trees.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl From<TokenTree> for TokenStream {
+    fn from(tree: TokenTree) -> TokenStream {
+        iter::once(tree).collect()
+    }
+}
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl From<TokenNode> for TokenStream {
+    fn from(kind: TokenNode) -> TokenStream {
+        TokenTree::from(kind).into()
+    }
+}
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl iter::FromIterator<TokenStream> for TokenStream {
+    fn from_iter<I: IntoIterator<Item = TokenStream>>(streams: I) -> Self {
+        TokenStream {
+            inner: TokenStream_::concat(streams.into_iter().map(|stream| stream.inner).collect())
+        }
+    }
+}
+
+/// Builds a `TokenStream` from token trees. Consecutive `Op`s which are
+/// `Spacing::Joint` to the next one are glued into multi-character operators,
+/// taking the longest operator possible each time.
+///
+/// # Panics
+///
+/// Panics if an `Op` is not a character which can start an operator.
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl iter::FromIterator<TokenTree> for TokenStream {
+    fn from_iter<I: IntoIterator<Item = TokenTree>>(trees: I) -> Self {
+        let mut streams = Vec::new();
+        let mut ops = String::new();
+        let mut ops_span = DUMMY_SP;
+        for tree in trees {
+            let span = tree.span.0;
+            let tree = match tree.kind {
+                TokenNode::Op(op, spacing) => {
+                    if ops.is_empty() {
+                        ops_span = span;
+                    } else {
+                        ops_span.hi = cmp::max(ops_span.hi, span.hi);
+                    }
+                    ops.push(op);
+                    if spacing == Spacing::Alone {
+                        glue_ops(&ops, ops_span, &mut streams);
+                        ops.clear();
+                    }
+                    continue;
+                }
+                TokenNode::Group(delimiter, stream) => {
+                    tokenstream::TokenTree::Delimited(span, tokenstream::Delimited {
+                        delim: delimiter.to_internal(),
+                        tts: stream.inner.into(),
+                    })
+                }
+                TokenNode::Term(term) => tokenstream::TokenTree::Token(span, term.to_internal()),
+                TokenNode::Literal(literal) => tokenstream::TokenTree::Token(span, literal.0),
+            };
+            if !ops.is_empty() {
+                glue_ops(&ops, ops_span, &mut streams);
+                ops.clear();
+            }
+            streams.push(tree.into());
+        }
+        if !ops.is_empty() {
+            glue_ops(&ops, ops_span, &mut streams);
+        }
+        TokenStream { inner: TokenStream_::concat(streams) }
+    }
+}
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl IntoIterator for TokenStream {
+    type Item = TokenTree;
+    type IntoIter = TokenTreeIter;
+
+    fn into_iter(self) -> TokenTreeIter {
+        TokenTreeIter { cursor: self.inner.into_trees(), next: Vec::new() }
+    }
+}
+
+/// An iterator over the token trees of a `TokenStream`.
+#[unstable(feature = "proc_macro", issue = "38356")]
+pub struct TokenTreeIter {
+    cursor: tokenstream::Cursor,
+    // The remaining trees of a token which is split into several, such as
+    // the `Op`s of a multi-character operator, in reverse order.
+    next: Vec<TokenTree>,
+}
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl Iterator for TokenTreeIter {
+    type Item = TokenTree;
+
+    fn next(&mut self) -> Option<TokenTree> {
+        loop {
+            if let Some(tree) = self.next.pop() {
+                return Some(tree);
+            }
+            match self.cursor.next() {
+                Some(tree) => {
+                    self.next = TokenTree::from_internal(tree);
+                    self.next.reverse();
+                }
+                None => return None,
+            }
+        }
+    }
+}
+
+/// A region of source code, used to point diagnostics at the code a token
+/// came from.
+#[derive(Copy, Clone, Debug)]
+#[unstable(feature = "proc_macro", issue = "38356")]
+pub struct Span(codemap::Span);
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl Span {
+    /// The span of the invocation of the current procedural macro.
+    pub fn call_site() -> Span {
+        Span(__internal::call_site())
+    }
+
+    /// Creates an error at this span. It is reported once `emit` is called.
+    pub fn error<T: Into<String>>(self, message: T) -> Diagnostic {
+        Diagnostic::spanned(self, Level::Error, message)
+    }
+
+    /// Creates a warning at this span. It is reported once `emit` is called.
+    pub fn warning<T: Into<String>>(self, message: T) -> Diagnostic {
+        Diagnostic::spanned(self, Level::Warning, message)
+    }
+
+    /// Creates a note at this span. It is reported once `emit` is called.
+    pub fn note<T: Into<String>>(self, message: T) -> Diagnostic {
+        Diagnostic::spanned(self, Level::Note, message)
+    }
+
+    /// Creates a help message at this span. It is reported once `emit` is
+    /// called.
+    pub fn help<T: Into<String>>(self, message: T) -> Diagnostic {
+        Diagnostic::spanned(self, Level::Help, message)
+    }
+}
+
+/// A single token, or a delimited group of token trees, together with its
+/// span.
+#[derive(Clone, Debug)]
+#[unstable(feature = "proc_macro", issue = "38356")]
+pub struct TokenTree {
+    /// The span of the tree.
+    pub span: Span,
+    /// What kind of tree this is.
+    pub kind: TokenNode,
+}
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl From<TokenNode> for TokenTree {
+    fn from(kind: TokenNode) -> TokenTree {
+        TokenTree { span: Span::call_site(), kind: kind }
+    }
+}
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl fmt::Display for TokenTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        TokenStream::from(self.clone()).fmt(f)
+    }
+}
+
+/// The kinds of `TokenTree`.
+#[derive(Clone, Debug)]
+#[unstable(feature = "proc_macro", issue = "38356")]
+pub enum TokenNode {
+    /// A token stream surrounded by delimiters.
+    Group(Delimiter, TokenStream),
+    /// An identifier, a keyword, a lifetime such as `'a`, or `_`.
+    Term(Term),
+    /// A punctuation character such as `+` or `#`. Operators of several
+    /// characters, like `::` or `+=`, are a sequence of `Op`s which are
+    /// `Spacing::Joint` to the next one.
+    Op(char, Spacing),
+    /// A literal, such as `1`, `"hello"` or `b'a'`.
+    Literal(Literal),
+}
+
+/// The delimiters of a `TokenNode::Group`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[unstable(feature = "proc_macro", issue = "38356")]
+pub enum Delimiter {
+    /// `( ... )`
+    Parenthesis,
+    /// `{ ... }`
+    Brace,
+    /// `[ ... ]`
+    Bracket,
+    /// An invisible delimiter, which groups the tokens of something the
+    /// compiler parsed already, such as an expression passed to `macro_rules!`.
+    None,
+}
+
+impl Delimiter {
+    fn from_internal(delim: token::DelimToken) -> Delimiter {
+        match delim {
+            token::Paren => Delimiter::Parenthesis,
+            token::Brace => Delimiter::Brace,
+            token::Bracket => Delimiter::Bracket,
+            token::NoDelim => Delimiter::None,
+        }
+    }
+
+    fn to_internal(self) -> token::DelimToken {
+        match self {
+            Delimiter::Parenthesis => token::Paren,
+            Delimiter::Brace => token::Brace,
+            Delimiter::Bracket => token::Bracket,
+            Delimiter::None => token::NoDelim,
+        }
+    }
+}
+
+/// Whether an `Op` is followed by another `Op` which is part of the same
+/// operator.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[unstable(feature = "proc_macro", issue = "38356")]
+pub enum Spacing {
+    /// The `Op` ends an operator.
+    Alone,
+    /// The `Op` is joined to the next one, as the `:`s of `::` are.
+    Joint,
+}
+
+/// An identifier, a keyword, a lifetime such as `'a`, or `_`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[unstable(feature = "proc_macro", issue = "38356")]
+pub struct Term(Symbol);
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl Term {
+    /// Creates a term with the given text.
+    pub fn intern(string: &str) -> Term {
+        Term(Symbol::intern(string))
+    }
+
+    /// The text of the term.
+    pub fn as_str(&self) -> &str {
+        // Interned strings are never freed while the compiler runs, so they
+        // outlive the `Term`.
+        unsafe { &*(&*self.0.as_str() as *const str) }
+    }
+
+    fn to_internal(self) -> token::Token {
+        let ident = ast::Ident::with_empty_ctxt(self.0);
+        match self.as_str() {
+            "_" => token::Underscore,
+            s if s.starts_with('\'') => token::Lifetime(ident),
+            _ => token::Ident(ident),
+        }
+    }
+}
+
+/// A literal, such as `1`, `"hello"` or `b'a'`.
+#[derive(Clone, Debug)]
+#[unstable(feature = "proc_macro", issue = "38356")]
+pub struct Literal(token::Token);
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl Literal {
+    /// An unsuffixed integer literal.
+    pub fn integer(n: u64) -> Literal {
+        Literal::new(token::Integer(Symbol::intern(&n.to_string())))
+    }
+
+    /// An unsuffixed floating point literal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is negative, infinite or NaN, which can't be written as
+    /// a single literal.
+    pub fn float(n: f64) -> Literal {
+        if !n.is_finite() || n.is_sign_negative() {
+            panic!("`{:?}` cannot be written as a float literal", n);
+        }
+        Literal::new(token::Float(Symbol::intern(&format!("{:?}", n))))
+    }
+
+    /// A string literal.
+    pub fn string(string: &str) -> Literal {
+        let escaped: String = string.chars().flat_map(char::escape_default).collect();
+        Literal::new(token::Str_(Symbol::intern(&escaped)))
+    }
+
+    /// A character literal.
+    pub fn character(c: char) -> Literal {
+        let escaped: String = c.escape_default().collect();
+        Literal::new(token::Char(Symbol::intern(&escaped)))
+    }
+
+    /// A byte string literal.
+    pub fn byte_string(bytes: &[u8]) -> Literal {
+        let escaped: String = bytes.iter().cloned()
+                                   .flat_map(ascii::escape_default)
+                                   .map(|b| b as char)
+                                   .collect();
+        Literal::new(token::ByteStr(Symbol::intern(&escaped)))
+    }
+
+    fn new(lit: token::Lit) -> Literal {
+        Literal(token::Literal(lit, None))
+    }
+}
+
+#[unstable(feature = "proc_macro", issue = "38356")]
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&pprust::token_to_string(&self.0))
+    }
+}
+
+impl TokenTree {
+    fn from_internal(tree: tokenstream::TokenTree) -> Vec<TokenTree> {
+        let (span, token) = match tree {
+            tokenstream::TokenTree::Delimited(span, delimited) => {
+                let delimiter = Delimiter::from_internal(delimited.delim);
+                let stream = TokenStream { inner: delimited.tts.into() };
+                return vec![TokenTree {
+                    span: Span(span),
+                    kind: TokenNode::Group(delimiter, stream),
+                }];
+            }
+            tokenstream::TokenTree::Token(span, token) => (span, token),
+        };
+
+        let kind = match token {
+            token::Eq | token::Lt | token::Le | token::EqEq | token::Ne | token::Ge |
+            token::Gt | token::AndAnd | token::OrOr | token::Not | token::Tilde |
+            token::BinOp(_) | token::BinOpEq(_) | token::At | token::Dot | token::DotDot |
+            token::DotDotDot | token::Comma | token::Semi | token::Colon | token::ModSep |
+            token::RArrow | token::LArrow | token::FatArrow | token::Pound | token::Dollar |
+            token::Question => return split_op(span, &pprust::token_to_string(&token)),
+            token::Ident(ident) | token::Lifetime(ident) => TokenNode::Term(Term(ident.name)),
+            token::Underscore => TokenNode::Term(Term::intern("_")),
+            token::Literal(lit, suffix) => TokenNode::Literal(Literal(token::Literal(lit, suffix))),
+            token::DocComment(comment) => return doc_comment(span, &comment.as_str()),
+            token::Interpolated(nt) => {
+                let stream = TokenStream { inner: nonterminal_stream(nt, span) };
+                TokenNode::Group(Delimiter::None, stream)
+            }
+            token::OpenDelim(..) | token::CloseDelim(..) | token::SubstNt(..) |
+            token::Whitespace | token::Comment | token::Shebang(..) | token::Eof => {
+                unreachable!()
+            }
+        };
+        vec![TokenTree { span: Span(span), kind: kind }]
+    }
+}
+
+/// Splits the operator `op` into an `Op` per character. Each gets its own
+/// span if the operator's span covers exactly its text.
+fn split_op(span: codemap::Span, op: &str) -> Vec<TokenTree> {
+    let exact = span.lo + codemap::BytePos(op.len() as u32) == span.hi;
+    let last = op.chars().count() - 1;
+    op.chars().enumerate().map(|(i, c)| {
+        let span = if exact {
+            let lo = span.lo + codemap::BytePos(i as u32);
+            codemap::Span { lo: lo, hi: lo + codemap::BytePos(1), ctxt: span.ctxt }
+        } else {
+            span
+        };
+        let spacing = if i < last { Spacing::Joint } else { Spacing::Alone };
+        TokenTree { span: Span(span), kind: TokenNode::Op(c, spacing) }
+    }).collect()
+}
+
+/// Appends the tokens of the joint operator characters `ops` to `streams`,
+/// gluing the longest operator possible into each token.
+fn glue_ops(ops: &str, span: codemap::Span, streams: &mut Vec<TokenStream_>) {
+    let chars: Vec<char> = ops.chars().collect();
+    let mut start = 0;
+    while start < chars.len() {
+        let max = cmp::min(chars.len() - start, 3);
+        let (len, token) = (1..max + 1).rev().filter_map(|len| {
+            let op: String = chars[start..start + len].iter().cloned().collect();
+            op_token(&op).map(|token| (len, token))
+        }).next().unwrap_or_else(|| panic!("`{}` is not an operator character", chars[start]));
+        streams.push(tokenstream::TokenTree::Token(span, token).into());
+        start += len;
+    }
+}
+
+fn op_token(op: &str) -> Option<token::Token> {
+    Some(match op {
+        "=" => token::Eq,
+        "<" => token::Lt,
+        "<=" => token::Le,
+        "==" => token::EqEq,
+        "!=" => token::Ne,
+        ">=" => token::Ge,
+        ">" => token::Gt,
+        "&&" => token::AndAnd,
+        "||" => token::OrOr,
+        "!" => token::Not,
+        "~" => token::Tilde,
+        "+" => token::BinOp(token::Plus),
+        "-" => token::BinOp(token::Minus),
+        "*" => token::BinOp(token::Star),
+        "/" => token::BinOp(token::Slash),
+        "%" => token::BinOp(token::Percent),
+        "^" => token::BinOp(token::Caret),
+        "&" => token::BinOp(token::And),
+        "|" => token::BinOp(token::Or),
+        "<<" => token::BinOp(token::Shl),
+        ">>" => token::BinOp(token::Shr),
+        "+=" => token::BinOpEq(token::Plus),
+        "-=" => token::BinOpEq(token::Minus),
+        "*=" => token::BinOpEq(token::Star),
+        "/=" => token::BinOpEq(token::Slash),
+        "%=" => token::BinOpEq(token::Percent),
+        "^=" => token::BinOpEq(token::Caret),
+        "&=" => token::BinOpEq(token::And),
+        "|=" => token::BinOpEq(token::Or),
+        "<<=" => token::BinOpEq(token::Shl),
+        ">>=" => token::BinOpEq(token::Shr),
+        "@" => token::At,
+        "." => token::Dot,
+        ".." => token::DotDot,
+        "..." => token::DotDotDot,
+        "," => token::Comma,
+        ";" => token::Semi,
+        ":" => token::Colon,
+        "::" => token::ModSep,
+        "->" => token::RArrow,
+        "<-" => token::LArrow,
+        "=>" => token::FatArrow,
+        "#" => token::Pound,
+        "$" => token::Dollar,
+        "?" => token::Question,
+        _ => return None,
+    })
+}
+
+/// The trees of the attribute `#[doc = "..."]` (or `#![doc = "..."]`) which
+/// the doc comment `comment` stands for.
+fn doc_comment(span: codemap::Span, comment: &str) -> Vec<TokenTree> {
+    let tree = |kind| TokenTree { span: Span(span), kind: kind };
+    let text = comments::strip_doc_comment_decoration(comment);
+    let attr = vec![
+        tree(TokenNode::Term(Term::intern("doc"))),
+        tree(TokenNode::Op('=', Spacing::Alone)),
+        tree(TokenNode::Literal(Literal::string(&text))),
+    ];
+    let mut trees = vec![tree(TokenNode::Op('#', Spacing::Alone))];
+    if let ast::AttrStyle::Inner = comments::doc_comment_style(comment) {
+        trees.push(tree(TokenNode::Op('!', Spacing::Alone)));
+    }
+    trees.push(tree(TokenNode::Group(Delimiter::Bracket, attr.into_iter().collect())));
+    trees
+}
+
+/// The tokens of an already parsed nonterminal. Items keep the spans of their
+/// source code; other nonterminals are pretty-printed and reparsed, so all of
+/// their tokens get the span of the nonterminal.
+fn nonterminal_stream(nt: Rc<token::Nonterminal>, span: codemap::Span) -> TokenStream_ {
+    __internal::with_parse_sess(|sess| match *nt {
+        token::NtItem(ref item) => stream_for_item(&Annotatable::Item(item.clone()), sess),
+        token::NtTT(ref tree) => tree.clone().into(),
+        _ => {
+            let text = pprust::token_to_string(&token::Interpolated(nt.clone()));
+            let name = "<macro expansion>".to_string();
+            parse::parse_stream_from_source_str(name, text, sess)
+                .with_spans_from(&TokenStream_::empty(), span)
+        }
+    })
+}
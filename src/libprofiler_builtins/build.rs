@@ -0,0 +1,73 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Compiles the profiler part of the `compiler-rt` library, which
+//! instrumented binaries (`-Z profile-generate`, `-Z instrument-coverage`) use
+//! to write their profiles.
+//!
+//! Like the builtins in `libcompiler_builtins`, the sources are compiled
+//! directly with the `gcc` crate rather than with compiler-rt's build system.
+
+extern crate build_helper;
+extern crate gcc;
+
+use std::env;
+use std::path::Path;
+use build_helper::native_lib_boilerplate;
+
+fn main() {
+    let target = env::var("TARGET").expect("TARGET was not set");
+
+    let native = match native_lib_boilerplate("compiler-rt", "profiler", "profiler-rt", ".") {
+        Ok(native) => native,
+        _ => return,
+    };
+
+    let cfg = &mut gcc::Config::new();
+    cfg.out_dir(&native.out_dir);
+
+    let mut sources = vec!["GCDAProfiling.c",
+                           "InstrProfiling.c",
+                           "InstrProfilingBuffer.c",
+                           "InstrProfilingFile.c",
+                           "InstrProfilingMerge.c",
+                           "InstrProfilingMergeFile.c",
+                           "InstrProfilingNameVar.c",
+                           "InstrProfilingPlatformDarwin.c",
+                           "InstrProfilingPlatformLinux.c",
+                           "InstrProfilingPlatformOther.c",
+                           "InstrProfilingRuntime.cc",
+                           "InstrProfilingUtil.c",
+                           "InstrProfilingValue.c",
+                           "InstrProfilingWriter.c"];
+
+    if target.contains("msvc") {
+        // Don't pull in extra libraries on MSVC
+        cfg.flag("/Zl");
+        sources.push("WindowsMMap.c");
+        cfg.define("strdup", Some("_strdup"));
+        cfg.define("open", Some("_open"));
+        cfg.define("fdopen", Some("_fdopen"));
+    } else {
+        // Turn off various features of gcc and such, mostly copying
+        // compiler-rt's build system already
+        cfg.flag("-fno-builtin");
+        cfg.flag("-fvisibility=hidden");
+        cfg.flag("-fomit-frame-pointer");
+        cfg.flag("-ffreestanding");
+        cfg.define("VISIBILITY_HIDDEN", None);
+    }
+
+    for src in sources {
+        cfg.file(Path::new("../compiler-rt/lib/profile").join(src));
+    }
+
+    cfg.compile("libprofiler-rt.a");
+}
@@ -9,20 +9,26 @@
 // except according to those terms.
 
 use hir::def_id::DefId;
+use util::profiling::{self, Activity};
 use super::DepNode;
 use super::thread::{DepGraphThreadData, DepMessage};
 
 pub struct DepTask<'graph> {
     data: &'graph DepGraphThreadData,
     key: Option<DepNode<DefId>>,
+    _activity: Option<Activity>,
 }
 
 impl<'graph> DepTask<'graph> {
     pub fn new(data: &'graph DepGraphThreadData, key: DepNode<DefId>)
                -> Option<DepTask<'graph>> {
+        // Tasks are profiled even when the dep-graph is not being built.
+        let activity = profiling::activity("task", || format!("{:?}", key));
         if data.is_enqueue_enabled() {
             data.enqueue(DepMessage::PushTask(key.clone()));
-            Some(DepTask { data: data, key: Some(key) })
+            Some(DepTask { data: data, key: Some(key), _activity: activity })
+        } else if activity.is_some() {
+            Some(DepTask { data: data, key: None, _activity: activity })
         } else {
             None
         }
@@ -31,8 +37,8 @@ impl<'graph> DepTask<'graph> {
 
 impl<'graph> Drop for DepTask<'graph> {
     fn drop(&mut self) {
-        if self.data.is_enqueue_enabled() {
-            self.data.enqueue(DepMessage::PopTask(self.key.take().unwrap()));
+        if let Some(key) = self.key.take() {
+            self.data.enqueue(DepMessage::PopTask(key));
         }
     }
 }
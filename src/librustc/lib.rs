@@ -115,6 +115,7 @@ pub mod util {
     pub mod ppaux;
     pub mod nodemap;
     pub mod fs;
+    pub mod profiling;
 }
 
 // A private module so that macro-expanded idents like
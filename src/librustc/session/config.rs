@@ -44,7 +44,7 @@ use std::hash::Hasher;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashSet;
 use std::iter::FromIterator;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub struct Config {
     pub target: Target,
@@ -60,6 +60,16 @@ pub enum Sanitizer {
     Thread,
 }
 
+/// What `-Z apply-suggestions` does with the applicable suggestions collected
+/// during a compilation.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ApplySuggestions {
+    /// Rewrite the affected source files in place.
+    InPlace,
+    /// Print a unified diff of the changes to stdout.
+    Diff,
+}
+
 #[derive(Clone, Copy, PartialEq, Hash)]
 pub enum OptLevel {
     No, // -O0
@@ -135,6 +145,7 @@ impl OutputType {
 pub enum ErrorOutputType {
     HumanReadable(ColorConfig),
     Json,
+    Short(ColorConfig),
 }
 
 impl Default for ErrorOutputType {
@@ -340,6 +351,7 @@ pub enum PrintRequest {
     RelocationModels,
     CodeModels,
     TargetSpec,
+    CrateGraph,
 }
 
 pub enum Input {
@@ -654,11 +666,13 @@ macro_rules! options {
             Some(::rustc_back::LinkerFlavor::one_of());
         pub const parse_optimization_fuel: Option<&'static str> =
             Some("crate=integer");
+        pub const parse_apply_suggestions: Option<&'static str> =
+            Some("either `in-place` or `diff`");
     }
 
     #[allow(dead_code)]
     mod $mod_set {
-        use super::{$struct_name, Passes, SomePasses, AllPasses, Sanitizer};
+        use super::{$struct_name, Passes, SomePasses, AllPasses, Sanitizer, ApplySuggestions};
         use rustc_back::{LinkerFlavor, PanicStrategy};
 
         $(
@@ -780,6 +794,16 @@ macro_rules! options {
             true
         }
 
+        fn parse_apply_suggestions(slot: &mut Option<ApplySuggestions>, v: Option<&str>)
+                                   -> bool {
+            match v {
+                Some("in-place") => *slot = Some(ApplySuggestions::InPlace),
+                Some("diff") => *slot = Some(ApplySuggestions::Diff),
+                _ => return false,
+            }
+            true
+        }
+
         fn parse_sanitizer(slote: &mut Option<Sanitizer>, v: Option<&str>) -> bool {
             match v {
                 Some("address") => *slote = Some(Sanitizer::Address),
@@ -904,6 +928,9 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "The output of `-Z time-llvm-passes` will only reflect timings of \
          re-translated modules when used with incremental compilation" )],
         "measure time of each LLVM pass"),
+    self_profile: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "write a trace of the passes, dep-graph tasks and codegen threads to this file, \
+         in the Chrome trace_event format"),
     input_stats: bool = (false, parse_bool, [UNTRACKED],
         "gather statistics about the input"),
     trans_stats: bool = (false, parse_bool, [UNTRACKED_WITH_WARNING(true,
@@ -1017,6 +1044,19 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
           "pass `-install_name @rpath/...` to the macOS linker"),
     sanitizer: Option<Sanitizer> = (None, parse_sanitizer, [TRACKED],
                                    "Use a sanitizer"),
+    // Profile-guided optimization is unstable, like the profiler runtime it
+    // needs, which is only built with `profiler = true` in config.toml. Under
+    // `-C` the options would be stable as soon as they were added.
+    profile_generate: Option<String> = (None, parse_opt_string, [TRACKED],
+        "instrument the generated code to write a profile to the given directory \
+         when it runs (needs the profiler runtime)"),
+    profile_use: Option<String> = (None, parse_opt_string, [TRACKED],
+        "optimize the generated code using the given merged `.profdata` file"),
+    instrument_coverage: bool = (false, parse_bool, [TRACKED],
+        "instrument the generated code with counters of how often each source \
+         region runs, for `llvm-cov` (needs the profiler runtime)"),
+    thinlto: bool = (false, parse_bool, [TRACKED],
+        "optimize codegen units with ThinLTO, and with `-C lto`, upstream crates too"),
     linker_flavor: Option<LinkerFlavor> = (None, parse_linker_flavor, [UNTRACKED],
                                            "Linker flavor"),
     fuel: Option<(String, u64)> = (None, parse_optimization_fuel, [TRACKED],
@@ -1033,6 +1073,9 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "a single extra argument to prepend the linker invocation (can be used several times)"),
     pre_link_args: Option<Vec<String>> = (None, parse_opt_list, [UNTRACKED],
         "extra arguments to prepend to the linker invocation (space separated)"),
+    apply_suggestions: Option<ApplySuggestions> = (None, parse_apply_suggestions, [UNTRACKED],
+        "apply the suggestions known to be correct to the source files (`in-place`) \
+         or print them as a unified diff (`diff`)"),
 }
 
 pub fn default_lib_output() -> CrateType {
@@ -1213,6 +1256,7 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
                               "relocation-models", "code-models"];
     if nightly_options::is_nightly_build() {
         print_opts.push("target-spec-json");
+        print_opts.push("crate-graph");
     }
 
     vec![
@@ -1270,7 +1314,7 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
         opt::multi("Z", "", "Set internal debugging options", "FLAG"),
         opt::opt_s("", "error-format",
                       "How errors and other messages are produced",
-                      "human|json"),
+                      "human|json|short"),
         opt::opt_s("", "color", "Configure coloring of output:
                                  auto   = colorize, if output goes to a tty (default);
                                  always = always colorize output;
@@ -1339,13 +1383,14 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
         match matches.opt_str("error-format").as_ref().map(|s| &s[..]) {
             Some("human")   => ErrorOutputType::HumanReadable(color),
             Some("json") => ErrorOutputType::Json,
+            Some("short") => ErrorOutputType::Short(color),
 
             None => ErrorOutputType::HumanReadable(color),
 
             Some(arg) => {
                 early_error(ErrorOutputType::HumanReadable(color),
-                            &format!("argument for --error-format must be human or json (instead \
-                                      was `{}`)",
+                            &format!("argument for --error-format must be human, json or \
+                                      short (instead was `{}`)",
                                      arg))
             }
         }
@@ -1566,17 +1611,39 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
             "code-models" => PrintRequest::CodeModels,
             "target-spec-json" if nightly_options::is_unstable_enabled(matches)
                 => PrintRequest::TargetSpec,
+            "crate-graph" if nightly_options::is_unstable_enabled(matches)
+                => PrintRequest::CrateGraph,
             req => {
                 early_error(error_format, &format!("unknown print request `{}`", req))
             }
         }
     }));
 
+    // The crate graph is only known once compilation has loaded the crates,
+    // while the other requests are printed instead of compiling.
+    if prints.contains(&PrintRequest::CrateGraph) &&
+       prints.iter().any(|req| *req != PrintRequest::CrateGraph) {
+        early_error(error_format, "`--print crate-graph` cannot be combined with other \
+                                   print requests");
+    }
+
     if !cg.remark.is_empty() && debuginfo == NoDebugInfo {
         early_warn(error_format, "-C remark will not show source locations without \
                                 --debuginfo");
     }
 
+    if debugging_opts.profile_generate.is_some() && debugging_opts.profile_use.is_some() {
+        early_error(error_format, "options `-Z profile-generate` and `-Z profile-use` \
+                                   are incompatible");
+    }
+
+    if let Some(ref path) = debugging_opts.profile_use {
+        if !Path::new(path).exists() {
+            early_error(error_format, &format!("file `{}` passed to `-Z profile-use` \
+                                                does not exist", path));
+        }
+    }
+
     let mut externs = BTreeMap::new();
     for arg in &matches.opt_strs("extern") {
         let mut parts = arg.splitn(2, '=');
@@ -2447,6 +2514,8 @@ mod tests {
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.time_llvm_passes = true;
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.self_profile = Some(String::from("trace.json"));
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.input_stats = true;
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.trans_stats = true;
@@ -2554,5 +2623,21 @@ mod tests {
         opts = reference.clone();
         opts.debugging_opts.mir_opt_level = 3;
         assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.profile_generate = Some(String::from("abc"));
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.profile_use = Some(String::from("abc"));
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.instrument_coverage = true;
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.thinlto = true;
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
     }
 }
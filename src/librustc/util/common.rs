@@ -19,6 +19,8 @@ use std::iter::repeat;
 use std::path::Path;
 use std::time::{Duration, Instant};
 
+use util::profiling;
+
 // The name of the associated type for `Fn` return types
 pub const FN_OUTPUT_NAME: &'static str = "Output";
 
@@ -45,6 +47,7 @@ pub fn set_time_depth(depth: usize) {
 pub fn time<T, F>(do_it: bool, what: &str, f: F) -> T where
     F: FnOnce() -> T,
 {
+    let _activity = profiling::activity("pass", || what.to_string());
     if !do_it { return f(); }
 
     let old = TIME_DEPTH.with(|slot| {
@@ -0,0 +1,151 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Self-profiling (`-Z self-profile`).
+//!
+//! Records when passes, dep-graph tasks and the work of the codegen threads
+//! begin and end. The events are written as a trace in the Chrome
+//! `trace_event` format, which can be loaded in `chrome://tracing`, and
+//! `src/etc/summarize-self-profile.py` aggregates the traces of several runs.
+//!
+//! A thread only records events once it has been given the profiler with
+//! `set_current`, so threads which take part in the compilation should be
+//! handed the result of `current()` from the thread which spawns them.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serialize::json::as_json;
+
+struct Event {
+    category: &'static str,
+    name: String,
+    // `B` when the activity begins, `E` when it ends.
+    phase: char,
+    time: Duration,
+    thread: usize,
+}
+
+pub struct Profiler {
+    start: Instant,
+    events: Mutex<Vec<Event>>,
+    // The names of the threads which recorded events, by thread id.
+    threads: Mutex<Vec<String>>,
+}
+
+thread_local!(static PROFILER: RefCell<Option<(Arc<Profiler>, usize)>> = RefCell::new(None));
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler {
+            start: Instant::now(),
+            events: Mutex::new(vec![]),
+            threads: Mutex::new(vec![]),
+        }
+    }
+
+    fn record(&self, thread: usize, category: &'static str, name: String, phase: char) {
+        let time = self.start.elapsed();
+        self.events.lock().unwrap().push(Event {
+            category: category,
+            name: name,
+            phase: phase,
+            time: time,
+            thread: thread,
+        });
+    }
+
+    /// Writes the events recorded so far to `path` as a Chrome trace.
+    pub fn write_trace(&self, path: &Path) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "{{\"traceEvents\": [")?;
+        for (tid, name) in self.threads.lock().unwrap().iter().enumerate() {
+            writeln!(file,
+                     "{{\"name\": \"thread_name\", \"ph\": \"M\", \"pid\": 0, \"tid\": {}, \
+                      \"args\": {{\"name\": {}}}}},",
+                     tid, as_json(name))?;
+        }
+        let events = self.events.lock().unwrap();
+        for (i, event) in events.iter().enumerate() {
+            let micros = event.time.as_secs() as f64 * 1_000_000.0 +
+                         event.time.subsec_nanos() as f64 / 1_000.0;
+            writeln!(file,
+                     "{{\"name\": {}, \"cat\": \"{}\", \"ph\": \"{}\", \"ts\": {:.3}, \
+                      \"pid\": 0, \"tid\": {}}}{}",
+                     as_json(&event.name), event.category, event.phase, micros, event.thread,
+                     if i + 1 < events.len() { "," } else { "" })?;
+        }
+        writeln!(file, "], \"displayTimeUnit\": \"ms\"}}")?;
+        file.flush()
+    }
+}
+
+/// The profiler of the current thread, if it is being profiled.
+pub fn current() -> Option<Arc<Profiler>> {
+    PROFILER.with(|p| p.borrow().as_ref().map(|&(ref profiler, _)| profiler.clone()))
+}
+
+/// Starts (or with `None`, stops) recording the events of the current
+/// thread with `profiler`.
+pub fn set_current(profiler: Option<Arc<Profiler>>) {
+    let profiler = profiler.map(|profiler| {
+        let thread = {
+            let mut threads = profiler.threads.lock().unwrap();
+            let name = thread::current().name().map(|name| name.to_string())
+                                               .unwrap_or_else(|| {
+                format!("thread {}", threads.len())
+            });
+            threads.push(name);
+            threads.len() - 1
+        };
+        (profiler, thread)
+    });
+    PROFILER.with(|p| *p.borrow_mut() = profiler);
+}
+
+/// An activity being profiled, which ends when this is dropped.
+pub struct Activity {
+    profiler: Arc<Profiler>,
+    thread: usize,
+    category: &'static str,
+    name: String,
+}
+
+/// Records the beginning of an activity if the current thread is being
+/// profiled. The name is only computed then.
+pub fn activity<F>(category: &'static str, name: F) -> Option<Activity>
+    where F: FnOnce() -> String
+{
+    PROFILER.with(|p| {
+        p.borrow().as_ref().map(|&(ref profiler, thread)| {
+            let name = name();
+            profiler.record(thread, category, name.clone(), 'B');
+            Activity {
+                profiler: profiler.clone(),
+                thread: thread,
+                category: category,
+                name: name,
+            }
+        })
+    })
+}
+
+impl Drop for Activity {
+    fn drop(&mut self) {
+        let name = mem::replace(&mut self.name, String::new());
+        self.profiler.record(self.thread, self.category, name, 'E');
+    }
+}
@@ -15,7 +15,7 @@ use rustc_data_structures::stable_hasher::StableHasher;
 use rustc_mir as mir;
 use rustc::session::{Session, CompileResult, compile_result_from_err_count};
 use rustc::session::config::{self, Input, OutputFilenames, OutputType,
-                             OutputTypes};
+                             OutputTypes, PrintRequest};
 use rustc::session::search_paths::PathKind;
 use rustc::lint;
 use rustc::middle::{self, dependency_format, stability, reachable};
@@ -24,6 +24,7 @@ use rustc::mir::transform::{MIR_CONST, MIR_VALIDATED, MIR_OPTIMIZED, Passes};
 use rustc::ty::{self, TyCtxt, Resolutions, GlobalArenas};
 use rustc::traits;
 use rustc::util::common::time;
+use rustc::util::profiling::{self, Profiler};
 use rustc::util::nodemap::NodeSet;
 use rustc::util::fs::rename_or_copy_remove;
 use rustc_borrowck as borrowck;
@@ -50,6 +51,7 @@ use std::io::{self, Write};
 use std::iter;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::Arc;
 use syntax::{ast, diagnostics, visit};
 use syntax::attr;
 use syntax::ext::base::ExtCtxt;
@@ -83,10 +85,14 @@ pub fn compile_input(sess: &Session,
         }}
     }
 
+    let _self_profile = sess.opts.debugging_opts.self_profile.as_ref().map(|path| {
+        SelfProfile::start(sess, PathBuf::from(path))
+    });
+
     // We need nested scopes here, because the intermediate results can keep
     // large chunks of memory alive and we want to free them as soon as
     // possible to keep the peak memory usage low
-    let (outputs, trans) = {
+    let (outputs, mut trans) = {
         let krate = match phase_1_parse_input(sess, input) {
             Ok(krate) => krate,
             Err(mut parse_error) => {
@@ -226,7 +232,7 @@ pub fn compile_input(sess: &Session,
         sess.code_stats.borrow().print_type_sizes();
     }
 
-    let phase5_result = phase_5_run_llvm_passes(sess, &trans, &outputs);
+    let phase5_result = phase_5_run_llvm_passes(sess, &mut trans, &outputs);
 
     controller_entry_point!(after_llvm,
                             sess,
@@ -268,6 +274,36 @@ pub fn anon_src() -> String {
     "<anon>".to_string()
 }
 
+/// Profiles the compilation with `-Z self-profile`, writing the trace once
+/// this is dropped, even if the compilation is aborted by a fatal error.
+struct SelfProfile<'a> {
+    sess: &'a Session,
+    path: PathBuf,
+    profiler: Arc<Profiler>,
+}
+
+impl<'a> SelfProfile<'a> {
+    fn start(sess: &'a Session, path: PathBuf) -> SelfProfile<'a> {
+        let profiler = Arc::new(Profiler::new());
+        profiling::set_current(Some(profiler.clone()));
+        SelfProfile {
+            sess: sess,
+            path: path,
+            profiler: profiler,
+        }
+    }
+}
+
+impl<'a> Drop for SelfProfile<'a> {
+    fn drop(&mut self) {
+        profiling::set_current(None);
+        if let Err(e) = self.profiler.write_trace(&self.path) {
+            self.sess.warn(&format!("failed to write the self-profile to `{}`: {}",
+                                    self.path.display(), e));
+        }
+    }
+}
+
 pub fn source_name(input: &Input) -> String {
     match *input {
         // FIXME (#9639): This needs to handle non-utf8 paths
@@ -781,6 +817,12 @@ pub fn phase_2_configure_and_expand<F>(sess: &Session,
         sess.parse_sess.span_diagnostic.abort_if_errors();
     }
 
+    // Name resolution loads the last crates, such as the panic runtime and
+    // the allocator.
+    if sess.opts.prints.contains(&PrintRequest::CrateGraph) {
+        println!("{}", cstore.crate_graph().pretty());
+    }
+
     // Needs to go *after* expansion to be able to check the results of macro expansion.
     time(time_passes, "complete gated feature checking", || {
         sess.track_errors(|| {
@@ -1071,7 +1113,7 @@ pub fn phase_4_translate_to_llvm<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
 /// Run LLVM itself, producing a bitcode file, assembly file or object file
 /// as a side effect.
 pub fn phase_5_run_llvm_passes(sess: &Session,
-                               trans: &trans::CrateTranslation,
+                               trans: &mut trans::CrateTranslation,
                                outputs: &OutputFilenames) -> CompileResult {
     if sess.opts.cg.no_integrated_as ||
         (sess.target.target.options.no_integrated_as &&
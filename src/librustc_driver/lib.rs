@@ -90,6 +90,7 @@ use std::default::Default;
 use std::env;
 use std::io::{self, Read, Write};
 use std::iter::repeat;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::PathBuf;
 use std::process;
 use std::rc::Rc;
@@ -110,6 +111,7 @@ pub mod driver;
 pub mod pretty;
 pub mod target_features;
 mod derive_registrar;
+mod suggestions;
 
 const BUG_REPORT_URL: &'static str = "https://github.com/rust-lang/rust/blob/master/CONTRIBUTING.\
                                       md#bug-reports";
@@ -142,7 +144,9 @@ pub fn run<F>(run_compiler: F) -> isize
                     Some(sess) => sess.fatal(&abort_msg(err_count)),
                     None => {
                         let emitter =
-                            errors::emitter::EmitterWriter::stderr(errors::ColorConfig::Auto, None);
+                            errors::emitter::EmitterWriter::stderr(errors::ColorConfig::Auto,
+                                                                   None,
+                                                                   false);
                         let handler = errors::Handler::with_emitter(true, false, Box::new(emitter));
                         handler.emit(&MultiSpan::new(),
                                      &abort_msg(err_count),
@@ -220,8 +224,27 @@ pub fn run_compiler<'a>(args: &[String],
 
     let plugins = sess.opts.debugging_opts.extra_plugins.clone();
     let control = callbacks.build_controller(&sess, &matches);
-    (driver::compile_input(&sess, &cstore, &input, &odir, &ofile, Some(plugins), &control),
-     Some(sess))
+
+    let mode = match sess.opts.debugging_opts.apply_suggestions {
+        Some(mode) => mode,
+        None => {
+            return (driver::compile_input(&sess, &cstore, &input, &odir, &ofile, Some(plugins),
+                                          &control),
+                    Some(sess))
+        }
+    };
+
+    // Most errors abort the compilation by unwinding, so catch that to apply
+    // the suggestions emitted up to that point before carrying on.
+    sess.diagnostic().record_applicable_suggestions();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        driver::compile_input(&sess, &cstore, &input, &odir, &ofile, Some(plugins), &control)
+    }));
+    suggestions::apply_suggestions(&sess, mode);
+    match result {
+        Ok(result) => (result, Some(sess)),
+        Err(payload) => panic::resume_unwind(payload),
+    }
 }
 
 // Extract output directory and file from matches.
@@ -581,7 +604,8 @@ impl RustcDefaultCalls {
                         odir: &Option<PathBuf>,
                         ofile: &Option<PathBuf>)
                         -> Compilation {
-        if sess.opts.prints.is_empty() {
+        // The crate graph is printed by the driver once all crates are loaded.
+        if sess.opts.prints.iter().all(|req| *req == PrintRequest::CrateGraph) {
             return Compilation::Continue;
         }
 
@@ -607,6 +631,7 @@ impl RustcDefaultCalls {
                 },
                 PrintRequest::Sysroot => println!("{}", sess.sysroot().display()),
                 PrintRequest::TargetSpec => println!("{}", sess.target.target.to_json().pretty()),
+                PrintRequest::CrateGraph => {}
                 PrintRequest::FileNames |
                 PrintRequest::CrateName => {
                     let input = match input {
@@ -1089,7 +1114,9 @@ pub fn monitor<F: FnOnce() + Send + 'static>(f: F) {
         // Thread panicked without emitting a fatal diagnostic
         if !value.is::<errors::FatalError>() {
             let emitter =
-                Box::new(errors::emitter::EmitterWriter::stderr(errors::ColorConfig::Auto, None));
+                Box::new(errors::emitter::EmitterWriter::stderr(errors::ColorConfig::Auto,
+                                                                None,
+                                                                false));
             let handler = errors::Handler::with_emitter(true, false, emitter);
 
             // a .span_bug or .bug call has already printed what
@@ -0,0 +1,365 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Support for `-Z apply-suggestions`.
+//!
+//! Every suggestion marked as applicable that was emitted during the
+//! compilation is turned into a set of edits against the source files it
+//! points into. A suggestion is applied as a whole or not at all: if any of
+//! its edits would overlap an edit that has already been accepted, the whole
+//! suggestion is skipped and left for a later run of the compiler, once the
+//! code has been re-checked with the other suggestions in place. Running the
+//! compiler again on its own output is therefore safe and eventually stops
+//! changing anything.
+
+use rustc::session::Session;
+use rustc::session::config::ApplySuggestions;
+use errors::CodeSuggestion;
+use syntax_pos::{FileMap, NO_EXPANSION};
+
+use std::cmp;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::rc::Rc;
+
+/// A replacement of the bytes `lo..hi` of a file with `text`.
+#[derive(Clone, PartialEq, Debug)]
+struct Edit {
+    lo: usize,
+    hi: usize,
+    text: String,
+}
+
+impl Edit {
+    fn overlaps(&self, other: &Edit) -> bool {
+        // Two insertions at the same point conflict as well, since the order
+        // in which they end up in the file would be arbitrary.
+        self.lo < other.hi && other.lo < self.hi || self.lo == other.lo
+    }
+}
+
+/// The edits accepted so far for a single file.
+struct FileEdits {
+    filemap: Rc<FileMap>,
+    edits: Vec<Edit>,
+}
+
+/// Applies or prints the applicable suggestions recorded by the session's
+/// diagnostic handler, depending on `mode`.
+pub fn apply_suggestions(sess: &Session, mode: ApplySuggestions) {
+    let suggestions = sess.diagnostic().take_applicable_suggestions();
+    if suggestions.is_empty() {
+        return;
+    }
+
+    let mut files: Vec<FileEdits> = Vec::new();
+    let mut applied = 0;
+    let mut skipped = 0;
+    for suggestion in &suggestions {
+        let edits = match suggestion_edits(sess, suggestion) {
+            Some(edits) => edits,
+            None => {
+                skipped += 1;
+                continue
+            }
+        };
+        if accept(&mut files, edits) {
+            applied += 1;
+        } else {
+            skipped += 1;
+        }
+    }
+
+    let mut files_changed = 0;
+    for file in &mut files {
+        file.edits.sort_by(|a, b| a.lo.cmp(&b.lo));
+        let src = file.filemap.src.as_ref().unwrap();
+        let result = match mode {
+            ApplySuggestions::InPlace => write_file(&file.filemap.name, src, &file.edits),
+            ApplySuggestions::Diff => {
+                print_diff(&file.filemap.name, src, &file.edits);
+                Ok(())
+            }
+        };
+        match result {
+            Ok(()) => files_changed += 1,
+            Err(msg) => sess.err(&msg),
+        }
+    }
+
+    let verb = match mode {
+        ApplySuggestions::InPlace => "applied",
+        ApplySuggestions::Diff => "proposed",
+    };
+    sess.note_without_error(&format!("{} {} suggestion{} to {} file{}",
+                                     verb,
+                                     applied,
+                                     if applied == 1 { "" } else { "s" },
+                                     files_changed,
+                                     if files_changed == 1 { "" } else { "s" }));
+    if skipped > 0 {
+        sess.note_without_error(&format!("skipped {} suggestion{} that could not be applied \
+                                          together with the others; run the compiler again \
+                                          to pick them up",
+                                         skipped,
+                                         if skipped == 1 { "" } else { "s" }));
+    }
+}
+
+/// Translates a suggestion into edits against the one file it points into.
+/// Returns `None` if the suggestion cannot be applied mechanically.
+fn suggestion_edits(sess: &Session, suggestion: &CodeSuggestion)
+                    -> Option<(Rc<FileMap>, Vec<Edit>)> {
+    let codemap = sess.codemap();
+    let mut filemap: Option<Rc<FileMap>> = None;
+    let mut edits = Vec::new();
+    for part in &suggestion.substitution_parts {
+        // Only a single, unambiguous replacement can be applied, and only to
+        // code that was written by hand.
+        if part.substitutions.len() != 1 || part.span.ctxt != NO_EXPANSION ||
+           part.span.hi < part.span.lo {
+            return None;
+        }
+        let lo = codemap.lookup_byte_offset(part.span.lo);
+        let hi = codemap.lookup_byte_offset(part.span.hi);
+        if !Rc::ptr_eq(&lo.fm, &hi.fm) {
+            return None;
+        }
+        let fm = lo.fm;
+        if !fm.is_real_file() || fm.is_imported() || fm.name_was_remapped {
+            return None;
+        }
+        match filemap {
+            Some(ref other) if !Rc::ptr_eq(other, &fm) => return None,
+            _ => {}
+        }
+        edits.push(Edit {
+            lo: lo.pos.0 as usize,
+            hi: hi.pos.0 as usize,
+            text: part.substitutions[0].clone(),
+        });
+        filemap = Some(fm);
+    }
+    filemap.map(|fm| (fm, edits))
+}
+
+/// Adds the edits of a suggestion to the accepted ones, unless they conflict
+/// with each other or with edits accepted before.
+fn accept(files: &mut Vec<FileEdits>, (filemap, edits): (Rc<FileMap>, Vec<Edit>)) -> bool {
+    let index = match files.iter().position(|f| Rc::ptr_eq(&f.filemap, &filemap)) {
+        Some(index) => index,
+        None => {
+            files.push(FileEdits { filemap: filemap, edits: Vec::new() });
+            files.len() - 1
+        }
+    };
+    let accepted = &mut files[index].edits;
+
+    let mut new_edits: Vec<Edit> = Vec::new();
+    for edit in edits {
+        // The same suggestion is often reported more than once, for example
+        // when a macro invocation is expanded several times.
+        if accepted.contains(&edit) || new_edits.contains(&edit) {
+            continue
+        }
+        if accepted.iter().chain(&new_edits).any(|e| e.overlaps(&edit)) {
+            return false;
+        }
+        new_edits.push(edit);
+    }
+    accepted.extend(new_edits);
+    true
+}
+
+fn apply(src: &str, edits: &[Edit]) -> String {
+    let mut out = String::with_capacity(src.len());
+    let mut pos = 0;
+    for edit in edits {
+        out.push_str(&src[pos..edit.lo]);
+        out.push_str(&edit.text);
+        pos = edit.hi;
+    }
+    out.push_str(&src[pos..]);
+    out
+}
+
+fn write_file(path: &str, src: &str, edits: &[Edit]) -> Result<(), String> {
+    let mut contents = String::new();
+    File::open(path).and_then(|mut f| f.read_to_string(&mut contents)).map_err(|e| {
+        format!("failed to read `{}` to apply suggestions: {}", path, e)
+    })?;
+
+    // The code map does not keep the byte order mark, so put it back.
+    let bom = if contents.starts_with("\u{feff}") { "\u{feff}" } else { "" };
+    if &contents[bom.len()..] != src {
+        return Err(format!("`{}` was modified during compilation, \
+                            not applying suggestions to it", path));
+    }
+
+    let new = format!("{}{}", bom, apply(src, edits));
+    File::create(path).and_then(|mut f| f.write_all(new.as_bytes())).map_err(|e| {
+        format!("failed to write `{}` to apply suggestions: {}", path, e)
+    })
+}
+
+const CONTEXT: usize = 3;
+
+/// Splits `s` into lines, keeping the line terminators.
+fn lines(s: &str) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for (i, _) in s.match_indices('\n') {
+        lines.push(&s[start..i + 1]);
+        start = i + 1;
+    }
+    if start < s.len() {
+        lines.push(&s[start..]);
+    }
+    lines
+}
+
+/// A run of consecutive lines touched by edits, along with their new text.
+struct Chunk<'a> {
+    start: usize,
+    end: usize,
+    new: Vec<&'a str>,
+}
+
+fn print_line(out: &mut String, prefix: char, line: &str) {
+    out.push(prefix);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+fn print_diff(path: &str, src: &str, edits: &[Edit]) {
+    print!("{}", diff(path, src, edits));
+}
+
+/// Renders the edits as a unified diff of `src`.
+fn diff(path: &str, src: &str, edits: &[Edit]) -> String {
+    let old = lines(src);
+    let mut line_starts = Vec::with_capacity(old.len() + 1);
+    let mut pos = 0;
+    for line in &old {
+        line_starts.push(pos);
+        pos += line.len();
+    }
+    line_starts.push(pos);
+    let line_of = |pos: usize| match line_starts.binary_search(&pos) {
+        Ok(line) => line,
+        Err(line) => line - 1,
+    };
+
+    // Group the edits into chunks of whole lines, merging edits that touch
+    // the same or adjacent lines.
+    let mut groups: Vec<(usize, usize, Vec<Edit>)> = Vec::new();
+    let last_line = old.len().saturating_sub(1);
+    for edit in edits {
+        let start = cmp::min(line_of(edit.lo), last_line);
+        let end = cmp::min(line_of(cmp::max(edit.hi, edit.lo + 1) - 1), last_line) + 1;
+        if let Some(last) = groups.last_mut() {
+            if start < last.1 {
+                last.1 = cmp::max(last.1, end);
+                last.2.push(edit.clone());
+                continue
+            }
+        }
+        groups.push((start, end, vec![edit.clone()]));
+    }
+
+    let new_texts: Vec<String> = groups.iter().map(|&(start, end, ref edits)| {
+        let base = line_starts[start];
+        let text = &src[base..line_starts[cmp::min(end, old.len())]];
+        let edits: Vec<Edit> = edits.iter().map(|e| {
+            Edit { lo: e.lo - base, hi: e.hi - base, text: e.text.clone() }
+        }).collect();
+        apply(text, &edits)
+    }).collect();
+    let chunks: Vec<Chunk> = groups.iter().zip(&new_texts).map(|(&(start, end, _), text)| {
+        Chunk { start: start, end: cmp::min(end, old.len()), new: lines(text) }
+    }).collect();
+
+    let mut out = format!("--- {}\n+++ {}\n", path, path);
+    let mut delta: isize = 0;
+    let mut i = 0;
+    while i < chunks.len() {
+        // Chunks whose context would overlap are shown in a single hunk.
+        let mut j = i + 1;
+        while j < chunks.len() && chunks[j].start - chunks[j - 1].end <= 2 * CONTEXT {
+            j += 1;
+        }
+        let hunk = &chunks[i..j];
+        let old_start = hunk[0].start.saturating_sub(CONTEXT);
+        let old_end = cmp::min(hunk[hunk.len() - 1].end + CONTEXT, old.len());
+
+        let mut body = String::new();
+        let mut new_len = 0;
+        let mut line = old_start;
+        for chunk in hunk {
+            for l in &old[line..chunk.start] {
+                print_line(&mut body, ' ', l);
+                new_len += 1;
+            }
+            for l in &old[chunk.start..chunk.end] {
+                print_line(&mut body, '-', l);
+            }
+            for l in &chunk.new {
+                print_line(&mut body, '+', l);
+                new_len += 1;
+            }
+            line = chunk.end;
+        }
+        for l in &old[line..old_end] {
+            print_line(&mut body, ' ', l);
+            new_len += 1;
+        }
+
+        let old_len = old_end - old_start;
+        let new_start = (old_start as isize + delta) as usize;
+        out.push_str(&format!("@@ -{} +{} @@\n",
+                              range(old_start, old_len),
+                              range(new_start, new_len)));
+        out.push_str(&body);
+        delta += new_len as isize - old_len as isize;
+        i = j;
+    }
+    out
+}
+
+/// Formats a hunk range the way `diff -u` does.
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, len),
+    }
+}
+
+#[test]
+fn test_overlapping_suggestions() {
+    let edit = |lo, hi, text: &str| Edit { lo: lo, hi: hi, text: text.to_string() };
+    assert!(edit(0, 3, "a").overlaps(&edit(2, 5, "b")));
+    assert!(edit(4, 4, "a").overlaps(&edit(4, 4, "b")));
+    assert!(!edit(0, 3, "a").overlaps(&edit(3, 5, "b")));
+    assert!(!edit(3, 3, "a").overlaps(&edit(0, 3, "b")));
+}
+
+#[test]
+fn test_diff() {
+    let src = "fn main() {\n    let x = 1;\n    let y = 2;\n}";
+    let edits = vec![Edit { lo: 20, hi: 21, text: "y".to_string() }];
+    assert_eq!(apply(src, &edits), "fn main() {\n    let y = 1;\n    let y = 2;\n}");
+    assert_eq!(diff("main.rs", src, &edits),
+               "--- main.rs\n+++ main.rs\n@@ -1,4 +1,4 @@\n \
+                fn main() {\n-    let x = 1;\n+    let y = 1;\n     let y = 2;\n \
+                }\n\\ No newline at end of file\n");
+}
@@ -219,10 +219,24 @@ impl Diagnostic {
                 substitutions: vec![suggestion],
             }],
             msg: msg.to_owned(),
+            applicable: false,
         });
         self
     }
 
+    /// Same as `span_suggestion`, but for suggestions which are certain to be
+    /// what the user wants, so that they can be applied automatically with
+    /// `-Z apply-suggestions`.
+    pub fn span_applicable_suggestion(&mut self,
+                                      sp: Span,
+                                      msg: &str,
+                                      suggestion: String)
+                                      -> &mut Self {
+        self.span_suggestion(sp, msg, suggestion);
+        self.suggestions.last_mut().unwrap().applicable = true;
+        self
+    }
+
     pub fn span_suggestions(&mut self, sp: Span, msg: &str, suggestions: Vec<String>) -> &mut Self {
         self.suggestions.push(CodeSuggestion {
             substitution_parts: vec![Substitution {
@@ -230,6 +244,7 @@ impl Diagnostic {
                 substitutions: suggestions,
             }],
             msg: msg.to_owned(),
+            applicable: false,
         });
         self
     }
@@ -264,9 +279,9 @@ impl Diagnostic {
         self.children.extend(from.children.iter().cloned())
     }
 
-    /// Convenience function for internal use, clients should use one of the
-    /// public methods above.
-    fn sub(&mut self,
+    /// Adds a sub-diagnostic of the given level. Clients which know the level
+    /// beforehand should use one of the public methods above.
+    pub fn sub(&mut self,
            level: Level,
            message: &str,
            span: MultiSpan,
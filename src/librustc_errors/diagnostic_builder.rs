@@ -98,6 +98,7 @@ impl<'a> DiagnosticBuilder<'a> {
         }
 
         self.handler.emitter.borrow_mut().emit(&self);
+        self.handler.record_suggestions(&self.suggestions);
         self.cancel();
 
         if self.level == Level::Error {
@@ -151,6 +152,11 @@ impl<'a> DiagnosticBuilder<'a> {
                                     msg: &str,
                                     suggestion: String)
                                     -> &mut Self);
+    forward!(pub fn span_applicable_suggestion(&mut self,
+                                               sp: Span,
+                                               msg: &str,
+                                               suggestion: String)
+                                               -> &mut Self);
     forward!(pub fn span_suggestions(&mut self,
                                      sp: Span,
                                      msg: &str,
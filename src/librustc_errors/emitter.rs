@@ -37,6 +37,8 @@ impl Emitter for EmitterWriter {
 
         if let Some((sugg, rest)) = db.suggestions.split_first() {
             if rest.is_empty() &&
+               // short messages have no labels to display the suggestion in
+               !self.short_message &&
                // don't display multipart suggestions as labels
                sugg.substitution_parts.len() == 1 &&
                // don't display multi-suggestions as labels
@@ -65,11 +67,19 @@ impl Emitter for EmitterWriter {
         }
 
         self.fix_multispans_in_std_macros(&mut primary_span, &mut children);
-        self.emit_messages_default(&db.level,
-                                   &db.styled_message(),
-                                   &db.code,
-                                   &primary_span,
-                                   &children);
+        if self.short_message {
+            self.emit_messages_short(&db.level,
+                                     &db.styled_message(),
+                                     &db.code,
+                                     &primary_span,
+                                     &children);
+        } else {
+            self.emit_messages_default(&db.level,
+                                       &db.styled_message(),
+                                       &db.code,
+                                       &primary_span,
+                                       &children);
+        }
     }
 }
 
@@ -100,6 +110,7 @@ impl ColorConfig {
 pub struct EmitterWriter {
     dst: Destination,
     cm: Option<Rc<CodeMapper>>,
+    short_message: bool,
 }
 
 struct FileWithAnnotatedLines {
@@ -109,25 +120,37 @@ struct FileWithAnnotatedLines {
 }
 
 impl EmitterWriter {
-    pub fn stderr(color_config: ColorConfig, code_map: Option<Rc<CodeMapper>>) -> EmitterWriter {
+    /// Creates an emitter writing to stderr. With `short_message` set, every
+    /// diagnostic is condensed to `file:line:col: level[code]: message` lines
+    /// instead of being rendered with source snippets.
+    pub fn stderr(color_config: ColorConfig,
+                  code_map: Option<Rc<CodeMapper>>,
+                  short_message: bool)
+                  -> EmitterWriter {
         if color_config.use_color() {
             let dst = Destination::from_stderr();
             EmitterWriter {
                 dst: dst,
                 cm: code_map,
+                short_message: short_message,
             }
         } else {
             EmitterWriter {
                 dst: Raw(Box::new(io::stderr())),
                 cm: code_map,
+                short_message: short_message,
             }
         }
     }
 
-    pub fn new(dst: Box<Write + Send>, code_map: Option<Rc<CodeMapper>>) -> EmitterWriter {
+    pub fn new(dst: Box<Write + Send>,
+               code_map: Option<Rc<CodeMapper>>,
+               short_message: bool)
+               -> EmitterWriter {
         EmitterWriter {
             dst: Raw(dst),
             cm: code_map,
+            short_message: short_message,
         }
     }
 
@@ -1159,6 +1182,80 @@ impl EmitterWriter {
             }
         }
     }
+
+    /// The `file:line:col` of the primary span of `msp`, if it has one.
+    fn short_location(&self, msp: &MultiSpan) -> Option<String> {
+        let cm = match self.cm {
+            Some(ref cm) => cm,
+            None => return None,
+        };
+        match msp.primary_span() {
+            Some(span) if span != DUMMY_SP => {
+                let loc = cm.lookup_char_pos(span.lo);
+                Some(format!("{}:{}:{}", loc.file.name, loc.line, loc.col.0 + 1))
+            }
+            _ => None,
+        }
+    }
+
+    /// Appends one `location: level[code]: message` line to `buffer`.
+    fn short_message_line(&self,
+                          buffer: &mut StyledBuffer,
+                          line: usize,
+                          location: Option<&String>,
+                          level: &Level,
+                          code: &Option<String>,
+                          msg: &str) {
+        if let Some(location) = location {
+            buffer.append(line, location, Style::LineAndColumn);
+            buffer.append(line, ": ", Style::NoStyle);
+        }
+        buffer.append(line, &level.to_string(), Style::Level(level.clone()));
+        if let Some(ref code) = *code {
+            buffer.append(line, "[", Style::Level(level.clone()));
+            buffer.append(line, code, Style::Level(level.clone()));
+            buffer.append(line, "]", Style::Level(level.clone()));
+        }
+        buffer.append(line, ": ", Style::HeaderMsg);
+        // Keep each message on a single line, as tools reading this format
+        // expect one diagnostic per line.
+        let msg = msg.lines().map(|l| l.trim()).filter(|l| !l.is_empty())
+                     .collect::<Vec<_>>().join(" ");
+        buffer.append(line, &msg, Style::HeaderMsg);
+    }
+
+    fn emit_messages_short(&mut self,
+                           level: &Level,
+                           message: &Vec<(String, Style)>,
+                           code: &Option<String>,
+                           span: &MultiSpan,
+                           children: &Vec<SubDiagnostic>) {
+        let mut buffer = StyledBuffer::new();
+        let primary_location = self.short_location(span);
+        let message = message.iter().map(|m| &m.0[..]).collect::<String>();
+        self.short_message_line(&mut buffer, 0, primary_location.as_ref(), level, code,
+                                &message);
+
+        for (i, child) in children.iter().enumerate() {
+            let (location, message) = match child.render_span {
+                Some(Suggestion(ref cs)) => {
+                    let span = MultiSpan::from_span(cs.substitution_spans().next().unwrap());
+                    let substitution = &cs.substitution_parts[0].substitutions[0];
+                    (self.short_location(&span), format!("{} `{}`", cs.msg, substitution))
+                }
+                Some(FullSpan(ref msp)) => (self.short_location(msp), child.message()),
+                None => (self.short_location(&child.span), child.message()),
+            };
+            // Children without a span of their own are about the primary one.
+            let location = location.or_else(|| primary_location.clone());
+            self.short_message_line(&mut buffer, i + 1, location.as_ref(), &child.level,
+                                    &None, &message);
+        }
+
+        if let Err(e) = emit_to_destination(&buffer.render(), level, &mut self.dst) {
+            panic!("failed to emit error: {}", e);
+        }
+    }
 }
 
 fn draw_col_separator(buffer: &mut StyledBuffer, line: usize, col: usize) {
@@ -38,7 +38,7 @@ use self::Level::*;
 use emitter::{Emitter, EmitterWriter};
 
 use std::cell::{RefCell, Cell};
-use std::{error, fmt};
+use std::{error, fmt, mem};
 use std::rc::Rc;
 
 pub mod diagnostic;
@@ -87,6 +87,10 @@ pub struct CodeSuggestion {
     /// ```
     pub substitution_parts: Vec<Substitution>,
     pub msg: String,
+    /// Whether applying the suggestion is known to result in the intended
+    /// code, so that tools may apply it without a human looking at it first.
+    /// Only suggestions with a single substitution should be marked so.
+    pub applicable: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, RustcEncodable, RustcDecodable)]
@@ -263,6 +267,7 @@ pub struct Handler {
     treat_err_as_bug: bool,
     continue_after_error: Cell<bool>,
     delayed_span_bug: RefCell<Option<(MultiSpan, String)>>,
+    applicable_suggestions: RefCell<Option<Vec<CodeSuggestion>>>,
 }
 
 impl Handler {
@@ -271,7 +276,7 @@ impl Handler {
                             treat_err_as_bug: bool,
                             cm: Option<Rc<CodeMapper>>)
                             -> Handler {
-        let emitter = Box::new(EmitterWriter::stderr(color_config, cm));
+        let emitter = Box::new(EmitterWriter::stderr(color_config, cm, false));
         Handler::with_emitter(can_emit_warnings, treat_err_as_bug, emitter)
     }
 
@@ -286,6 +291,7 @@ impl Handler {
             treat_err_as_bug: treat_err_as_bug,
             continue_after_error: Cell::new(true),
             delayed_span_bug: RefCell::new(None),
+            applicable_suggestions: RefCell::new(None),
         }
     }
 
@@ -293,6 +299,30 @@ impl Handler {
         self.continue_after_error.set(continue_after_error);
     }
 
+    /// Starts recording the suggestions marked `applicable` of every
+    /// diagnostic emitted from now on, for `take_applicable_suggestions`.
+    pub fn record_applicable_suggestions(&self) {
+        let mut suggestions = self.applicable_suggestions.borrow_mut();
+        if suggestions.is_none() {
+            *suggestions = Some(Vec::new());
+        }
+    }
+
+    /// Returns the applicable suggestions recorded so far, in the order in
+    /// which their diagnostics were emitted.
+    pub fn take_applicable_suggestions(&self) -> Vec<CodeSuggestion> {
+        match *self.applicable_suggestions.borrow_mut() {
+            Some(ref mut suggestions) => mem::replace(suggestions, Vec::new()),
+            None => Vec::new(),
+        }
+    }
+
+    fn record_suggestions(&self, suggestions: &[CodeSuggestion]) {
+        if let Some(ref mut recorded) = *self.applicable_suggestions.borrow_mut() {
+            recorded.extend(suggestions.iter().filter(|s| s.applicable).cloned());
+        }
+    }
+
     pub fn struct_dummy<'a>(&'a self) -> DiagnosticBuilder<'a> {
         DiagnosticBuilder::new(self, Level::Cancelled, "")
     }
@@ -109,6 +109,8 @@ fn main() {
 
     if major > 3 {
         optional_components.push("hexagon");
+        // Writes the coverage mapping of `-Z instrument-coverage`
+        optional_components.push("coverage");
     }
 
     // FIXME: surely we don't need all these components, right? Stuff like mcjit
@@ -117,6 +119,7 @@ fn main() {
                                 "bitreader",
                                 "bitwriter",
                                 "linker",
+                                "lto",
                                 "asmparser",
                                 "mcjit",
                                 "interpreter",
@@ -169,6 +172,7 @@ fn main() {
     cfg.file("../rustllvm/PassWrapper.cpp")
        .file("../rustllvm/RustWrapper.cpp")
        .file("../rustllvm/ArchiveWrapper.cpp")
+       .file("../rustllvm/CoverageMappingWrapper.cpp")
        .cpp(true)
        .cpp_link_stdlib(None) // we handle this below
        .compile("librustllvm.a");
@@ -419,6 +419,29 @@ pub type SMDiagnosticRef = *mut SMDiagnostic_opaque;
 pub enum RustArchiveMember_opaque {}
 pub type RustArchiveMemberRef = *mut RustArchiveMember_opaque;
 #[allow(missing_copy_implementations)]
+pub enum ThinLTOData_opaque {}
+pub type ThinLTODataRef = *mut ThinLTOData_opaque;
+
+/// A serialized module passed to `LLVMRustCreateThinLTOData`.
+#[repr(C)]
+pub struct ThinLTOModule {
+    pub identifier: *const c_char,
+    pub data: *const u8,
+    pub len: usize,
+}
+
+/// Matches LLVMRustCounterMappingRegion in CoverageMappingWrapper.cpp
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+pub struct CounterMappingRegion {
+    pub counter_id: c_uint,
+    pub file_id: c_uint,
+    pub line_start: c_uint,
+    pub column_start: c_uint,
+    pub line_end: c_uint,
+    pub column_end: c_uint,
+}
+#[allow(missing_copy_implementations)]
 pub enum OperandBundleDef_opaque {}
 pub type OperandBundleDefRef = *mut OperandBundleDef_opaque;
 
@@ -1582,7 +1605,10 @@ extern "C" {
                                                OptLevel: CodeGenOptLevel,
                                                MergeFunctions: bool,
                                                SLPVectorize: bool,
-                                               LoopVectorize: bool);
+                                               LoopVectorize: bool,
+                                               PrepareForThinLTO: bool,
+                                               PGOGenPath: *const c_char,
+                                               PGOUsePath: *const c_char);
     pub fn LLVMRustAddLibraryInfo(PM: PassManagerRef,
                                   M: ModuleRef,
                                   DisableSimplifyLibCalls: bool);
@@ -1602,6 +1628,26 @@ extern "C" {
     pub fn LLVMRustRunRestrictionPass(M: ModuleRef, syms: *const *const c_char, len: size_t);
     pub fn LLVMRustMarkAllFunctionsNounwind(M: ModuleRef);
 
+    pub fn LLVMRustThinLTOAvailable() -> bool;
+    pub fn LLVMRustWriteThinBitcodeToFile(M: ModuleRef, Path: *const c_char) -> bool;
+    pub fn LLVMRustCreateThinLTOData(Modules: *const ThinLTOModule,
+                                     NumModules: c_uint,
+                                     PreservedSymbols: *const *const c_char,
+                                     PreservedSymbolsLen: c_uint)
+                                     -> ThinLTODataRef;
+    pub fn LLVMRustFreeThinLTOData(Data: ThinLTODataRef);
+    pub fn LLVMRustPrepareThinLTORename(Data: ThinLTODataRef, Module: ModuleRef) -> bool;
+    pub fn LLVMRustPrepareThinLTOResolveWeak(Data: ThinLTODataRef, Module: ModuleRef) -> bool;
+    pub fn LLVMRustPrepareThinLTOInternalize(Data: ThinLTODataRef, Module: ModuleRef) -> bool;
+    pub fn LLVMRustPrepareThinLTOImport(Data: ThinLTODataRef, Module: ModuleRef) -> bool;
+    pub fn LLVMRustParseBitcodeForThinLTO(Context: ContextRef,
+                                          Data: *const u8,
+                                          len: usize,
+                                          Identifier: *const c_char)
+                                          -> ModuleRef;
+    pub fn LLVMRustPassManagerBuilderPopulateThinLTOPassManager(PMB: PassManagerBuilderRef,
+                                                                PM: PassManagerRef);
+
     pub fn LLVMRustOpenArchive(path: *const c_char) -> ArchiveRef;
     pub fn LLVMRustArchiveIteratorNew(AR: ArchiveRef) -> ArchiveIteratorRef;
     pub fn LLVMRustArchiveIteratorNext(AIR: ArchiveIteratorRef) -> ArchiveChildRef;
@@ -1666,4 +1712,16 @@ extern "C" {
     pub fn LLVMRustSetComdat(M: ModuleRef, V: ValueRef, Name: *const c_char);
     pub fn LLVMRustUnsetComdat(V: ValueRef);
     pub fn LLVMRustSetModulePIELevel(M: ModuleRef);
+
+    pub fn LLVMRustCoverageWriteFilenamesSectionToBuffer(Filenames: *const *const c_char,
+                                                         FilenamesLen: size_t,
+                                                         BufferOut: RustStringRef);
+    pub fn LLVMRustCoverageWriteMappingToBuffer(VirtualFileMappingIDs: *const c_uint,
+                                                NumVirtualFileMappingIDs: c_uint,
+                                                MappingRegions: *const CounterMappingRegion,
+                                                NumMappingRegions: c_uint,
+                                                BufferOut: RustStringRef);
+    pub fn LLVMRustCoverageCreatePGOFuncNameVar(F: ValueRef, FuncName: *const c_char) -> ValueRef;
+    pub fn LLVMRustCoverageComputeHash(Name: *const c_char) -> u64;
+    pub fn LLVMRustCoverageMappingVersion() -> u32;
 }
@@ -18,13 +18,17 @@ use rustc::hir::def_id::{CRATE_DEF_INDEX, LOCAL_CRATE, CrateNum, DefIndex, DefId
 use rustc::hir::map::definitions::{DefPathTable, GlobalMetaDataKind};
 use rustc::hir::svh::Svh;
 use rustc::middle::cstore::{DepKind, ExternCrate, MetadataLoader};
+use rustc::session::search_paths::PathKind;
 use rustc_back::PanicStrategy;
 use rustc_data_structures::indexed_vec::IndexVec;
 use rustc::util::nodemap::{FxHashMap, FxHashSet, NodeMap, DefIdMap};
 
 use std::cell::{RefCell, Cell};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::rc::Rc;
 use owning_ref::ErasedBoxRef;
+use rustc_serialize::json::{Json, ToJson};
 use syntax::{ast, attr};
 use syntax::ext::base::SyntaxExtension;
 use syntax::symbol::Symbol;
@@ -255,6 +259,66 @@ impl CStore {
     pub fn do_extern_mod_stmt_cnum(&self, emod_id: ast::NodeId) -> Option<CrateNum> {
         self.extern_mod_crate_map.borrow().get(&emod_id).cloned()
     }
+
+    /// Describes every loaded crate for `--print crate-graph`: where it was
+    /// loaded from and how it was found, its SVH and kind, and the crates it
+    /// depends on.
+    pub fn crate_graph(&self) -> Json {
+        fn path_json(path: &Option<(PathBuf, PathKind)>) -> Json {
+            match *path {
+                Some((ref path, _)) => path.display().to_string().to_json(),
+                None => Json::Null,
+            }
+        }
+
+        let metas = self.metas.borrow();
+        let mut cnums = metas.keys().cloned().collect::<Vec<_>>();
+        cnums.sort();
+        let crates = cnums.into_iter().map(|cnum| {
+            let data = &metas[&cnum];
+            let source = &data.source;
+            let kind = if data.proc_macros.is_some() {
+                "proc-macro"
+            } else if source.rlib.is_some() {
+                "rlib"
+            } else if source.rmeta.is_some() {
+                "rmeta"
+            } else {
+                "dylib"
+            };
+            let path_kind = source.rlib.iter()
+                                  .chain(source.rmeta.iter())
+                                  .chain(source.dylib.iter())
+                                  .map(|&(_, kind)| kind)
+                                  .next();
+            let found_by = match path_kind {
+                Some(PathKind::ExternFlag) => "extern",
+                _ => "search",
+            };
+            let mut deps = vec![];
+            for &dep in data.cnum_map.borrow().iter() {
+                if dep != cnum && !deps.contains(&dep) {
+                    deps.push(dep);
+                }
+            }
+            let direct = data.extern_crate.get().map_or(false, |ext| ext.direct);
+
+            let mut map = BTreeMap::new();
+            map.insert("cnum".to_string(), cnum.as_u32().to_json());
+            map.insert("name".to_string(), data.name().to_string().to_json());
+            map.insert("svh".to_string(), data.hash().to_string().to_json());
+            map.insert("kind".to_string(), kind.to_json());
+            map.insert("found_by".to_string(), found_by.to_json());
+            map.insert("direct".to_string(), direct.to_json());
+            map.insert("rlib".to_string(), path_json(&source.rlib));
+            map.insert("rmeta".to_string(), path_json(&source.rmeta));
+            map.insert("dylib".to_string(), path_json(&source.dylib));
+            map.insert("dependencies".to_string(),
+                       deps.iter().map(|dep| dep.as_u32()).collect::<Vec<_>>().to_json());
+            Json::Object(map)
+        }).collect::<Vec<_>>();
+        Json::Array(crates)
+    }
 }
 
 impl CrateMetadata {
@@ -298,6 +362,11 @@ impl CrateMetadata {
         attr::contains_name(&attrs, "sanitizer_runtime")
     }
 
+    pub fn is_profiler_runtime(&self, dep_graph: &DepGraph) -> bool {
+        let attrs = self.get_item_attrs(CRATE_DEF_INDEX, dep_graph);
+        attr::contains_name(&attrs, "profiler_runtime")
+    }
+
     pub fn is_no_builtins(&self, dep_graph: &DepGraph) -> bool {
         let attrs = self.get_item_attrs(CRATE_DEF_INDEX, dep_graph);
         attr::contains_name(&attrs, "no_builtins")
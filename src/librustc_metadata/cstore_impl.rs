@@ -159,6 +159,13 @@ impl CrateStore for cstore::CStore {
         result
     }
 
+    fn blanket_impls(&self) -> Vec<DefId>
+    {
+        let mut result = vec![];
+        self.iter_crate_data(|_, cdata| cdata.get_blanket_impls(&mut result));
+        result
+    }
+
     fn impl_defaultness(&self, def: DefId) -> hir::Defaultness
     {
         self.dep_graph.read(DepNode::MetaData(def));
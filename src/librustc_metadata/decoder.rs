@@ -970,6 +970,13 @@ impl<'a, 'tcx> CrateMetadata {
         }
     }
 
+    pub fn get_blanket_impls(&self, result: &mut Vec<DefId>) {
+        if self.proc_macros.is_some() {
+            return;
+        }
+        result.extend(self.root.blanket_impls.decode(self).map(|idx| self.local_def_id(idx)));
+    }
+
     pub fn get_trait_of_item(&self, id: DefIndex) -> Option<DefId> {
         self.def_key(id).parent.and_then(|parent_index| {
             match self.entry(parent_index).kind {
@@ -24,6 +24,7 @@ use rustc::middle::lang_items;
 use rustc::mir;
 use rustc::traits::specialization_graph;
 use rustc::ty::{self, Ty, TyCtxt, ReprOptions};
+use rustc::ty::fast_reject;
 
 use rustc::session::config::{self, CrateTypeProcMacro};
 use rustc::util::nodemap::{FxHashMap, NodeSet};
@@ -281,6 +282,27 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
         self.lazy(definitions.def_path_table())
     }
 
+    fn encode_blanket_impls(&mut self) -> LazySeq<DefIndex> {
+        let tcx = self.tcx;
+        let mut impls: Vec<_> = tcx.hir.krate().items.values().filter_map(|item| {
+            if let hir::ItemImpl(..) = item.node {
+                let def_id = tcx.hir.local_def_id(item.id);
+                let is_blanket = tcx.impl_trait_ref(def_id).is_some() &&
+                    fast_reject::simplify_type(tcx, tcx.type_of(def_id), false).is_none();
+                if is_blanket {
+                    return Some(def_id.index);
+                }
+            }
+            None
+        }).collect();
+
+        // Bring everything into deterministic order for hashing
+        impls.sort_unstable_by_key(|&def_index| {
+            tcx.hir.definitions().def_path_hash(def_index)
+        });
+        self.lazy_seq(impls)
+    }
+
     fn encode_codemap(&mut self) -> LazySeq<syntax_pos::FileMap> {
         let codemap = self.tcx.sess.codemap();
         let all_filemaps = codemap.files();
@@ -377,6 +399,7 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
             global_metadata_def_index(GlobalMetaDataKind::Impls),
             IsolatedEncoder::encode_impls,
             ());
+        let blanket_impls = self.encode_blanket_impls();
         let impl_bytes = self.position() - i;
 
         // Encode exported symbols info.
@@ -424,6 +447,7 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
             codemap: codemap,
             def_path_table: def_path_table,
             impls: impls,
+            blanket_impls: blanket_impls,
             exported_symbols: exported_symbols,
             index: index,
         });
@@ -254,6 +254,9 @@ pub struct CrateRoot {
     pub codemap: LazySeq<syntax_pos::FileMap>,
     pub def_path_table: Lazy<hir::map::definitions::DefPathTable>,
     pub impls: Tracked<LazySeq<TraitImpls>>,
+    /// The impls of traits for all types, such as `impl<T: Display> ToString
+    /// for T`, which rustdoc shows on the pages of the types they apply to.
+    pub blanket_impls: LazySeq<DefIndex>,
     pub exported_symbols: Tracked<LazySeq<DefIndex>>,
     pub index: LazySeq<index::Index>,
 }
@@ -431,9 +431,9 @@ fn link_binary_output(sess: &Session,
 fn object_filenames(trans: &CrateTranslation,
                     outputs: &OutputFilenames)
                     -> Vec<PathBuf> {
-    trans.modules.iter().map(|module| {
-        outputs.temp_path(OutputType::Object, Some(&module.name))
-    }).collect()
+    let names = trans.modules.iter().map(|module| &module.name)
+                     .chain(trans.thin_lto_upstream_modules.iter());
+    names.map(|name| outputs.temp_path(OutputType::Object, Some(name))).collect()
 }
 
 fn archive_search_paths(sess: &Session) -> Vec<PathBuf> {
@@ -954,6 +954,12 @@ fn link_args(cmd: &mut Linker,
     // Pass debuginfo flags down to the linker.
     cmd.debuginfo();
 
+    // Make sure the profiler runtime is kept in instrumented binaries.
+    if sess.opts.debugging_opts.profile_generate.is_some() ||
+       sess.opts.debugging_opts.instrument_coverage {
+        cmd.pgo_gen();
+    }
+
     // We want to prevent the compiler from accidentally leaking in any system
     // libraries, so we explicitly ask gcc to not link to any libraries by
     // default. Note that this does not happen for windows because windows pulls
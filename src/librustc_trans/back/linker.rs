@@ -111,6 +111,7 @@ pub trait Linker {
     fn args(&mut self, args: &[String]);
     fn export_symbols(&mut self, tmpdir: &Path, crate_type: CrateType);
     fn subsystem(&mut self, subsystem: &str);
+    fn pgo_gen(&mut self);
     // Should have been finalize(self), but we don't support self-by-value on trait objects (yet?).
     fn finalize(&mut self) -> Command;
 }
@@ -366,6 +367,17 @@ impl<'a> Linker for GccLinker<'a> {
         self.linker_arg(&format!("--subsystem,{}", subsystem));
     }
 
+    fn pgo_gen(&mut self) {
+        if !self.sess.target.target.options.linker_is_gnu { return }
+
+        // LLVM only makes instrumented code reference the profiler runtime
+        // on targets where clang's driver doesn't do it, which on ELF
+        // platforms is done with this `-u` flag. Without it the runtime,
+        // which writes the profile on exit, would be dropped from the link.
+        self.cmd.arg("-u");
+        self.cmd.arg("__llvm_profile_runtime");
+    }
+
     fn finalize(&mut self) -> Command {
         self.hint_dynamic(); // Reset to default before returning the composed command line.
         let mut cmd = Command::new("");
@@ -538,6 +550,11 @@ impl<'a> Linker for MsvcLinker<'a> {
         }
     }
 
+    fn pgo_gen(&mut self) {
+        // Nothing needed here, LLVM already makes instrumented code
+        // reference the profiler runtime on this target.
+    }
+
     fn finalize(&mut self) -> Command {
         let mut cmd = Command::new("");
         ::std::mem::swap(&mut cmd, &mut self.cmd);
@@ -676,6 +693,10 @@ impl<'a> Linker for EmLinker<'a> {
         // noop
     }
 
+    fn pgo_gen(&mut self) {
+        // noop, but maybe we need something like the gnu linker?
+    }
+
     fn finalize(&mut self) -> Command {
         let mut cmd = Command::new("");
         ::std::mem::swap(&mut cmd, &mut self.cmd);
@@ -17,8 +17,10 @@ use llvm::archive_ro::ArchiveRO;
 use llvm::{ModuleRef, TargetMachineRef, True, False};
 use rustc::util::common::time;
 use rustc::util::common::path2cstr;
-use rustc::hir::def_id::LOCAL_CRATE;
+use rustc::hir::def_id::{CrateNum, LOCAL_CRATE};
 use back::write::{ModuleConfig, with_llvm_pmb};
+use errors::Handler;
+use ModuleLlvm;
 
 use libc;
 use flate;
@@ -38,12 +40,8 @@ pub fn crate_type_allows_lto(crate_type: config::CrateType) -> bool {
     }
 }
 
-pub fn run(sess: &session::Session,
-           llmod: ModuleRef,
-           tm: TargetMachineRef,
-           exported_symbols: &ExportedSymbols,
-           config: &ModuleConfig,
-           temp_no_opt_bc_filename: &Path) {
+/// Reports an error if the crate can't be compiled with LTO, fat or thin.
+pub fn check_lto_allowed(sess: &session::Session) {
     if sess.opts.cg.prefer_dynamic {
         sess.struct_err("cannot prefer dynamic linking when performing LTO")
             .note("only 'staticlib', 'bin', and 'cdylib' outputs are \
@@ -59,7 +57,13 @@ pub fn run(sess: &session::Session,
                             static library outputs");
         }
     }
+}
 
+/// The symbols which must stay visible after LTO: those exported by the
+/// local crate and, with `-C lto`, by the upstream crates taking part in it.
+fn symbol_white_list(sess: &session::Session,
+                     exported_symbols: &ExportedSymbols)
+                     -> Vec<CString> {
     let export_threshold =
         symbol_export::crates_export_threshold(&sess.crate_types.borrow());
 
@@ -79,20 +83,33 @@ pub fn run(sess: &session::Session,
         .filter_map(symbol_filter)
         .collect();
 
-    // For each of our upstream dependencies, find the corresponding rlib and
-    // load the bitcode from the archive. Then merge it into the current LLVM
-    // module that we've got.
+    if sess.lto() {
+        link::each_linked_rlib(sess, &mut |cnum, _| {
+            // `#![no_builtins]` crates don't participate in LTO.
+            if sess.cstore.is_no_builtins(cnum) {
+                return;
+            }
+
+            symbol_white_list.extend(
+                exported_symbols.exported_symbols(cnum)
+                                .iter()
+                                .filter_map(symbol_filter));
+        });
+    }
+
+    symbol_white_list
+}
+
+/// Calls `f` with the decoded bitcode of each module of the upstream crates
+/// taking part in LTO, along with its name in the rlib.
+fn each_upstream_bytecode(sess: &session::Session,
+                          f: &mut FnMut(CrateNum, &str, &[u8])) {
     link::each_linked_rlib(sess, &mut |cnum, path| {
         // `#![no_builtins]` crates don't participate in LTO.
         if sess.cstore.is_no_builtins(cnum) {
             return;
         }
 
-        symbol_white_list.extend(
-            exported_symbols.exported_symbols(cnum)
-                            .iter()
-                            .filter_map(symbol_filter));
-
         let archive = ArchiveRO::open(&path).expect("wanted an rlib");
         let bytecodes = archive.iter().filter_map(|child| {
             child.ok().and_then(|c| c.name().map(|name| (name, c)))
@@ -139,19 +156,37 @@ pub fn run(sess: &session::Session,
                 })
             };
 
-            let ptr = bc_decoded.as_ptr();
-            debug!("linking {}", name);
-            time(sess.time_passes(), &format!("ll link {}", name), || unsafe {
-                if !llvm::LLVMRustLinkInExternalBitcode(llmod,
-                                                        ptr as *const libc::c_char,
-                                                        bc_decoded.len() as libc::size_t) {
-                    write::llvm_err(sess.diagnostic(),
-                                    format!("failed to load bc of `{}`",
-                                            name));
-                }
-            });
+            f(cnum, name, &bc_decoded);
         }
     });
+}
+
+pub fn run(sess: &session::Session,
+           llmod: ModuleRef,
+           tm: TargetMachineRef,
+           exported_symbols: &ExportedSymbols,
+           config: &ModuleConfig,
+           temp_no_opt_bc_filename: &Path) {
+    check_lto_allowed(sess);
+
+    let symbol_white_list = symbol_white_list(sess, exported_symbols);
+
+    // For each of our upstream dependencies, find the corresponding rlib and
+    // load the bitcode from the archive. Then merge it into the current LLVM
+    // module that we've got.
+    each_upstream_bytecode(sess, &mut |_, name, bc_decoded| {
+        let ptr = bc_decoded.as_ptr();
+        debug!("linking {}", name);
+        time(sess.time_passes(), &format!("ll link {}", name), || unsafe {
+            if !llvm::LLVMRustLinkInExternalBitcode(llmod,
+                                                    ptr as *const libc::c_char,
+                                                    bc_decoded.len() as libc::size_t) {
+                write::llvm_err(sess.diagnostic(),
+                                format!("failed to load bc of `{}`",
+                                        name));
+            }
+        });
+    });
 
     // Internalize everything but the exported symbols of the current module
     let arr: Vec<*const libc::c_char> = symbol_white_list.iter()
@@ -208,6 +243,158 @@ pub fn run(sess: &session::Session,
     debug!("lto done");
 }
 
+/// The modules of the upstream crates taking part in ThinLTO with `-C lto`,
+/// by the name their objects get.
+pub fn upstream_thin_modules(sess: &session::Session) -> Vec<(String, Vec<u8>)> {
+    let mut modules = vec![];
+    each_upstream_bytecode(sess, &mut |cnum, name, bc_decoded| {
+        let name = name.trim_right_matches(".bytecode.deflate");
+        modules.push((format!("{}.lto{}", name, cnum), bc_decoded.to_vec()));
+    });
+    modules
+}
+
+/// The result of the ThinLTO global analysis over all the modules taking
+/// part in it, shared by the codegen threads importing into and optimizing
+/// each module.
+pub struct ThinData {
+    data: llvm::ThinLTODataRef,
+    // The serialized modules, by name. LLVM imports from these buffers
+    // without copying them.
+    modules: Vec<(CString, Vec<u8>)>,
+    no_landing_pads: bool,
+}
+
+unsafe impl Send for ThinData {}
+unsafe impl Sync for ThinData {}
+
+impl ThinData {
+    /// Runs the global analysis over `modules`, which must have been written
+    /// along with their summaries.
+    pub fn new(sess: &session::Session,
+               exported_symbols: &ExportedSymbols,
+               modules: Vec<(String, Vec<u8>)>)
+               -> ThinData {
+        let modules: Vec<(CString, Vec<u8>)> = modules.into_iter().map(|(name, bc)| {
+            (CString::new(name).unwrap(), bc)
+        }).collect();
+        let thin_modules: Vec<llvm::ThinLTOModule> = modules.iter().map(|&(ref name, ref bc)| {
+            llvm::ThinLTOModule {
+                identifier: name.as_ptr(),
+                data: bc.as_ptr(),
+                len: bc.len(),
+            }
+        }).collect();
+
+        let symbol_white_list = symbol_white_list(sess, exported_symbols);
+        let arr: Vec<*const libc::c_char> = symbol_white_list.iter()
+                                                             .map(|c| c.as_ptr())
+                                                             .collect();
+
+        let data = time(sess.time_passes(), "ThinLTO global analysis", || unsafe {
+            llvm::LLVMRustCreateThinLTOData(thin_modules.as_ptr(),
+                                            thin_modules.len() as libc::c_uint,
+                                            arr.as_ptr(),
+                                            arr.len() as libc::c_uint)
+        });
+        if data.is_null() {
+            write::llvm_err(sess.diagnostic(),
+                            "failed to prepare ThinLTO context".to_string());
+        }
+
+        ThinData {
+            data: data,
+            modules: modules,
+            no_landing_pads: sess.no_landing_pads(),
+        }
+    }
+
+    /// Parses each module into its own context, to be handed to
+    /// `optimize_thin`.
+    pub fn parse_modules(&self, sess: &session::Session) -> Vec<(String, ModuleLlvm)> {
+        self.modules.iter().map(|&(ref name, ref bc)| {
+            let name = name.to_str().unwrap();
+            (name.to_string(), parse_module(sess, name, bc))
+        }).collect()
+    }
+}
+
+/// Parses the module `bc` into a new context, naming it `name`, which is
+/// what ThinLTO identifies it by.
+pub fn parse_module(sess: &session::Session, name: &str, bc: &[u8]) -> ModuleLlvm {
+    let name_c = CString::new(name).unwrap();
+    unsafe {
+        let llcx = llvm::LLVMContextCreate();
+        let llmod = llvm::LLVMRustParseBitcodeForThinLTO(llcx,
+                                                         bc.as_ptr(),
+                                                         bc.len(),
+                                                         name_c.as_ptr());
+        if llmod.is_null() {
+            write::llvm_err(sess.diagnostic(),
+                            format!("failed to parse bitcode of `{}`", name));
+        }
+        ModuleLlvm {
+            llcx: llcx,
+            llmod: llmod,
+        }
+    }
+}
+
+impl Drop for ThinData {
+    fn drop(&mut self) {
+        unsafe {
+            llvm::LLVMRustFreeThinLTOData(self.data);
+        }
+    }
+}
+
+/// Applies the decisions of the global analysis in `data` to `llmod`, one of
+/// the modules it was run over, importing the functions it decided to, then
+/// runs the ThinLTO optimization passes. Unlike `run`, this only touches the
+/// one module, so the modules can be processed in parallel.
+pub unsafe fn optimize_thin(handler: &Handler,
+                            data: &ThinData,
+                            llmod: ModuleRef,
+                            tm: TargetMachineRef,
+                            config: &ModuleConfig) {
+    if !llvm::LLVMRustPrepareThinLTORename(data.data, llmod) {
+        write::llvm_err(handler, "failed to rename the symbols of thin LTO module".to_string());
+    }
+    if !llvm::LLVMRustPrepareThinLTOResolveWeak(data.data, llmod) {
+        write::llvm_err(handler,
+                        "failed to resolve the weak symbols of thin LTO module".to_string());
+    }
+    if !llvm::LLVMRustPrepareThinLTOInternalize(data.data, llmod) {
+        write::llvm_err(handler,
+                        "failed to internalize the symbols of thin LTO module".to_string());
+    }
+    if !llvm::LLVMRustPrepareThinLTOImport(data.data, llmod) {
+        write::llvm_err(handler, "failed to import functions into thin LTO module".to_string());
+    }
+
+    if data.no_landing_pads {
+        llvm::LLVMRustMarkAllFunctionsNounwind(llmod);
+    }
+
+    // Like `run`, but with the pipeline LLVM's ThinLTO backend uses.
+    let pm = llvm::LLVMCreatePassManager();
+    llvm::LLVMRustAddAnalysisPasses(tm, pm, llmod);
+    let pass = llvm::LLVMRustFindAndCreatePass("verify\0".as_ptr() as *const _);
+    assert!(!pass.is_null());
+    llvm::LLVMRustAddPass(pm, pass);
+
+    with_llvm_pmb(llmod, config, &mut |b| {
+        llvm::LLVMRustPassManagerBuilderPopulateThinLTOPassManager(b, pm);
+    });
+
+    let pass = llvm::LLVMRustFindAndCreatePass("verify\0".as_ptr() as *const _);
+    assert!(!pass.is_null());
+    llvm::LLVMRustAddPass(pm, pass);
+
+    llvm::LLVMRunPassManager(pm, llmod);
+    llvm::LLVMDisposePassManager(pm);
+}
+
 fn is_versioned_bytecode_format(bc: &[u8]) -> bool {
     let magic_id_byte_count = link::RLIB_BYTECODE_OBJECT_MAGIC.len();
     return bc.len() > magic_id_byte_count &&
@@ -20,6 +20,7 @@ use llvm::{ModuleRef, TargetMachineRef, PassManagerRef, DiagnosticInfoRef, Conte
 use llvm::SMDiagnosticRef;
 use {CrateTranslation, ModuleLlvm, ModuleSource, ModuleTranslation};
 use rustc::util::common::{time, time_depth, set_time_depth, path2cstr};
+use rustc::util::profiling;
 use rustc::util::fs::link_or_copy;
 use errors::{self, Handler, Level, DiagnosticBuilder};
 use errors::emitter::Emitter;
@@ -29,7 +30,9 @@ use context::{is_pie_binary, get_reloc_model};
 use std::cmp;
 use std::ffi::CString;
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::ptr;
 use std::str;
 use std::sync::{Arc, Mutex};
 use std::sync::mpsc::channel;
@@ -271,6 +274,23 @@ pub struct ModuleConfig {
     vectorize_slp: bool,
     merge_functions: bool,
     inline_threshold: Option<usize>,
+    // The path instrumented code writes its profile to, from
+    // `-Z profile-generate`.
+    pgo_gen: Option<String>,
+    // The profile to optimize with, from `-Z profile-use`.
+    pgo_use: Option<String>,
+    // Whether to lower the coverage counter increments of
+    // `-Z instrument-coverage`.
+    instrument_coverage: bool,
+    // Whether to stop after optimizing the module and write it out along
+    // with its summary, for the ThinLTO global analysis.
+    prepare_for_thin_lto: bool,
+    // The ThinLTO global analysis to import into the module with, instead of
+    // optimizing it on its own.
+    thin_lto_data: Option<Arc<lto::ThinData>>,
+    // Whether the module was created for this work item alone, by ThinLTO,
+    // and should be disposed of once codegened.
+    owns_module: bool,
     // Instead of creating an object file by doing LLVM codegen, just
     // make the object file bitcode. Provides easy compatibility with
     // emscripten's ecc compiler, when used as the linker.
@@ -302,7 +322,13 @@ impl ModuleConfig {
             vectorize_loop: false,
             vectorize_slp: false,
             merge_functions: false,
-            inline_threshold: None
+            inline_threshold: None,
+            pgo_gen: None,
+            pgo_use: None,
+            instrument_coverage: false,
+            prepare_for_thin_lto: false,
+            thin_lto_data: None,
+            owns_module: false,
         }
     }
 
@@ -460,7 +486,15 @@ unsafe fn optimize_and_codegen(cgcx: &CodegenContext,
         llvm::LLVMWriteBitcodeToFile(llmod, out.as_ptr());
     }
 
-    if config.opt_level.is_some() {
+    if let Some(ref data) = config.thin_lto_data {
+        time(config.time_passes, &format!("ThinLTO passes [{}]", cgcx.worker), ||
+             lto::optimize_thin(cgcx.handler, data, llmod, tm, &config));
+        if config.emit_lto_bc {
+            let out = output_names.temp_path_ext("lto.bc", module_name);
+            let out = path2cstr(&out);
+            llvm::LLVMWriteBitcodeToFile(llmod, out.as_ptr());
+        }
+    } else if config.opt_level.is_some() {
         // Create the two optimizing pass managers. These mirror what clang
         // does, and are by populated by LLVM's default PassManagerBuilder.
         // Each manager has a different set of passes, but they also share
@@ -489,6 +523,10 @@ unsafe fn optimize_and_codegen(cgcx: &CodegenContext,
         };
 
         if !config.no_verify { assert!(addpass("verify")); }
+        // The counters are lowered ahead of the optimizations, which could
+        // otherwise drop the increments of functions the coverage mapping
+        // still has records for.
+        if config.instrument_coverage { assert!(addpass("instrprof")); }
         if !config.no_prepopulate_passes {
             llvm::LLVMRustAddAnalysisPasses(tm, fpm, llmod);
             llvm::LLVMRustAddAnalysisPasses(tm, mpm, llmod);
@@ -526,7 +564,7 @@ unsafe fn optimize_and_codegen(cgcx: &CodegenContext,
         llvm::LLVMDisposePassManager(mpm);
 
         match cgcx.lto_ctxt {
-            Some((sess, exported_symbols)) if sess.lto() =>  {
+            Some((sess, exported_symbols)) if sess.lto() && !config.prepare_for_thin_lto => {
                 time(sess.time_passes(), "all lto passes", || {
                     let temp_no_opt_bc_filename =
                         output_names.temp_path_ext("no-opt.lto.bc", module_name);
@@ -547,6 +585,20 @@ unsafe fn optimize_and_codegen(cgcx: &CodegenContext,
         }
     }
 
+    // With ThinLTO, the module is only codegened once functions from the
+    // others have been imported into it. The bitcode kept in rlibs gets a
+    // summary too, so that downstream crates can use ThinLTO with it.
+    if config.prepare_for_thin_lto {
+        let out = output_names.temp_path_ext("thin-lto.bc", module_name);
+        write_thin_bitcode(cgcx.handler, llmod, &out);
+        if config.emit_bc {
+            let out = output_names.temp_path(OutputType::Bitcode, module_name);
+            write_thin_bitcode(cgcx.handler, llmod, &out);
+        }
+        llvm::LLVMRustDisposeTargetMachine(tm);
+        return
+    }
+
     // A codegen-specific pass manager is used to generate object
     // files for an LLVM module.
     //
@@ -639,6 +691,12 @@ unsafe fn optimize_and_codegen(cgcx: &CodegenContext,
     llvm::LLVMRustDisposeTargetMachine(tm);
 }
 
+unsafe fn write_thin_bitcode(handler: &Handler, llmod: ModuleRef, path: &Path) {
+    let path_c = path2cstr(path);
+    if !llvm::LLVMRustWriteThinBitcodeToFile(llmod, path_c.as_ptr()) {
+        llvm_err(handler, format!("could not write output to {}", path.display()));
+    }
+}
 
 pub fn cleanup_llvm(trans: &CrateTranslation) {
     for module in trans.modules.iter() {
@@ -656,7 +714,7 @@ pub fn cleanup_llvm(trans: &CrateTranslation) {
 }
 
 pub fn run_passes(sess: &Session,
-                  trans: &CrateTranslation,
+                  trans: &mut CrateTranslation,
                   output_types: &OutputTypes,
                   crate_output: &OutputFilenames) {
     // It's possible that we have `codegen_units > 1` but only one item in
@@ -664,13 +722,31 @@ pub fn run_passes(sess: &Session,
     // case, but it would be confusing to have the validity of
     // `-Z lto -C codegen-units=2` depend on details of the crate being
     // compiled, so we complain regardless.
-    if sess.lto() && sess.opts.cg.codegen_units > 1 {
+    //
+    // With `-Z thinlto`, the codegen units (and with `-C lto`, the modules of
+    // the upstream crates) are instead optimized and codegened separately,
+    // importing from each other what ThinLTO's analysis of their summaries
+    // decided to.
+    let thin_lto = sess.opts.debugging_opts.thinlto &&
+                   (sess.lto() || sess.opts.cg.codegen_units > 1);
+    if sess.lto() && sess.opts.cg.codegen_units > 1 && !thin_lto {
         // This case is impossible to handle because LTO expects to be able
         // to combine the entire crate and all its dependencies into a
         // single compilation unit, but each codegen unit is in a separate
         // LLVM context, so they can't easily be combined.
         sess.fatal("can't perform LTO when using multiple codegen units");
     }
+    if thin_lto {
+        if sess.opts.debugging_opts.incremental.is_some() {
+            sess.fatal("can't perform ThinLTO when compiling incrementally");
+        }
+        if !unsafe { llvm::LLVMRustThinLTOAvailable() } {
+            sess.fatal("ThinLTO requires LLVM 4.0 or later");
+        }
+        if sess.lto() {
+            lto::check_lto_allowed(sess);
+        }
+    }
 
     // Sanity check
     assert!(trans.modules.len() == sess.opts.cg.codegen_units ||
@@ -701,9 +777,29 @@ pub fn run_passes(sess: &Session,
         }
     }
 
+    // Lower the coverage counter increments of `-Z instrument-coverage` to
+    // the counters and data the profiler runtime writes out.
+    modules_config.instrument_coverage = sess.opts.debugging_opts.instrument_coverage;
+
     modules_config.opt_level = Some(get_llvm_opt_level(sess.opts.optimize));
     modules_config.opt_size = Some(get_llvm_opt_size(sess.opts.optimize));
 
+    // Instrument the code to write a profile when it exits, like clang's
+    // `-fprofile-generate`, or optimize it with a profile merged with
+    // `llvm-profdata`. `%m` lets the runs of a binary merge their counts into
+    // the same file.
+    if sess.opts.debugging_opts.profile_generate.is_some() ||
+       sess.opts.debugging_opts.profile_use.is_some() {
+        if unsafe { llvm::LLVMRustVersionMajor() } < 4 {
+            sess.fatal("profile-guided optimization requires LLVM 4.0 or later");
+        }
+    }
+    if let Some(ref dir) = sess.opts.debugging_opts.profile_generate {
+        let path = Path::new(dir).join("default_%m.profraw");
+        modules_config.pgo_gen = Some(path.to_string_lossy().into_owned());
+    }
+    modules_config.pgo_use = sess.opts.debugging_opts.profile_use.clone();
+
     // Save all versions of the bytecode if we're saving our temporaries.
     if sess.opts.cg.save_temps {
         modules_config.emit_no_opt_bc = true;
@@ -751,6 +847,9 @@ pub fn run_passes(sess: &Session,
     modules_config.set_flags(sess, trans);
     metadata_config.set_flags(sess, trans);
 
+    if thin_lto {
+        modules_config.prepare_for_thin_lto = true;
+    }
 
     // Populate a buffer with a list of codegen threads.  Items are processed in
     // LIFO order, just because it's a tiny bit simpler that way.  (The order
@@ -773,6 +872,29 @@ pub fn run_passes(sess: &Session,
         work_items.push(work);
     }
 
+    // The upstream modules taking part in ThinLTO only need a summary, as
+    // they have been optimized already.
+    let mut upstream_names = vec![];
+    if thin_lto && sess.lto() {
+        let mut upstream_config = ModuleConfig::new(tm, vec![]);
+        upstream_config.set_flags(sess, trans);
+        upstream_config.prepare_for_thin_lto = true;
+        upstream_config.owns_module = true;
+
+        for (name, bc) in lto::upstream_thin_modules(sess) {
+            let mtrans = ModuleTranslation {
+                source: ModuleSource::Translated(lto::parse_module(sess, &name, &bc)),
+                name: name.clone(),
+                symbol_name_hash: 0,
+            };
+            work_items.push(build_work_item(sess,
+                                            mtrans,
+                                            upstream_config.clone(),
+                                            crate_output.clone()));
+            upstream_names.push(name);
+        }
+    }
+
     if sess.opts.debugging_opts.incremental_info {
         dump_incremental_data(&trans);
     }
@@ -785,10 +907,45 @@ pub fn run_passes(sess: &Session,
     //       to crash because of too many open file handles. See #39280 for
     //       some discussion on how to improve this in the future.
     let num_workers = cmp::min(work_items.len() - 1, 32);
-    if num_workers <= 1 {
-        run_work_singlethreaded(sess, &trans.exported_symbols, work_items);
-    } else {
-        run_work_multithreaded(sess, work_items, num_workers);
+    run_work(sess, &trans.exported_symbols, work_items, num_workers);
+
+    if thin_lto {
+        let names = trans.modules.iter().map(|m| m.name.clone())
+                                 .chain(upstream_names.iter().cloned());
+        let modules = names.map(|name| {
+            let path = crate_output.temp_path_ext("thin-lto.bc", Some(&name));
+            let mut bc = Vec::new();
+            if let Err(e) = fs::File::open(&path).and_then(|mut f| f.read_to_end(&mut bc)) {
+                sess.fatal(&format!("failed to read {}: {}", path.display(), e));
+            }
+            if !sess.opts.cg.save_temps {
+                remove(sess, &path);
+            }
+            (name, bc)
+        }).collect();
+        let data = Arc::new(lto::ThinData::new(sess, &trans.exported_symbols, modules));
+
+        let mut thin_config = modules_config.clone();
+        thin_config.emit_no_opt_bc = false;
+        thin_config.emit_bc = false;
+        thin_config.prepare_for_thin_lto = false;
+        thin_config.thin_lto_data = Some(data.clone());
+        thin_config.owns_module = true;
+
+        let work_items: Vec<_> = data.parse_modules(sess).into_iter().map(|(name, mllvm)| {
+            let mtrans = ModuleTranslation {
+                name: name,
+                symbol_name_hash: 0,
+                source: ModuleSource::Translated(mllvm),
+            };
+            build_work_item(sess, mtrans, thin_config.clone(), crate_output.clone())
+        }).collect();
+        let num_workers = cmp::min(work_items.len(), 32);
+        run_work(sess, &trans.exported_symbols, work_items, num_workers);
+
+        // The objects of the upstream modules are linked in place of the
+        // rlibs'.
+        trans.thin_lto_upstream_modules = upstream_names;
     }
 
     // If in incr. comp. mode, preserve the `.o` files for potential re-use
@@ -993,15 +1150,23 @@ fn build_work_item(sess: &Session,
 
 fn execute_work_item(cgcx: &CodegenContext,
                      work_item: WorkItem) {
+    let _activity = profiling::activity("codegen", || {
+        format!("codegen unit {}", work_item.mtrans.name)
+    });
     unsafe {
         match work_item.mtrans.source {
             ModuleSource::Translated(mllvm) => {
                 debug!("llvm-optimizing {:?}", work_item.mtrans.name);
+                let owns_module = work_item.config.owns_module;
                 optimize_and_codegen(cgcx,
                                      work_item.mtrans,
                                      mllvm,
                                      work_item.config,
                                      work_item.output_names);
+                if owns_module {
+                    llvm::LLVMDisposeModule(mllvm.llmod);
+                    llvm::LLVMContextDispose(mllvm.llcx);
+                }
             }
             ModuleSource::Preexisting(wp) => {
                 let incr_comp_session_dir = cgcx.incr_comp_session_dir
@@ -1031,6 +1196,17 @@ fn execute_work_item(cgcx: &CodegenContext,
     }
 }
 
+fn run_work(sess: &Session,
+            exported_symbols: &ExportedSymbols,
+            work_items: Vec<WorkItem>,
+            num_workers: usize) {
+    if num_workers <= 1 {
+        run_work_singlethreaded(sess, exported_symbols, work_items);
+    } else {
+        run_work_multithreaded(sess, work_items, num_workers);
+    }
+}
+
 fn run_work_singlethreaded(sess: &Session,
                            exported_symbols: &ExportedSymbols,
                            work_items: Vec<WorkItem>) {
@@ -1066,8 +1242,10 @@ fn run_work_multithreaded(sess: &Session,
         let incr_comp_session_dir = sess.incr_comp_session_dir_opt().map(|r| r.clone());
 
         let depth = time_depth();
+        let profiler = profiling::current();
         thread::Builder::new().name(format!("codegen-{}", i)).spawn(move || {
             set_time_depth(depth);
+            profiling::set_current(profiler);
 
             let diag_handler = Handler::with_emitter(true, false, box diag_emitter);
 
@@ -1161,10 +1339,18 @@ pub unsafe fn with_llvm_pmb(llmod: ModuleRef,
     let opt_size = config.opt_size.unwrap_or(llvm::CodeGenOptSizeNone);
     let inline_threshold = config.inline_threshold;
 
+    let pgo_gen_path = config.pgo_gen.as_ref().map(|s| CString::new(s.as_bytes()).unwrap());
+    let pgo_use_path = config.pgo_use.as_ref().map(|s| CString::new(s.as_bytes()).unwrap());
+
     llvm::LLVMRustConfigurePassManagerBuilder(builder, opt_level,
                                               config.merge_functions,
                                               config.vectorize_slp,
-                                              config.vectorize_loop);
+                                              config.vectorize_loop,
+                                              config.prepare_for_thin_lto,
+                                              pgo_gen_path.as_ref().map_or(ptr::null(),
+                                                                           |s| s.as_ptr()),
+                                              pgo_use_path.as_ref().map_or(ptr::null(),
+                                                                           |s| s.as_ptr()));
     llvm::LLVMPassManagerBuilderSetSizeLevel(builder, opt_size as u32);
 
     if opt_size != llvm::CodeGenOptSizeNone {
@@ -59,6 +59,7 @@ use common::{type_is_zero_size, val_ty};
 use common;
 use consts;
 use context::{self, LocalCrateContext, SharedCrateContext, Stats};
+use coverageinfo;
 use debuginfo;
 use declare;
 use machine;
@@ -1062,6 +1063,12 @@ pub fn trans_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
 
     let check_overflow = tcx.sess.overflow_checks();
 
+    // The coverage mapping is written by LLVM, which can only do so from 4.0 on.
+    if tcx.sess.opts.debugging_opts.instrument_coverage &&
+       unsafe { llvm::LLVMRustVersionMajor() } < 4 {
+        tcx.sess.fatal("`-Z instrument-coverage` requires LLVM 4.0 or later");
+    }
+
     let link_meta = link::build_link_meta(incremental_hashes_map);
 
     let shared_ccx = SharedCrateContext::new(tcx,
@@ -1098,6 +1105,7 @@ pub fn trans_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
             no_builtins: no_builtins,
             linker_info: linker_info,
             windows_subsystem: None,
+            thin_lto_upstream_modules: vec![],
         };
     }
 
@@ -1194,6 +1202,11 @@ pub fn trans_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                 }
             }
 
+            // Write the coverage mapping, which has to be kept in llvm.used
+            if ccx.sess().opts.debugging_opts.instrument_coverage {
+                coverageinfo::finalize(&ccx);
+            }
+
             // Create the llvm.used variable
             // This variable has type [N x i8*] and is stored in the llvm.metadata section
             if !ccx.used_statics().borrow().is_empty() {
@@ -1314,6 +1327,7 @@ pub fn trans_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
         no_builtins: no_builtins,
         linker_info: linker_info,
         windows_subsystem: windows_subsystem,
+        thin_lto_upstream_modules: vec![],
     }
 }
 
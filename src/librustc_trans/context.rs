@@ -14,6 +14,7 @@ use rustc::dep_graph::{DepGraph, DepGraphSafe};
 use rustc::hir;
 use rustc::hir::def_id::DefId;
 use rustc::traits;
+use coverageinfo;
 use debuginfo;
 use callee;
 use base;
@@ -140,6 +141,8 @@ pub struct LocalCrateContext<'a, 'tcx: 'a> {
 
     dbg_cx: Option<debuginfo::CrateDebugContext<'tcx>>,
 
+    coverage_cx: Option<coverageinfo::CrateCoverageContext>,
+
     eh_personality: Cell<Option<ValueRef>>,
     eh_unwind_resume: Cell<Option<ValueRef>>,
     rust_try_fn: Cell<Option<ValueRef>>,
@@ -381,6 +384,12 @@ impl<'a, 'tcx> LocalCrateContext<'a, 'tcx> {
                 None
             };
 
+            let coverage_cx = if shared.tcx.sess.opts.debugging_opts.instrument_coverage {
+                Some(coverageinfo::CrateCoverageContext::new())
+            } else {
+                None
+            };
+
             let local_ccx = LocalCrateContext {
                 llmod: llmod,
                 llcx: llcx,
@@ -402,6 +411,7 @@ impl<'a, 'tcx> LocalCrateContext<'a, 'tcx> {
                 opaque_vec_type: Type::from_ref(ptr::null_mut()),
                 str_slice_type: Type::from_ref(ptr::null_mut()),
                 dbg_cx: dbg_cx,
+                coverage_cx: coverage_cx,
                 eh_personality: Cell::new(None),
                 eh_unwind_resume: Cell::new(None),
                 rust_try_fn: Cell::new(None),
@@ -571,6 +581,10 @@ impl<'b, 'tcx> CrateContext<'b, 'tcx> {
         &self.local().dbg_cx
     }
 
+    pub fn coverage_cx<'a>(&'a self) -> &'a Option<coverageinfo::CrateCoverageContext> {
+        &self.local().coverage_cx
+    }
+
     pub fn rust_try_fn<'a>(&'a self) -> &'a Cell<Option<ValueRef>> {
         &self.local().rust_try_fn
     }
@@ -817,6 +831,7 @@ fn declare_intrinsic(ccx: &CrateContext, key: &str) -> Option<ValueRef> {
     ifn!("llvm.trap", fn() -> void);
     ifn!("llvm.debugtrap", fn() -> void);
     ifn!("llvm.frameaddress", fn(t_i32) -> i8p);
+    ifn!("llvm.instrprof.increment", fn(i8p, t_i64, t_i32, t_i32) -> void);
 
     ifn!("llvm.powi.f32", fn(t_f32, t_i32) -> t_f32);
     ifn!("llvm.powi.f64", fn(t_f64, t_i32) -> t_f64);
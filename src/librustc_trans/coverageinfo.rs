@@ -0,0 +1,314 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Source-based code coverage for `-Z instrument-coverage`.
+//!
+//! Every translated function counts how often it is entered, and every basic
+//! block with statements of its own counts how often it runs. The counters
+//! are `llvm.instrprof.increment` calls, which LLVM's `instrprof` pass lowers
+//! to the counters the profiler runtime writes out. Next to them, each
+//! codegen unit gets a `__llvm_coverage_mapping` global, which maps the
+//! counters back to the source regions they count, in the format `llvm-cov`
+//! reads.
+
+use llvm::{self, ValueRef};
+use rustc::mir::{self, Mir};
+use rustc::ty;
+use builder::Builder;
+use common::{C_array, C_bytes, C_struct, C_u32, C_u64, CrateContext, val_ty};
+use consts;
+use declare;
+use monomorphize::Instance;
+use type_::Type;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::indexed_vec::IndexVec;
+use syntax_pos::{NO_EXPANSION, Span};
+
+use std::cell::RefCell;
+use std::cmp;
+use std::collections::hash_map::DefaultHasher;
+use std::ffi::{CStr, CString};
+use std::hash::Hasher;
+use std::path::Path;
+
+/// The coverage records of the functions in a codegen unit, written out by
+/// `finalize`.
+pub struct CrateCoverageContext {
+    functions: RefCell<Vec<FunctionRecord>>,
+}
+
+impl CrateCoverageContext {
+    pub fn new() -> CrateCoverageContext {
+        CrateCoverageContext {
+            functions: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+struct FunctionRecord {
+    name_md5: u64,
+    hash: u64,
+    filename: String,
+    regions: Vec<llvm::CounterMappingRegion>,
+}
+
+/// The counters of a function being translated.
+pub struct FunctionCoverage {
+    name_var: ValueRef,
+    hash: u64,
+    num_counters: u32,
+    counters: IndexVec<mir::BasicBlock, Option<u32>>,
+}
+
+/// Sets up the counters of a function and records the source regions they
+/// count. Returns `None` if the function isn't instrumented.
+pub fn create_function_coverage<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
+                                          instance: Instance<'tcx>,
+                                          mir: &Mir<'tcx>,
+                                          llfn: ValueRef,
+                                          reentrant_start_block: bool)
+                                          -> Option<FunctionCoverage> {
+    let coverage_cx = match *ccx.coverage_cx() {
+        Some(ref coverage_cx) => coverage_cx,
+        None => return None,
+    };
+
+    // Shims have no source of their own to map their counters to.
+    match instance.def {
+        ty::InstanceDef::Item(_) => {}
+        _ => return None,
+    }
+
+    let cm = ccx.sess().codemap();
+    let start = cm.lookup_char_pos(mir.span.lo);
+    let end = cm.lookup_char_pos(mir.span.hi);
+    if start.file.name.starts_with('<') || start.file.start_pos != end.file.start_pos {
+        return None;
+    }
+
+    // Counter 0 counts the calls of the function, over all of its body.
+    let mut regions = vec![llvm::CounterMappingRegion {
+        counter_id: 0,
+        file_id: 0,
+        line_start: start.line as u32,
+        column_start: start.col.0 as u32 + 1,
+        line_end: end.line as u32,
+        column_end: end.col.0 as u32 + 1,
+    }];
+
+    // The start block is already counted by counter 0, unless it is also
+    // the target of a loop.
+    let mut counters = IndexVec::from_elem_n(None, mir.basic_blocks().len());
+    for (bb, data) in mir.basic_blocks().iter_enumerated() {
+        if data.is_cleanup || (bb == mir::START_BLOCK && !reentrant_start_block) {
+            continue;
+        }
+        let span = match block_span(mir, data) {
+            Some(span) => span,
+            None => continue,
+        };
+        let lo = cm.lookup_char_pos(span.lo);
+        let hi = cm.lookup_char_pos(span.hi);
+        let region = llvm::CounterMappingRegion {
+            counter_id: regions.len() as u32,
+            file_id: 0,
+            line_start: lo.line as u32,
+            column_start: lo.col.0 as u32 + 1,
+            line_end: hi.line as u32,
+            column_end: hi.col.0 as u32 + 1,
+        };
+        // `llvm-cov` adds up the counts of regions with the same location,
+        // so blocks that share one are only counted once.
+        if regions.iter().any(|r| same_location(r, &region)) {
+            continue;
+        }
+        counters[bb] = Some(region.counter_id);
+        regions.push(region);
+    }
+
+    let mut hasher = DefaultHasher::new();
+    for region in &regions {
+        hasher.write_u32(region.line_start);
+        hasher.write_u32(region.column_start);
+        hasher.write_u32(region.line_end);
+        hasher.write_u32(region.column_end);
+    }
+    let hash = hasher.finish();
+
+    let name = unsafe { CStr::from_ptr(llvm::LLVMGetValueName(llfn)) };
+    let (name_var, name_md5) = unsafe {
+        (llvm::LLVMRustCoverageCreatePGOFuncNameVar(llfn, name.as_ptr()),
+         llvm::LLVMRustCoverageComputeHash(name.as_ptr()))
+    };
+
+    let filename = Path::new(&ccx.sess().working_dir.0).join(&start.file.name);
+    let num_counters = regions.len() as u32;
+    coverage_cx.functions.borrow_mut().push(FunctionRecord {
+        name_md5: name_md5,
+        hash: hash,
+        filename: filename.to_string_lossy().into_owned(),
+        regions: regions,
+    });
+
+    Some(FunctionCoverage {
+        name_var: name_var,
+        hash: hash,
+        num_counters: num_counters,
+        counters: counters,
+    })
+}
+
+impl FunctionCoverage {
+    /// Counts a call of the function.
+    pub fn count_entry(&self, bcx: &Builder) {
+        self.increment(bcx, 0);
+    }
+
+    /// Counts a run of `bb`, if it has a counter.
+    pub fn count_block(&self, bcx: &Builder, bb: mir::BasicBlock) {
+        if let Some(counter) = self.counters[bb] {
+            self.increment(bcx, counter);
+        }
+    }
+
+    fn increment(&self, bcx: &Builder, counter: u32) {
+        let ccx = bcx.ccx;
+        let llfn = ccx.get_intrinsic("llvm.instrprof.increment");
+        bcx.call(llfn, &[consts::ptrcast(self.name_var, Type::i8p(ccx)),
+                         C_u64(ccx, self.hash),
+                         C_u32(ccx, self.num_counters),
+                         C_u32(ccx, counter)], None);
+    }
+}
+
+/// The source a block runs, from the start of its first statement to the
+/// end of its last one. Statements that only mark the scope of locals, and
+/// terminators that only pass control on, span more than the block runs.
+fn block_span<'tcx>(mir: &Mir<'tcx>, data: &mir::BasicBlockData<'tcx>) -> Option<Span> {
+    let statements = data.statements.iter().filter_map(|statement| {
+        match statement.kind {
+            mir::StatementKind::Assign(..) |
+            mir::StatementKind::SetDiscriminant { .. } |
+            mir::StatementKind::InlineAsm { .. } => Some(statement.source_info.span),
+            _ => None,
+        }
+    });
+    let terminator = data.terminator();
+    let terminator = match terminator.kind {
+        mir::TerminatorKind::Call { .. } |
+        mir::TerminatorKind::Assert { .. } |
+        mir::TerminatorKind::DropAndReplace { .. } => Some(terminator.source_info.span),
+        _ => None,
+    };
+
+    let mut block_span: Option<Span> = None;
+    for span in statements.chain(terminator) {
+        // Macro expansions are mapped to the place they were expanded at.
+        let mut span = span;
+        while span.ctxt != NO_EXPANSION && span.ctxt != mir.span.ctxt {
+            if let Some(info) = span.ctxt.outer().expn_info() {
+                span = info.call_site;
+            } else {
+                break;
+            }
+        }
+        if span.lo < mir.span.lo || span.hi > mir.span.hi {
+            continue;
+        }
+        block_span = Some(match block_span {
+            Some(block_span) => Span {
+                lo: cmp::min(block_span.lo, span.lo),
+                hi: cmp::max(block_span.hi, span.hi),
+                ctxt: block_span.ctxt,
+            },
+            None => span,
+        });
+    }
+    block_span
+}
+
+fn same_location(a: &llvm::CounterMappingRegion, b: &llvm::CounterMappingRegion) -> bool {
+    a.line_start == b.line_start && a.column_start == b.column_start &&
+    a.line_end == b.line_end && a.column_end == b.column_end
+}
+
+/// Writes the coverage mapping of the functions of the codegen unit.
+pub fn finalize(ccx: &CrateContext) {
+    let coverage_cx = match *ccx.coverage_cx() {
+        Some(ref coverage_cx) => coverage_cx,
+        None => return,
+    };
+    let functions = coverage_cx.functions.borrow();
+    if functions.is_empty() {
+        return;
+    }
+
+    let mut filenames = Vec::new();
+    let mut filename_ids = FxHashMap();
+    let mut records = Vec::new();
+    let mut mappings = Vec::new();
+    for function in functions.iter() {
+        let filename_id = *filename_ids.entry(&function.filename).or_insert_with(|| {
+            filenames.push(CString::new(&function.filename[..]).unwrap());
+            filenames.len() as u32 - 1
+        });
+        let mapping = llvm::build_byte_buffer(|s| unsafe {
+            llvm::LLVMRustCoverageWriteMappingToBuffer(&filename_id,
+                                                       1,
+                                                       function.regions.as_ptr(),
+                                                       function.regions.len() as u32,
+                                                       s);
+        });
+        records.push(C_struct(ccx, &[C_u64(ccx, function.name_md5),
+                                     C_u32(ccx, mapping.len() as u32),
+                                     C_u64(ccx, function.hash)], true));
+        mappings.extend(mapping);
+    }
+
+    let filename_ptrs = filenames.iter().map(|f| f.as_ptr()).collect::<Vec<_>>();
+    let mut data = llvm::build_byte_buffer(|s| unsafe {
+        llvm::LLVMRustCoverageWriteFilenamesSectionToBuffer(filename_ptrs.as_ptr(),
+                                                            filename_ptrs.len(),
+                                                            s);
+    });
+    let filenames_size = data.len();
+    data.extend(&mappings);
+    // The linker concatenates the mappings of all object files, each of which
+    // has to start 8-byte aligned.
+    while data.len() % 8 != 0 {
+        data.push(0);
+    }
+
+    let header = C_struct(ccx, &[C_u32(ccx, records.len() as u32),
+                                 C_u32(ccx, filenames_size as u32),
+                                 C_u32(ccx, mappings.len() as u32),
+                                 C_u32(ccx, unsafe { llvm::LLVMRustCoverageMappingVersion() })],
+                          false);
+    let records = C_array(val_ty(records[0]), &records);
+    let mapping = C_struct(ccx, &[header, records, C_bytes(ccx, &data)], false);
+
+    let section = if ccx.sess().target.target.options.is_like_osx {
+        "__LLVM_COV,__llvm_covmap"
+    } else {
+        "__llvm_covmap"
+    };
+    let section = CString::new(section).unwrap();
+    let llglobal = declare::define_global(ccx, "__llvm_coverage_mapping", val_ty(mapping))
+        .unwrap_or_else(|| bug!("symbol `__llvm_coverage_mapping` is already defined"));
+    unsafe {
+        llvm::LLVMSetInitializer(llglobal, mapping);
+        llvm::LLVMSetGlobalConstant(llglobal, llvm::True);
+        llvm::LLVMRustSetLinkage(llglobal, llvm::Linkage::InternalLinkage);
+        llvm::LLVMSetSection(llglobal, section.as_ptr());
+        llvm::LLVMSetAlignment(llglobal, 8);
+    }
+    ccx.used_statics().borrow_mut().push(consts::ptrcast(llglobal, Type::i8p(ccx)));
+}
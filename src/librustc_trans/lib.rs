@@ -114,6 +114,7 @@ mod collector;
 mod common;
 mod consts;
 mod context;
+mod coverageinfo;
 mod debuginfo;
 mod declare;
 mod glue;
@@ -171,7 +172,10 @@ pub struct CrateTranslation {
     pub exported_symbols: back::symbol_export::ExportedSymbols,
     pub no_builtins: bool,
     pub windows_subsystem: Option<String>,
-    pub linker_info: back::linker::LinkerInfo
+    pub linker_info: back::linker::LinkerInfo,
+    /// The names of the modules of upstream crates translated along with
+    /// this crate's by ThinLTO, whose objects are linked instead of the rlibs'.
+    pub thin_lto_upstream_modules: Vec<String>,
 }
 
 __build_diagnostic_array! { librustc_trans, DIAGNOSTICS }
@@ -44,6 +44,10 @@ impl<'a, 'tcx> MirContext<'a, 'tcx> {
 
         debug!("trans_block({:?}={:?})", bb, data);
 
+        if let Some(ref coverage) = self.coverage {
+            coverage.count_block(&bcx, bb);
+        }
+
         for statement in &data.statements {
             bcx = self.trans_statement(bcx, statement);
         }
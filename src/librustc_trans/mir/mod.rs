@@ -21,6 +21,7 @@ use rustc::session::config::FullDebugInfo;
 use base;
 use builder::Builder;
 use common::{self, CrateContext, Funclet};
+use coverageinfo::{self, FunctionCoverage};
 use debuginfo::{self, declare_local, VariableAccess, VariableKind, FunctionDebugContext};
 use monomorphize::Instance;
 use abi::FnType;
@@ -48,6 +49,9 @@ pub struct MirContext<'a, 'tcx:'a> {
 
     debug_context: debuginfo::FunctionDebugContext,
 
+    /// The coverage counters of the function, with `-Z instrument-coverage`.
+    coverage: Option<FunctionCoverage>,
+
     llfn: ValueRef,
 
     ccx: &'a CrateContext<'a, 'tcx>,
@@ -214,6 +218,11 @@ pub fn trans_mir<'a, 'tcx: 'a>(
     // Allocate a `Block` for every basic block, except
     // the start block, if nothing loops back to it.
     let reentrant_start_block = !mir.predecessors_for(mir::START_BLOCK).is_empty();
+    let coverage = coverageinfo::create_function_coverage(ccx, instance, mir, llfn,
+                                                          reentrant_start_block);
+    if let Some(ref coverage) = coverage {
+        coverage.count_entry(&bcx);
+    }
     let block_bcxs: IndexVec<mir::BasicBlock, BasicBlockRef> =
         mir.basic_blocks().indices().map(|bb| {
             if bb == mir::START_BLOCK && !reentrant_start_block {
@@ -241,6 +250,7 @@ pub fn trans_mir<'a, 'tcx: 'a>(
         scopes: scopes,
         locals: IndexVec::new(),
         debug_context: debug_context,
+        coverage: coverage,
         param_substs: {
             assert!(!instance.substs.needs_infer());
             instance.substs
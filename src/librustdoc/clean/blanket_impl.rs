@@ -0,0 +1,122 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Blanket impls, such as `impl<T: Display> ToString for T`, are only found
+//! on the pages of their traits. To show them on the pages of the local types
+//! they apply to as well, every struct, enum and union gets a copy of each
+//! blanket impl which trait selection says may apply to it.
+
+use std::mem;
+
+use rustc::hir;
+use rustc::hir::def_id::DefId;
+use rustc::infer::InferOk;
+use rustc::traits::{Obligation, ObligationCause, SelectionContext};
+use rustc::ty;
+use rustc::ty::subst::Subst;
+use syntax_pos::DUMMY_SP;
+
+use core::DocContext;
+
+use super::{inline, Clean, Item, ImplItem, ModuleItem, StructItem, EnumItem, UnionItem};
+
+/// Adds copies of the blanket impls which apply to the structs, enums and
+/// unions of the crate to the modules defining them.
+pub fn add_blanket_impls(cx: &DocContext, krate: &mut Item) {
+    let mut blanket_impls = vec![];
+    collect_local(cx, krate, &mut blanket_impls);
+    // The crate metadata lists blanket impls separately, so the impls of
+    // other crates don't all have to be looked at to find them.
+    for did in cx.tcx.sess.cstore.blanket_impls() {
+        if is_blanket_impl(cx, did) {
+            let mut items = vec![];
+            inline::build_impl_uncached(cx, did, &mut items);
+            blanket_impls.extend(items.into_iter().filter(|item| item.def_id == did));
+        }
+    }
+    if !blanket_impls.is_empty() {
+        add_to_module(cx, krate, &blanket_impls);
+    }
+}
+
+fn is_blanket_impl(cx: &DocContext, did: DefId) -> bool {
+    if cx.tcx.is_default_impl(did) ||
+       cx.tcx.impl_polarity(did) == hir::ImplPolarity::Negative {
+        return false;
+    }
+    match cx.tcx.type_of(did).sty {
+        ty::TyParam(..) => true,
+        _ => false,
+    }
+}
+
+fn collect_local(cx: &DocContext, module: &Item, blanket_impls: &mut Vec<Item>) {
+    if let ModuleItem(ref m) = module.inner {
+        for item in &m.items {
+            match item.inner {
+                ModuleItem(..) => collect_local(cx, item, blanket_impls),
+                ImplItem(ref i) if i.trait_.is_some() && is_blanket_impl(cx, item.def_id) => {
+                    blanket_impls.push(item.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn add_to_module(cx: &DocContext, module: &mut Item, blanket_impls: &[Item]) {
+    let m = match module.inner {
+        ModuleItem(ref mut m) => m,
+        _ => return,
+    };
+    let mut copies = vec![];
+    for item in &mut m.items {
+        match item.inner {
+            ModuleItem(..) => add_to_module(cx, item, blanket_impls),
+            StructItem(..) | EnumItem(..) | UnionItem(..) => {
+                for blanket_impl in blanket_impls {
+                    if applies(cx, blanket_impl.def_id, item.def_id) {
+                        let mut copy = blanket_impl.clone();
+                        if let ImplItem(ref mut i) = copy.inner {
+                            let for_ = cx.tcx.type_of(item.def_id).clean(cx);
+                            i.blanket_impl = Some(mem::replace(&mut i.for_, for_));
+                        }
+                        copies.push(copy);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    m.items.extend(copies);
+}
+
+/// Whether the impl `impl_did` may apply to the type `ty_did`, with any
+/// generic parameters of the type left as they are.
+fn applies(cx: &DocContext, impl_did: DefId, ty_did: DefId) -> bool {
+    let tcx = cx.tcx;
+    let ty = tcx.type_of(ty_did);
+    let param_env = tcx.param_env(ty_did);
+    tcx.infer_ctxt().enter(|infcx| {
+        let substs = infcx.fresh_substs_for_item(DUMMY_SP, impl_did);
+        let impl_ty = tcx.type_of(impl_did).subst(infcx.tcx, substs);
+        let cause = ObligationCause::dummy();
+        let mut obligations = match infcx.at(&cause, param_env).eq(impl_ty, ty) {
+            Ok(InferOk { obligations, .. }) => obligations,
+            Err(_) => return false,
+        };
+        let predicates = tcx.predicates_of(impl_did).instantiate(infcx.tcx, substs).predicates;
+        obligations.extend(predicates.into_iter().map(|predicate| {
+            Obligation::new(cause.clone(), param_env, predicate)
+        }));
+        let mut selcx = SelectionContext::new(&infcx);
+        obligations.iter().all(|obligation| selcx.evaluate_obligation(obligation))
+    })
+}
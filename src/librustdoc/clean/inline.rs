@@ -112,7 +112,7 @@ pub fn try_inline(cx: &DocContext, def: Def, name: ast::Name)
 }
 
 pub fn load_attrs(cx: &DocContext, did: DefId) -> clean::Attributes {
-    cx.tcx.get_attrs(did).clean(cx)
+    clean::Attributes::from_ast(&cx.tcx.get_attrs(did))
 }
 
 /// Record an external fully qualified name in the external_paths cache.
@@ -276,7 +276,11 @@ pub fn build_impl(cx: &DocContext, did: DefId, ret: &mut Vec<clean::Item>) {
     if !cx.renderinfo.borrow_mut().inlined.insert(did) {
         return
     }
+    build_impl_uncached(cx, did, ret)
+}
 
+/// Like `build_impl`, but cleans the impl even if it was inlined before.
+pub fn build_impl_uncached(cx: &DocContext, did: DefId, ret: &mut Vec<clean::Item>) {
     let attrs = load_attrs(cx, did);
     let tcx = cx.tcx;
     let associated_trait = tcx.impl_trait_ref(did);
@@ -419,6 +423,7 @@ pub fn build_impl(cx: &DocContext, did: DefId, ret: &mut Vec<clean::Item>) {
             generics: (tcx.generics_of(did), &predicates).clean(cx),
             items: trait_items,
             polarity: Some(polarity.clean(cx)),
+            blanket_impl: None,
         }),
         source: tcx.def_span(did).clean(cx),
         name: None,
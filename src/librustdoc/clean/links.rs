@@ -0,0 +1,341 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Resolution of links in doc comments whose targets are Rust paths, such as
+//! `[Vec::push]` or `[the trait](io::Read)`.
+//!
+//! The resolver is gone by the time rustdoc cleans the crate, so paths are
+//! looked up by hand: in the HIR for local modules and in the crate metadata
+//! for external ones. Paths are resolved relative to the module the doc
+//! comment appears in, then the crate root, then the standard prelude.
+
+use rustc::hir;
+use rustc::hir::def::Def;
+use rustc::hir::def_id::{CrateNum, DefId, CRATE_DEF_INDEX, LOCAL_CRATE};
+use rustc::ty;
+use syntax::ast;
+use syntax::ext::base::MacroKind;
+use syntax_pos::DUMMY_SP;
+
+use core::DocContext;
+use html::markdown;
+
+use super::{inline, register_def, Attributes, DocLink, TypeKind};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Namespace {
+    Type,
+    Value,
+    Macro,
+}
+
+fn namespace(def: Def) -> Option<Namespace> {
+    match def {
+        Def::Mod(..) | Def::Struct(..) | Def::Union(..) | Def::Enum(..) |
+        Def::Variant(..) | Def::Trait(..) | Def::TyAlias(..) |
+        Def::AssociatedTy(..) => Some(Namespace::Type),
+        Def::Fn(..) | Def::Const(..) | Def::Static(..) | Def::StructCtor(..) |
+        Def::VariantCtor(..) | Def::Method(..) | Def::AssociatedConst(..) => {
+            Some(Namespace::Value)
+        }
+        Def::Macro(..) => Some(Namespace::Macro),
+        _ => None,
+    }
+}
+
+/// Resolves the path links in the doc strings of `attrs`, which belong to an
+/// item in `module`, and records them in `attrs.links`.
+pub fn resolve_links(cx: &DocContext, module: ast::NodeId, attrs: &mut Attributes) {
+    let module = cx.tcx.hir.local_def_id(module);
+    let mut links: Vec<DocLink> = vec![];
+    for doc in &attrs.doc_strings {
+        for link in markdown::find_path_links(doc) {
+            if links.iter().any(|l| l.target == link.target) {
+                continue
+            }
+            let (path, suffix) = markdown::rust_path(link.target).unwrap();
+            match resolve(cx, module, path, suffix) {
+                Some((def, fragment)) => {
+                    links.push(DocLink {
+                        target: link.target.to_string(),
+                        did: register_link(cx, def),
+                        fragment: fragment,
+                    });
+                }
+                None => {
+                    // A single word may just as well be text in brackets
+                    // or a relative URL, so only warn about targets which
+                    // are unmistakably paths.
+                    let explicit = path.contains("::") || link.target.contains('`') ||
+                                   !suffix.is_empty();
+                    if explicit {
+                        cx.sess().span_warn(attrs.span.unwrap_or(DUMMY_SP),
+                                            &format!("unresolved link to `{}`", link.target));
+                    }
+                }
+            }
+        }
+    }
+    attrs.links = links;
+}
+
+/// Makes sure the page of `def` can be found when rendering, and returns the
+/// id of the item the link points to.
+fn register_link(cx: &DocContext, def: Def) -> DefId {
+    match def {
+        Def::Const(did) if !did.is_local() => {
+            inline::record_extern_fqn(cx, did, TypeKind::Const);
+            did
+        }
+        _ => register_def(cx, def),
+    }
+}
+
+fn resolve(cx: &DocContext, module: DefId, path: &str, suffix: &str)
+           -> Option<(Def, Option<String>)> {
+    let namespaces: &[Namespace] = match suffix {
+        "!" => &[Namespace::Macro],
+        "()" => &[Namespace::Value],
+        _ => &[Namespace::Type, Namespace::Value, Namespace::Macro],
+    };
+
+    let root = DefId { krate: LOCAL_CRATE, index: CRATE_DEF_INDEX };
+    let mut segments: Vec<&str> = path.split("::").collect();
+    let scopes = if path.starts_with("::") {
+        segments.remove(0);
+        vec![root]
+    } else if segments[0] == "crate" {
+        segments.remove(0);
+        vec![root]
+    } else if segments[0] == "self" || segments[0] == "super" {
+        let mut scope = module;
+        if segments[0] == "self" {
+            segments.remove(0);
+        }
+        while !segments.is_empty() && segments[0] == "super" {
+            segments.remove(0);
+            scope = match cx.tcx.parent_def_id(scope) {
+                Some(parent) => parent,
+                None => return None,
+            };
+        }
+        vec![scope]
+    } else {
+        let mut scopes = vec![module, root];
+        scopes.extend(prelude(cx));
+        scopes
+    };
+
+    if segments.is_empty() {
+        return Some((Def::Mod(scopes[0]), None));
+    }
+
+    for &ns in namespaces {
+        if let Some(def) = resolve_path(cx, &scopes, &segments, ns) {
+            return Some(fixup(cx, def));
+        }
+    }
+
+    // The last segment may name an associated item, field or variant of the
+    // type the rest of the path resolves to.
+    if suffix != "!" && segments.len() > 1 {
+        let (item, ty_path) = segments.split_last().unwrap();
+        if let Some(def) = resolve_path(cx, &scopes, ty_path, Namespace::Type) {
+            return associated_item(cx, def, item, suffix == "()");
+        }
+    }
+    None
+}
+
+fn resolve_path(cx: &DocContext, scopes: &[DefId], segments: &[&str], ns: Namespace)
+                -> Option<Def> {
+    let (last, modules) = segments.split_last().unwrap();
+    let mut scopes = scopes.to_vec();
+    for segment in modules {
+        match lookup(cx, &scopes, segment, Namespace::Type) {
+            Some(Def::Mod(did)) => scopes = vec![did],
+            _ => return None,
+        }
+    }
+    lookup(cx, &scopes, last, ns)
+}
+
+fn lookup(cx: &DocContext, scopes: &[DefId], name: &str, ns: Namespace) -> Option<Def> {
+    scopes.iter().filter_map(|&scope| {
+        module_children(cx, scope, 0).into_iter().find(|&(child, def)| {
+            child == name && namespace(def) == Some(ns)
+        }).map(|(_, def)| def)
+    }).next()
+}
+
+/// The items visible in the module `did`, including imports. Glob imports
+/// come last so that they are shadowed by everything else.
+fn module_children(cx: &DocContext, did: DefId, depth: usize) -> Vec<(ast::Name, Def)> {
+    if !did.is_local() {
+        return cx.tcx.sess.cstore.item_children(did, cx.tcx.sess).into_iter().map(|export| {
+            (export.ident.name, export.def)
+        }).collect();
+    }
+
+    let id = match cx.tcx.hir.as_local_node_id(did) {
+        Some(id) => id,
+        None => return vec![],
+    };
+    let module = if id == ast::CRATE_NODE_ID {
+        &cx.tcx.hir.krate().module
+    } else {
+        match cx.tcx.hir.expect_item(id).node {
+            hir::ItemMod(ref module) => module,
+            _ => return vec![],
+        }
+    };
+
+    let mut children = vec![];
+    let mut globs = vec![];
+    for item_id in &module.item_ids {
+        let item = cx.tcx.hir.expect_item(item_id.id);
+        let def_id = cx.tcx.hir.local_def_id(item.id);
+        let def = match item.node {
+            hir::ItemExternCrate(..) => {
+                match cx.tcx.sess.cstore.extern_mod_stmt_cnum(item.id) {
+                    Some(cnum) => Def::Mod(DefId { krate: cnum, index: CRATE_DEF_INDEX }),
+                    None => continue,
+                }
+            }
+            hir::ItemUse(ref path, hir::UseKind::Single) => path.def,
+            hir::ItemUse(ref path, hir::UseKind::Glob) => {
+                if let Def::Mod(module) = path.def {
+                    globs.push(module);
+                }
+                continue
+            }
+            hir::ItemStatic(_, mutbl, _) => Def::Static(def_id, mutbl == hir::MutMutable),
+            hir::ItemConst(..) => Def::Const(def_id),
+            hir::ItemFn(..) => Def::Fn(def_id),
+            hir::ItemMod(..) => Def::Mod(def_id),
+            hir::ItemForeignMod(ref foreign) => {
+                for item in &foreign.items {
+                    let def_id = cx.tcx.hir.local_def_id(item.id);
+                    children.push((item.name, match item.node {
+                        hir::ForeignItemFn(..) => Def::Fn(def_id),
+                        hir::ForeignItemStatic(_, mutbl) => Def::Static(def_id, mutbl),
+                    }));
+                }
+                continue
+            }
+            hir::ItemTy(..) => Def::TyAlias(def_id),
+            hir::ItemEnum(..) => Def::Enum(def_id),
+            hir::ItemStruct(..) => Def::Struct(def_id),
+            hir::ItemUnion(..) => Def::Union(def_id),
+            hir::ItemTrait(..) => Def::Trait(def_id),
+            hir::ItemUse(_, hir::UseKind::ListStem) |
+            hir::ItemGlobalAsm(..) |
+            hir::ItemDefaultImpl(..) |
+            hir::ItemImpl(..) => continue,
+        };
+        children.push((item.name, def));
+    }
+    if id == ast::CRATE_NODE_ID {
+        for def in &cx.tcx.hir.krate().exported_macros {
+            let def_id = cx.tcx.hir.local_def_id(def.id);
+            children.push((def.name, Def::Macro(def_id, MacroKind::Bang)));
+        }
+    }
+    // Glob imports may refer to each other, so give up eventually.
+    if depth < 8 {
+        for module in globs {
+            children.extend(module_children(cx, module, depth + 1));
+        }
+    }
+    children
+}
+
+/// The standard prelude, along with the root of the standard library for
+/// its macros.
+fn prelude(cx: &DocContext) -> Vec<DefId> {
+    let cstore = &cx.tcx.sess.cstore;
+    let std = ["std", "core"].iter().filter_map(|&name| {
+        cstore.crates().into_iter().find(|&cnum: &CrateNum| cstore.crate_name(cnum) == name)
+    }).next();
+    let root = match std {
+        Some(cnum) => DefId { krate: cnum, index: CRATE_DEF_INDEX },
+        None => return vec![],
+    };
+    let find_mod = |did: DefId, name: &str| {
+        cstore.item_children(did, cx.tcx.sess).into_iter().filter_map(|export| {
+            match export.def {
+                Def::Mod(did) if export.ident.name == name => Some(did),
+                _ => None,
+            }
+        }).next()
+    };
+    match find_mod(root, "prelude").and_then(|prelude| find_mod(prelude, "v1")) {
+        Some(v1) => vec![v1, root],
+        None => vec![root],
+    }
+}
+
+/// Links to variants and constructors go to their enum or struct.
+fn fixup(cx: &DocContext, def: Def) -> (Def, Option<String>) {
+    match def {
+        Def::Variant(did) => {
+            (def, Some(format!("variant.{}", cx.tcx.item_name(did))))
+        }
+        Def::VariantCtor(did, _) => fixup(cx, Def::Variant(cx.tcx.parent_def_id(did).unwrap())),
+        Def::StructCtor(did, _) => (Def::Struct(cx.tcx.parent_def_id(did).unwrap()), None),
+        _ => (def, None),
+    }
+}
+
+/// Resolves `name` as a variant, field or associated item of the type or
+/// trait `def`, returning the type or trait along with the fragment of the
+/// item on its page.
+fn associated_item(cx: &DocContext, def: Def, name: &str, is_value: bool)
+                   -> Option<(Def, Option<String>)> {
+    let did = match def {
+        Def::Trait(did) => {
+            return cx.tcx.associated_items(did).find(|item| item.name == name).map(|item| {
+                let kind = match item.kind {
+                    ty::AssociatedKind::Method if item.defaultness.has_value() => "method",
+                    ty::AssociatedKind::Method => "tymethod",
+                    ty::AssociatedKind::Type => "associatedtype",
+                    ty::AssociatedKind::Const => "associatedconstant",
+                };
+                (def, Some(format!("{}.{}", kind, name)))
+            });
+        }
+        Def::Enum(did) | Def::Struct(did) | Def::Union(did) => did,
+        _ => return None,
+    };
+
+    if !is_value {
+        let adt = cx.tcx.adt_def(did);
+        if let Def::Enum(..) = def {
+            if let Some(variant) = adt.variants.iter().find(|v| v.name == name) {
+                return Some((Def::Variant(variant.did), Some(format!("variant.{}", name))));
+            }
+        } else if adt.struct_variant().fields.iter().any(|f| f.name == name) {
+            return Some((def, Some(format!("structfield.{}", name))));
+        }
+    }
+
+    for &impl_did in cx.tcx.inherent_impls(did).iter() {
+        let item = cx.tcx.associated_items(impl_did).find(|item| item.name == name);
+        if let Some(item) = item {
+            let kind = match item.kind {
+                ty::AssociatedKind::Method => "method",
+                ty::AssociatedKind::Type => "associatedtype",
+                ty::AssociatedKind::Const => "associatedconstant",
+            };
+            return Some((def, Some(format!("{}.{}", kind, name))));
+        }
+    }
+    None
+}
@@ -52,6 +52,8 @@ use visit_ast;
 use html::item_type::ItemType;
 
 pub mod inline;
+mod blanket_impl;
+mod links;
 mod simplify;
 
 // extract the stability index for a node from tcx, if possible
@@ -138,6 +140,7 @@ impl<'a, 'tcx> Clean<Crate> for visit_ast::RustdocVisitor<'a, 'tcx> {
         // Clean the crate, translating the entire libsyntax AST to one that is
         // understood by rustdoc.
         let mut module = self.module.clean(cx);
+        blanket_impl::add_blanket_impls(cx, &mut module);
 
         let ExternalCrate { name, src, primitives, .. } = LOCAL_CRATE.clean(cx);
         {
@@ -207,7 +210,7 @@ impl Clean<ExternalCrate> for CrateNum {
         // rendering by delegating everything to a hash map.
         let as_primitive = |def: Def| {
             if let Def::Mod(def_id) = def {
-                let attrs = cx.tcx.get_attrs(def_id).clean(cx);
+                let attrs = Attributes::from_ast(&cx.tcx.get_attrs(def_id));
                 let mut prim = None;
                 for attr in attrs.lists("doc") {
                     if let Some(v) = attr.value_str() {
@@ -248,7 +251,7 @@ impl Clean<ExternalCrate> for CrateNum {
         ExternalCrate {
             name: cx.tcx.crate_name(*self).to_string(),
             src: PathBuf::from(krate_src),
-            attrs: cx.tcx.get_attrs(root).clean(cx),
+            attrs: Attributes::from_ast(&cx.tcx.get_attrs(root)),
             primitives: primitives,
         }
     }
@@ -429,6 +432,11 @@ impl Clean<Item> for doctree::Module {
             "".to_string()
         };
 
+        // Links in the docs of the module and of its items are resolved
+        // relative to the module.
+        cx.mod_ids.borrow_mut().push(self.id);
+        let attrs = self.attrs.clean(cx);
+
         let mut items: Vec<Item> = vec![];
         items.extend(self.extern_crates.iter().map(|x| x.clean(cx)));
         items.extend(self.imports.iter().flat_map(|x| x.clean(cx)));
@@ -445,6 +453,7 @@ impl Clean<Item> for doctree::Module {
         items.extend(self.impls.iter().flat_map(|x| x.clean(cx)));
         items.extend(self.macros.iter().map(|x| x.clean(cx)));
         items.extend(self.def_traits.iter().map(|x| x.clean(cx)));
+        cx.mod_ids.borrow_mut().pop();
 
         // determine if we should display the inner contents or
         // the outer `mod` item for the source code.
@@ -463,7 +472,7 @@ impl Clean<Item> for doctree::Module {
 
         Item {
             name: Some(name),
-            attrs: self.attrs.clean(cx),
+            attrs: attrs,
             source: whence.clean(cx),
             visibility: self.vis.clean(cx),
             stability: self.stab.clean(cx),
@@ -537,6 +546,18 @@ pub struct Attributes {
     pub doc_strings: Vec<String>,
     pub other_attrs: Vec<ast::Attribute>,
     pub span: Option<syntax_pos::Span>,
+    /// The links in the doc strings whose targets resolved to items.
+    pub links: Vec<DocLink>,
+}
+
+/// A link in a doc comment to an item named by its path, such as `[Vec]`.
+#[derive(Clone, RustcEncodable, RustcDecodable, PartialEq, Debug)]
+pub struct DocLink {
+    /// The target of the link as it is written in the doc comment.
+    pub target: String,
+    pub did: DefId,
+    /// The anchor of a field, variant or associated item on the page of `did`.
+    pub fragment: Option<String>,
 }
 
 impl Attributes {
@@ -562,6 +583,7 @@ impl Attributes {
             doc_strings: doc_strings,
             other_attrs: other_attrs,
             span: sp,
+            links: vec![],
         }
     }
 
@@ -579,8 +601,13 @@ impl AttributesExt for Attributes {
 }
 
 impl Clean<Attributes> for [ast::Attribute] {
-    fn clean(&self, _cx: &DocContext) -> Attributes {
-        Attributes::from_ast(self)
+    fn clean(&self, cx: &DocContext) -> Attributes {
+        let mut attrs = Attributes::from_ast(self);
+        let module = cx.mod_ids.borrow().last().cloned();
+        if let Some(module) = module {
+            links::resolve_links(cx, module, &mut attrs);
+        }
+        attrs
     }
 }
 
@@ -1958,7 +1985,7 @@ impl<'tcx> Clean<Item> for ty::FieldDef {
     fn clean(&self, cx: &DocContext) -> Item {
         Item {
             name: Some(self.name).clean(cx),
-            attrs: cx.tcx.get_attrs(self.did).clean(cx),
+            attrs: Attributes::from_ast(&cx.tcx.get_attrs(self.did)),
             source: cx.tcx.def_span(self.did).clean(cx),
             visibility: self.vis.clean(cx),
             stability: get_stability(cx, self.did),
@@ -2128,7 +2155,7 @@ impl<'tcx> Clean<Item> for ty::VariantDef {
                         Item {
                             source: cx.tcx.def_span(field.did).clean(cx),
                             name: Some(field.name.clean(cx)),
-                            attrs: cx.tcx.get_attrs(field.did).clean(cx),
+                            attrs: Attributes::from_ast(&cx.tcx.get_attrs(field.did)),
                             visibility: field.vis.clean(cx),
                             def_id: field.did,
                             stability: get_stability(cx, field.did),
@@ -2465,6 +2492,10 @@ pub struct Impl {
     pub for_: Type,
     pub items: Vec<Item>,
     pub polarity: Option<ImplPolarity>,
+    /// For the copies of blanket impls made for each type they apply to, the
+    /// type the blanket impl is for, such as `T`. `for_` is the type the
+    /// copy was made for.
+    pub blanket_impl: Option<Type>,
 }
 
 impl Clean<Vec<Item>> for doctree::Impl {
@@ -2502,6 +2533,7 @@ impl Clean<Vec<Item>> for doctree::Impl {
                 for_: self.for_.clean(cx),
                 items: items,
                 polarity: Some(self.polarity.clean(cx)),
+                blanket_impl: None,
             }),
         });
         ret
@@ -65,6 +65,8 @@ pub struct DocContext<'a, 'tcx: 'a> {
     pub ty_substs: RefCell<FxHashMap<Def, clean::Type>>,
     /// Table node id of lifetime parameter definition -> substituted lifetime
     pub lt_substs: RefCell<FxHashMap<ast::NodeId, clean::Lifetime>>,
+    /// The modules being cleaned, innermost last, for resolving links in docs
+    pub mod_ids: RefCell<Vec<ast::NodeId>>,
 }
 
 impl<'a, 'tcx> DocContext<'a, 'tcx> {
@@ -200,6 +202,7 @@ pub fn run_core(search_paths: SearchPaths,
             renderinfo: Default::default(),
             ty_substs: Default::default(),
             lt_substs: Default::default(),
+            mod_ids: Default::default(),
         };
         debug!("crate: {:?}", tcx.hir.krate());
 
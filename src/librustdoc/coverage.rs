@@ -0,0 +1,192 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Documentation coverage (`--show-coverage`).
+//!
+//! Counts how many of the items left in the crate by the strip passes are
+//! documented and how many have a code example, per module and per item kind.
+
+use std::collections::BTreeMap;
+use std::ops::AddAssign;
+
+use serialize::json::{Json, ToJson};
+
+use clean;
+use html::item_type::ItemType;
+use html::markdown;
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ItemCount {
+    pub total: u64,
+    pub with_docs: u64,
+    pub with_examples: u64,
+}
+
+impl ItemCount {
+    fn count_item(&mut self, has_docs: bool, has_example: bool) {
+        self.total += 1;
+        if has_docs {
+            self.with_docs += 1;
+        }
+        if has_example {
+            self.with_examples += 1;
+        }
+    }
+
+    fn percentage(&self, n: u64) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            n as f64 * 100.0 / self.total as f64
+        }
+    }
+
+    /// The percentage of items with documentation.
+    pub fn docs_percentage(&self) -> f64 {
+        self.percentage(self.with_docs)
+    }
+
+    /// The percentage of items with a code example.
+    pub fn examples_percentage(&self) -> f64 {
+        self.percentage(self.with_examples)
+    }
+}
+
+impl AddAssign for ItemCount {
+    fn add_assign(&mut self, other: ItemCount) {
+        self.total += other.total;
+        self.with_docs += other.with_docs;
+        self.with_examples += other.with_examples;
+    }
+}
+
+impl ToJson for ItemCount {
+    fn to_json(&self) -> Json {
+        let mut map = BTreeMap::new();
+        map.insert("total".to_string(), self.total.to_json());
+        map.insert("with_docs".to_string(), self.with_docs.to_json());
+        map.insert("with_examples".to_string(), self.with_examples.to_json());
+        Json::Object(map)
+    }
+}
+
+/// The coverage of a whole crate.
+#[derive(Default, Debug)]
+pub struct Coverage {
+    pub modules: BTreeMap<String, ItemCount>,
+    pub kinds: BTreeMap<&'static str, ItemCount>,
+    pub total: ItemCount,
+}
+
+impl Coverage {
+    pub fn new(krate: &clean::Crate) -> Coverage {
+        let mut coverage = Coverage::default();
+        if let Some(ref module) = krate.module {
+            coverage.visit(module, &mut vec![krate.name.clone()]);
+        }
+        coverage
+    }
+
+    fn visit(&mut self, item: &clean::Item, path: &mut Vec<String>) {
+        let is_module = item.is_mod() && !item.is_crate();
+        if is_module {
+            path.push(item.name.clone().unwrap_or_default());
+        }
+
+        let counted = match item.inner {
+            // Stripped items are not part of the documentation, and neither
+            // are imports. The items of trait impls are documented by the
+            // trait, so only the impls themselves could be counted, but they
+            // are not expected to have docs of their own.
+            clean::StrippedItem(..) |
+            clean::ImportItem(..) |
+            clean::ExternCrateItem(..) |
+            clean::ImplItem(..) |
+            clean::DefaultImplItem(..) => false,
+            _ => true,
+        };
+        if counted {
+            let docs = item.doc_value().unwrap_or("");
+            let has_docs = !docs.trim().is_empty();
+            let has_example = has_docs && markdown::has_code_example(docs);
+            let kind = ItemType::from(item).css_class();
+            self.modules.entry(path.join("::")).or_insert_with(ItemCount::default)
+                .count_item(has_docs, has_example);
+            self.kinds.entry(kind).or_insert_with(ItemCount::default)
+                .count_item(has_docs, has_example);
+            self.total.count_item(has_docs, has_example);
+        }
+
+        match item.inner {
+            clean::ModuleItem(ref m) => self.visit_all(&m.items, path),
+            clean::StructItem(ref s) => self.visit_all(&s.fields, path),
+            clean::UnionItem(ref u) => self.visit_all(&u.fields, path),
+            clean::EnumItem(ref e) => self.visit_all(&e.variants, path),
+            clean::VariantItem(clean::Variant { kind: clean::VariantKind::Struct(ref s) }) => {
+                self.visit_all(&s.fields, path)
+            }
+            clean::TraitItem(ref t) => self.visit_all(&t.items, path),
+            clean::ImplItem(ref i) if i.trait_.is_none() => self.visit_all(&i.items, path),
+            _ => {}
+        }
+
+        if is_module {
+            path.pop();
+        }
+    }
+
+    fn visit_all(&mut self, items: &[clean::Item], path: &mut Vec<String>) {
+        for item in items {
+            self.visit(item, path);
+        }
+    }
+
+    /// Prints the coverage as a table.
+    pub fn print_table(&self) {
+        fn row(name: &str, count: &ItemCount) {
+            println!("| {:<40} | {:>6} | {:>6} {:>5.1}% | {:>6} {:>5.1}% |",
+                     name, count.total,
+                     count.with_docs, count.docs_percentage(),
+                     count.with_examples, count.examples_percentage());
+        }
+        let separator = format!("+-{0:-<40}-+-{0:-<6}-+-{0:-<14}-+-{0:-<14}-+", "");
+
+        println!("{}", separator);
+        println!("| {:<40} | {:>6} | {:>14} | {:>14} |",
+                 "Module", "Items", "Documented", "Examples");
+        println!("{}", separator);
+        for (name, count) in &self.modules {
+            row(name, count);
+        }
+        println!("{}", separator);
+        println!("| {:<40} | {:>6} | {:>14} | {:>14} |",
+                 "Item kind", "Items", "Documented", "Examples");
+        println!("{}", separator);
+        for (name, count) in &self.kinds {
+            row(name, count);
+        }
+        println!("{}", separator);
+        row("Total", &self.total);
+        println!("{}", separator);
+    }
+}
+
+impl ToJson for Coverage {
+    fn to_json(&self) -> Json {
+        let kinds: BTreeMap<String, ItemCount> = self.kinds.iter()
+                                                           .map(|(k, v)| (k.to_string(), *v))
+                                                           .collect();
+        let mut map = BTreeMap::new();
+        map.insert("modules".to_string(), self.modules.to_json());
+        map.insert("kinds".to_string(), kinds.to_json());
+        map.insert("total".to_string(), self.total.to_json());
+        Json::Object(map)
+    }
+}
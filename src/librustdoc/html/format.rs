@@ -429,7 +429,10 @@ pub fn href(did: DefId) -> Option<(String, ItemType, Vec<String>)> {
             Some(&(ref fqp, shortty)) => {
                 (fqp, shortty, match cache.extern_locations[&did.krate] {
                     (.., render::Remote(ref s)) => s.to_string(),
-                    (.., render::Local) => repeat("../").take(loc.len()).collect(),
+                    (.., render::Local) => {
+                        render::LINKED_CRATES.with(|l| l.borrow_mut().insert(did.krate));
+                        repeat("../").take(loc.len()).collect()
+                    }
                     (.., render::Unknown) => return None,
                 })
             }
@@ -541,6 +544,7 @@ fn primitive_link(f: &mut fmt::Formatter,
                         Some((cname, s.to_string()))
                     }
                     (ref cname, _, render::Local) => {
+                        render::LINKED_CRATES.with(|l| l.borrow_mut().insert(def_id.krate));
                         let len = CURRENT_LOCATION_KEY.with(|s| s.borrow().len());
                         Some((cname, repeat("../").take(len).collect::<String>()))
                     }
@@ -826,7 +830,11 @@ fn fmt_impl(i: &clean::Impl,
         write!(f, " for ")?;
     }
 
-    fmt_type(&i.for_, f, use_absolute)?;
+    if let Some(ref ty) = i.blanket_impl {
+        fmt_type(ty, f, use_absolute)?;
+    } else {
+        fmt_type(&i.for_, f, use_absolute)?;
+    }
 
     fmt::Display::fmt(&WhereClause { gens: &i.generics, indent: 0, end_newline: true }, f)?;
     Ok(())
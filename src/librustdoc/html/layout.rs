@@ -125,7 +125,9 @@ r##"<!DOCTYPE html>
 
                 <p>
                     Search functions by type signature (e.g.
-                    <code>vec -> usize</code> or <code>* -> vec</code>)
+                    <code>Vec&lt;T&gt; -> usize</code>, <code>&amp;str -> String</code>
+                    or <code>* -> vec</code>). Inputs may be given in any order,
+                    and single capital letters like <code>T</code> match any type.
                 </p>
             </div>
         </div>
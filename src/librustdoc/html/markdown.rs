@@ -1057,6 +1057,256 @@ impl<'a> fmt::Display for MarkdownSummaryLine<'a> {
     }
 }
 
+/// A link in a doc comment whose target may be a Rust path, such as
+/// `[Vec::push]`, `` [`io::Read`] `` or `[the macro](vec!)`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PathLink<'a> {
+    /// The target as written: the destination of an inline link, or the
+    /// label of a reference link with no matching link definition.
+    pub target: &'a str,
+    /// The text of the link.
+    pub text: &'a str,
+    /// The byte range of the whole link.
+    pub span: (usize, usize),
+}
+
+/// If `target` is written like a Rust path, returns the path along with
+/// its suffix: `()` for functions, `!` for macros, or nothing. The whole
+/// target may be surrounded by backticks.
+pub fn rust_path(target: &str) -> Option<(&str, &str)> {
+    let mut target = target.trim();
+    if target.len() > 2 && target.starts_with('`') && target.ends_with('`') {
+        target = target[1..target.len() - 1].trim();
+    }
+    let (path, suffix) = if target.ends_with("()") {
+        (&target[..target.len() - 2], "()")
+    } else if target.ends_with('!') {
+        (&target[..target.len() - 1], "!")
+    } else {
+        (target, "")
+    };
+    let relative = if path.starts_with("::") { &path[2..] } else { path };
+    let is_ident = |s: &str| {
+        let mut chars = s.chars();
+        match chars.next() {
+            Some(c) if c.is_alphabetic() || c == '_' => {}
+            _ => return false,
+        }
+        s != "_" && chars.all(|c| c.is_alphanumeric() || c == '_')
+    };
+    if relative.split("::").all(is_ident) {
+        Some((path, suffix))
+    } else {
+        None
+    }
+}
+
+/// Returns the index just past the code span starting at `start`, or past
+/// its opening backticks if it is never closed.
+fn skip_code_span(md: &str, start: usize) -> usize {
+    let bytes = md.as_bytes();
+    let ticks = bytes[start..].iter().take_while(|&&b| b == b'`').count();
+    let mut i = start + ticks;
+    while i < bytes.len() {
+        if bytes[i] == b'`' {
+            let run = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+            if run == ticks {
+                return i + run;
+            }
+            i += run;
+        } else {
+            i += 1;
+        }
+    }
+    start + ticks
+}
+
+/// Returns whether a blank line, which ends a paragraph, starts at `i`.
+fn at_blank_line(md: &str, i: usize) -> bool {
+    md.as_bytes()[i] == b'\n' && md[i + 1..].split('\n').next().unwrap().trim().is_empty()
+}
+
+/// Returns the index of the `close` delimiter matching the `open` one just
+/// before `start`, skipping nested pairs, escapes and code spans. Links
+/// can't span paragraphs, so the search stops at a blank line.
+fn find_closing(md: &str, start: usize, open: u8, close: u8) -> Option<usize> {
+    let bytes = md.as_bytes();
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' => i = skip_code_span(md, i),
+            b'\n' if at_blank_line(md, i) => return None,
+            b if b == close && depth == 0 => return Some(i),
+            b if b == close => {
+                depth -= 1;
+                i += 1;
+            }
+            b if b == open => {
+                depth += 1;
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Returns the label of a link reference definition (`[label]: url`).
+fn link_definition(line: &str) -> Option<&str> {
+    let trimmed = line.trim_left();
+    if line.len() - trimmed.len() > 3 || !trimmed.starts_with('[') {
+        return None;
+    }
+    match find_closing(trimmed, 1, b'[', b']') {
+        Some(end) if trimmed[end + 1..].starts_with(':') => Some(&trimmed[1..end]),
+        _ => None,
+    }
+}
+
+/// Returns the byte ranges of `md` which hold no links: fenced code blocks
+/// and link reference definitions.
+fn skipped_ranges(md: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut fence: Option<(&str, usize)> = None;
+    let mut offset = 0;
+    for line in md.split('\n') {
+        let start = offset;
+        offset += line.len() + 1;
+
+        let trimmed = line.trim_left();
+        if let Some((marker, fence_start)) = fence {
+            if trimmed.starts_with(marker) {
+                ranges.push((fence_start, offset));
+                fence = None;
+            }
+        } else if trimmed.starts_with("```") {
+            fence = Some(("```", start));
+        } else if trimmed.starts_with("~~~") {
+            fence = Some(("~~~", start));
+        } else if link_definition(line).is_some() {
+            ranges.push((start, offset));
+        }
+    }
+    if let Some((_, fence_start)) = fence {
+        ranges.push((fence_start, offset));
+    }
+    ranges
+}
+
+/// Finds the links in `md` whose targets look like Rust paths, skipping code
+/// blocks, code spans and reference links which have a definition.
+pub fn find_path_links(md: &str) -> Vec<PathLink> {
+    let defined: Vec<String> = md.lines()
+                                 .filter_map(link_definition)
+                                 .map(|label| label.trim().to_lowercase())
+                                 .collect();
+    let is_candidate = |label: &str| {
+        rust_path(label).is_some() && !defined.contains(&label.trim().to_lowercase())
+    };
+    let skipped = skipped_ranges(md);
+
+    let mut links = Vec::new();
+    let bytes = md.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(&(_, end)) = skipped.iter().find(|&&(lo, hi)| lo <= i && i < hi) {
+            i = end;
+            continue
+        }
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' => i = skip_code_span(md, i),
+            b'[' => {
+                let close = match find_closing(md, i + 1, b'[', b']') {
+                    Some(close) => close,
+                    None => {
+                        i += 1;
+                        continue
+                    }
+                };
+                let text = &md[i + 1..close];
+                let rest = &md[close + 1..];
+                let link = if rest.starts_with('(') {
+                    find_closing(md, close + 2, b'(', b')').map(|end| {
+                        // The destination may be followed by a title.
+                        let dest = md[close + 2..end].trim();
+                        let dest = dest.split(char::is_whitespace).next().unwrap();
+                        (dest, end, true)
+                    })
+                } else if rest.starts_with('[') {
+                    find_closing(md, close + 2, b'[', b']').map(|end| {
+                        let reference = &md[close + 2..end];
+                        (if reference.is_empty() { text } else { reference }, end, false)
+                    })
+                } else {
+                    None
+                };
+                match link {
+                    Some((target, end, inline)) => {
+                        let is_path = if inline {
+                            rust_path(target).is_some()
+                        } else {
+                            is_candidate(target)
+                        };
+                        if is_path {
+                            links.push(PathLink {
+                                target: target,
+                                text: text,
+                                span: (i, end + 1),
+                            });
+                        }
+                        // Brackets in the text of a link are not links.
+                        i = end + 1;
+                    }
+                    None if is_candidate(text) => {
+                        links.push(PathLink {
+                            target: text,
+                            text: text,
+                            span: (i, close + 1),
+                        });
+                        i = close + 1;
+                    }
+                    // This may just be text in brackets, which can still
+                    // contain links.
+                    None => i += 1,
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    links
+}
+
+/// Turns the links found by `find_path_links` into inline links to the URLs
+/// `url_for` returns for their targets. Links to other targets are left
+/// alone.
+pub fn replace_path_links<F>(md: &str, mut url_for: F) -> String
+    where F: FnMut(&str) -> Option<String>
+{
+    let mut out = String::with_capacity(md.len());
+    let mut pos = 0;
+    for link in find_path_links(md) {
+        if let Some(url) = url_for(link.target) {
+            out.push_str(&md[pos..link.span.0]);
+            out.push_str(&format!("[{}]({})", link.text, url));
+            pos = link.span.1;
+        }
+    }
+    out.push_str(&md[pos..]);
+    out
+}
+
+/// Returns whether `md` contains a Rust code block, that is one which
+/// `rustdoc --test` would pick up.
+pub fn has_code_example(md: &str) -> bool {
+    Parser::new(md).any(|event| match event {
+        Event::Start(Tag::CodeBlock(ref s)) => s.is_empty() || LangString::parse(s).rust,
+        _ => false,
+    })
+}
+
 pub fn plain_summary_line(md: &str) -> String {
     struct ParserWrapper<'a> {
         inner: Parser<'a>,
@@ -1110,7 +1360,7 @@ pub fn plain_summary_line(md: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::{LangString, Markdown, MarkdownHtml};
-    use super::plain_summary_line;
+    use super::{find_path_links, plain_summary_line, replace_path_links};
     use super::RenderType;
     use html::render::reset_ids;
 
@@ -1230,4 +1480,36 @@ mod tests {
         t("Struct<'a, T>", "<p>Struct&lt;'a, T&gt;</p>\n");
         t("Struct<br>", "<p>Struct&lt;br&gt;</p>\n");
     }
+
+    #[test]
+    fn test_find_path_links() {
+        fn t(input: &str, expect: &[&str]) {
+            let targets: Vec<_> = find_path_links(input).iter().map(|l| l.target).collect();
+            assert_eq!(targets, expect, "original: {}", input);
+        }
+
+        t("[Vec] and [`io::Read`] and [push](Vec::push)", &["Vec", "`io::Read`", "Vec::push"]);
+        t("[text][Vec] and [Vec][]", &["Vec", "Vec"]);
+        t("[vec!] and [drop()]", &["vec!", "drop()"]);
+        t("a [link that\nspans lines](io::Read) and [io::\nRead]", &["io::Read"]);
+        t("[the [nested] text](Vec) and [[Vec]]", &["Vec", "Vec"]);
+        t("[x](foo.html) and [x](http://example.com \"title\")", &[]);
+        t("[Vec]\n\n[Vec]: http://example.com", &[]);
+        t("`[Vec]` and \\[Vec]\n```\n[Vec]\n```", &[]);
+        t("[not a\n\nlink](Vec)", &[]);
+    }
+
+    #[test]
+    fn test_replace_path_links() {
+        fn t(input: &str, expect: &str) {
+            let output = replace_path_links(input, |target| {
+                if target == "Unknown" { None } else { Some(format!("{}.html", target)) }
+            });
+            assert_eq!(output, expect, "original: {}", input);
+        }
+
+        t("see [Vec] or [the\ntrait](Read)", "see [Vec](Vec.html) or [the\ntrait](Read.html)");
+        t("[the `Vec`][Vec] and [Unknown]", "[the `Vec`](Vec.html) and [Unknown]");
+        t("[x](foo.html)", "[x](foo.html)");
+    }
 }
@@ -52,7 +52,7 @@ use std::sync::Arc;
 
 use externalfiles::ExternalHtml;
 
-use serialize::json::{ToJson, Json, as_json};
+use serialize::json::{self, ToJson, Json, as_json};
 use syntax::{abi, ast};
 use syntax::feature_gate::UnstableFeatures;
 use rustc::hir::def_id::{CrateNum, CRATE_DEF_INDEX, DefId};
@@ -336,8 +336,14 @@ impl ToJson for IndexItem {
 }
 
 /// A type used for the search index.
+///
+/// Types are normalized so that they are easy to match against queries:
+/// references and pointers are dropped, paths are reduced to their last
+/// segment and names are lowercased. Only the generic arguments which could
+/// be normalized themselves are kept.
 struct Type {
     name: Option<String>,
+    generics: Vec<Type>,
 }
 
 impl ToJson for Type {
@@ -346,6 +352,9 @@ impl ToJson for Type {
             Some(ref name) => {
                 let mut data = BTreeMap::new();
                 data.insert("name".to_owned(), name.to_json());
+                if !self.generics.is_empty() {
+                    data.insert("generics".to_owned(), self.generics.to_json());
+                }
                 Json::Object(data)
             },
             None => Json::Null
@@ -378,6 +387,9 @@ impl ToJson for IndexItemFunctionType {
 thread_local!(static CACHE_KEY: RefCell<Arc<Cache>> = Default::default());
 thread_local!(pub static CURRENT_LOCATION_KEY: RefCell<Vec<String>> =
                     RefCell::new(Vec::new()));
+/// The external crates linked to as `Local` while rendering, so that the
+/// links of a bundle can be checked.
+thread_local!(pub static LINKED_CRATES: RefCell<FxHashSet<CrateNum>> = Default::default());
 thread_local!(static USED_ID_MAP: RefCell<FxHashMap<String, usize>> =
                     RefCell::new(init_ids()));
 
@@ -394,9 +406,12 @@ fn init_ids() -> FxHashMap<String, usize> {
      "provided-methods",
      "implementors",
      "implementors-list",
+     "foreign-impls",
+     "foreign-impls-list",
      "methods",
      "deref-methods",
      "implementations",
+     "blanket-implementations",
      ].into_iter().map(|id| (String::from(*id), 1)).collect()
 }
 
@@ -437,7 +452,8 @@ pub fn run(mut krate: clean::Crate,
            passes: FxHashSet<String>,
            css_file_extension: Option<PathBuf>,
            renderinfo: RenderInfo,
-           render_type: RenderType) -> Result<(), Error> {
+           render_type: RenderType,
+           bundle: bool) -> Result<(), Error> {
     let src_root = match krate.src.parent() {
         Some(p) => p.to_path_buf(),
         None => PathBuf::new(),
@@ -544,7 +560,7 @@ pub fn run(mut krate: clean::Crate,
             None => PathBuf::new(),
         };
         cache.extern_locations.insert(n, (e.name.clone(), src_root,
-                                          extern_location(e, &cx.dst)));
+                                          extern_location(e, &cx.dst, bundle)));
 
         let did = DefId { krate: n, index: CRATE_DEF_INDEX };
         cache.external_paths.insert(did, (vec![e.name.to_string()], ItemType::Module));
@@ -578,7 +594,56 @@ pub fn run(mut krate: clean::Crate,
     write_shared(&cx, &krate, &*cache, index)?;
 
     // And finally render the whole crate's documentation
-    cx.krate(krate)
+    let dst = cx.dst.clone();
+    let name = krate.name.clone();
+    cx.krate(krate)?;
+
+    if bundle {
+        write_bundle(&dst, &name, &cache)?;
+    }
+    Ok(())
+}
+
+/// Records which crates the documentation of `krate` links to in the file
+/// shared by the crates of a bundle, then warns about every crate in the
+/// bundle which links to a crate whose documentation is not there. All of
+/// the crates are checked because documenting one crate can complete the
+/// links of those documented before it.
+fn write_bundle(dst: &Path, krate: &str, cache: &Cache) -> Result<(), Error> {
+    let _lock = flock::Lock::panicking_new(&dst.join(".lock"), true, true, true);
+
+    let mut linked: Vec<String> = LINKED_CRATES.with(|linked| {
+        linked.borrow().iter().map(|cnum| cache.extern_locations[cnum].0.clone()).collect()
+    });
+    linked.sort();
+
+    let path = dst.join("bundle.js");
+    let mut lines = try_err!(collect(&path, krate, "bundleLinks"), &path);
+    lines.push(format!("bundleLinks[{}] = {};", as_json(&krate), as_json(&linked)));
+    lines.sort();
+    let mut w = try_err!(File::create(&path), &path);
+    try_err!(writeln!(&mut w, "var bundleLinks = {{}};"), &path);
+    for line in &lines {
+        try_err!(writeln!(&mut w, "{}", line), &path);
+    }
+
+    for line in &lines {
+        let (name, links) = match line.find(" = ") {
+            Some(i) => (&line["bundleLinks[".len()..i - 1], &line[i + 3..line.len() - 1]),
+            None => continue,
+        };
+        let (name, links) = match (json::from_str(name), json::from_str(links)) {
+            (Ok(Json::String(name)), Ok(Json::Array(links))) => (name, links),
+            _ => continue,
+        };
+        for link in links.iter().filter_map(|link| link.as_string()) {
+            if !dst.join(link).join("index.html").exists() {
+                println!("warning: the documentation of `{}` links to `{}`, which is not \
+                          part of the bundle", name, link);
+            }
+        }
+    }
+    Ok(())
 }
 
 /// Build the search index from the collected metadata
@@ -599,7 +664,7 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
                 ty: item.type_(),
                 name: item.name.clone().unwrap(),
                 path: fqp[..fqp.len() - 1].join("::"),
-                desc: plain_summary_line(item.doc_value()),
+                desc: plain_doc_summary(&item),
                 parent: Some(did),
                 parent_idx: None,
                 search_type: get_index_search_type(&item),
@@ -637,7 +702,7 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
     }
 
     let crate_doc = krate.module.as_ref().map(|module| {
-        plain_summary_line(module.doc_value())
+        plain_doc_summary(module)
     }).unwrap_or(String::new());
 
     let mut crate_data = BTreeMap::new();
@@ -651,6 +716,25 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
             Json::Object(crate_data))
 }
 
+/// Reads the lines of the shared file `path` which define `key[...]` for
+/// crates other than `krate`.
+fn collect(path: &Path, krate: &str, key: &str) -> io::Result<Vec<String>> {
+    let mut ret = Vec::new();
+    if path.exists() {
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if !line.starts_with(key) {
+                continue;
+            }
+            if line.starts_with(&format!(r#"{}["{}"]"#, key, krate)) {
+                continue;
+            }
+            ret.push(line.to_string());
+        }
+    }
+    Ok(ret)
+}
+
 fn write_shared(cx: &Context,
                 krate: &clean::Crate,
                 cache: &Cache,
@@ -712,24 +796,6 @@ fn write_shared(cx: &Context,
     write(cx.dst.join("COPYRIGHT.txt"),
           include_bytes!("static/COPYRIGHT.txt"))?;
 
-    fn collect(path: &Path, krate: &str,
-               key: &str) -> io::Result<Vec<String>> {
-        let mut ret = Vec::new();
-        if path.exists() {
-            for line in BufReader::new(File::open(path)?).lines() {
-                let line = line?;
-                if !line.starts_with(key) {
-                    continue;
-                }
-                if line.starts_with(&format!(r#"{}["{}"]"#, key, krate)) {
-                    continue;
-                }
-                ret.push(line.to_string());
-            }
-        }
-        Ok(ret)
-    }
-
     // Update the search index
     let dst = cx.dst.join("search-index.js");
     let mut all_indexes = try_err!(collect(&dst, &krate.name, "searchIndex"), &dst);
@@ -868,10 +934,11 @@ fn clean_srcpath<F>(src_root: &Path, p: &Path, keep_filename: bool, mut f: F) wh
 
 /// Attempts to find where an external crate is located, given that we're
 /// rendering in to the specified source destination.
-fn extern_location(e: &clean::ExternalCrate, dst: &Path) -> ExternalLocation {
-    // See if there's documentation generated into the local directory
+fn extern_location(e: &clean::ExternalCrate, dst: &Path, bundle: bool) -> ExternalLocation {
+    // See if there's documentation generated into the local directory. When
+    // building a bundle, every crate is expected to be documented there.
     let local_location = dst.join(&e.name);
-    if local_location.is_dir() {
+    if bundle || local_location.is_dir() {
         return Local;
     }
 
@@ -1005,9 +1072,11 @@ impl DocFolder for Cache {
             self.traits.entry(item.def_id).or_insert_with(|| t.clone());
         }
 
-        // Collect all the implementors of traits.
+        // Collect all the implementors of traits. The copies of blanket impls
+        // made for the types they apply to are left out, the blanket impl
+        // itself is listed already.
         if let clean::ImplItem(ref i) = item.inner {
-            if let Some(did) = i.trait_.def_id() {
+            if let (Some(did), None) = (i.trait_.def_id(), i.blanket_impl.as_ref()) {
                 self.implementors.entry(did).or_insert(vec![]).push(Implementor {
                     def_id: item.def_id,
                     stability: item.stability.clone(),
@@ -1070,7 +1139,7 @@ impl DocFolder for Cache {
                             ty: item.type_(),
                             name: s.to_string(),
                             path: path.join("::").to_string(),
-                            desc: plain_summary_line(item.doc_value()),
+                            desc: plain_doc_summary(&item),
                             parent: parent,
                             parent_idx: None,
                             search_type: get_index_search_type(&item),
@@ -1441,7 +1510,7 @@ impl Context {
             };
             let short = short.to_string();
             map.entry(short).or_insert(vec![])
-                .push((myname, Some(plain_summary_line(item.doc_value()))));
+                .push((myname, Some(plain_doc_summary(item))));
         }
 
         for (_, items) in &mut map {
@@ -1652,9 +1721,9 @@ fn document_short(w: &mut fmt::Formatter, item: &clean::Item, link: AssocItemLin
     if let Some(s) = item.doc_value() {
         let markdown = if s.contains('\n') {
             format!("{} [Read more]({})",
-                    &plain_summary_line(Some(s)), naive_assoc_href(item, link))
+                    &plain_doc_summary(item), naive_assoc_href(item, link))
         } else {
-            format!("{}", &plain_summary_line(Some(s)))
+            format!("{}", &plain_doc_summary(item))
         };
         write!(w, "<div class='docblock'>{}</div>",
                Markdown(&markdown, render_type))?;
@@ -1687,9 +1756,47 @@ fn get_doc_value(item: &clean::Item) -> Option<&str> {
     }
 }
 
+/// Points the links in `md` whose targets were resolved to items at the URLs
+/// `url_for` gives for those items. All docs that get rendered, in full or
+/// summarized, go through here.
+fn link_items<F>(attrs: &clean::Attributes, md: &str, url_for: F) -> String
+    where F: Fn(&clean::DocLink) -> Option<String>
+{
+    if attrs.links.is_empty() {
+        return md.to_string();
+    }
+    markdown::replace_path_links(md, |target| {
+        attrs.links.iter().find(|link| link.target == target).and_then(|link| url_for(link))
+    })
+}
+
+/// The docs of `item`, with their links pointing at the pages of the items
+/// they were resolved to, relative to the page being rendered.
+fn linked_doc_value(item: &clean::Item) -> Option<String> {
+    item.doc_value().map(|md| {
+        link_items(&item.attrs, md, |link| {
+            href(link.did).map(|(mut url, ..)| {
+                if let Some(ref fragment) = link.fragment {
+                    url.push('#');
+                    url.push_str(fragment);
+                }
+                url
+            })
+        })
+    })
+}
+
+/// The summary line of the docs of `item` as plain text, for the search index
+/// and the sidebar, where links are reduced to their text.
+fn plain_doc_summary(item: &clean::Item) -> String {
+    let md = item.doc_value().map(|md| link_items(&item.attrs, md, |_| Some(String::new())));
+    plain_summary_line(md.as_ref().map(|md| &md[..]))
+}
+
 fn document_full(w: &mut fmt::Formatter, item: &clean::Item,
                  render_type: RenderType) -> fmt::Result {
-    if let Some(s) = get_doc_value(item) {
+    if get_doc_value(item).is_some() {
+        let s = linked_doc_value(item).unwrap_or(String::new());
         write!(w, "<div class='docblock'>{}</div>",
                Markdown(&format!("{}{}", md_render_assoc_item(item), s), render_type))?;
     }
@@ -1868,7 +1975,8 @@ fn item_module(w: &mut fmt::Formatter, cx: &Context,
                     _ => "",
                 };
 
-                let doc_value = myitem.doc_value().unwrap_or("");
+                let doc_value = linked_doc_value(myitem).unwrap_or(String::new());
+                let doc_value = &doc_value[..];
                 write!(w, "
                        <tr class='{stab} module-item'>
                            <td><a class=\"{class}\" href=\"{href}\"
@@ -2214,46 +2322,48 @@ fn item_trait(w: &mut fmt::Formatter, cx: &Context, it: &clean::Item,
     render_assoc_items(w, cx, it, it.def_id, AssocItemRender::All)?;
 
     let cache = cache();
-    write!(w, "
-        <h2 id='implementors'>Implementors</h2>
-        <ul class='item-list' id='implementors-list'>
-    ")?;
-    if let Some(implementors) = cache.implementors.get(&it.def_id) {
-        // The DefId is for the first Type found with that name. The bool is
-        // if any Types with the same name but different DefId have been found.
-        let mut implementor_dups: FxHashMap<&str, (DefId, bool)> = FxHashMap();
-        for implementor in implementors {
-            match implementor.impl_.for_ {
-                clean::ResolvedPath { ref path, did, is_generic: false, .. } |
-                clean::BorrowedRef {
-                    type_: box clean::ResolvedPath { ref path, did, is_generic: false, .. },
-                    ..
-                } => {
-                    let &mut (prev_did, ref mut has_duplicates) =
-                        implementor_dups.entry(path.last_name()).or_insert((did, false));
-                    if prev_did != did {
-                        *has_duplicates = true;
-                    }
+    let implementors = cache.implementors.get(&it.def_id).map(|i| &i[..]).unwrap_or(&[]);
+
+    // The DefId is for the first Type found with that name. The bool is
+    // if any Types with the same name but different DefId have been found.
+    let mut implementor_dups: FxHashMap<&str, (DefId, bool)> = FxHashMap();
+    for implementor in implementors {
+        match implementor.impl_.for_ {
+            clean::ResolvedPath { ref path, did, is_generic: false, .. } |
+            clean::BorrowedRef {
+                type_: box clean::ResolvedPath { ref path, did, is_generic: false, .. },
+                ..
+            } => {
+                let &mut (prev_did, ref mut has_duplicates) =
+                    implementor_dups.entry(path.last_name()).or_insert((did, false));
+                if prev_did != did {
+                    *has_duplicates = true;
                 }
-                _ => {}
             }
+            _ => {}
         }
+    }
 
-        for implementor in implementors {
-            write!(w, "<li><code>")?;
-            // If there's already another implementor that has the same abbridged name, use the
-            // full path, for example in `std::iter::ExactSizeIterator`
-            let use_absolute = match implementor.impl_.for_ {
-                clean::ResolvedPath { ref path, is_generic: false, .. } |
-                clean::BorrowedRef {
-                    type_: box clean::ResolvedPath { ref path, is_generic: false, .. },
-                    ..
-                } => implementor_dups[path.last_name()].1,
-                _ => false,
-            };
-            fmt_impl_for_trait_page(&implementor.impl_, w, use_absolute)?;
-            writeln!(w, "</code></li>")?;
+    let (foreign, local): (Vec<_>, Vec<_>) = implementors.iter().partition(|i| {
+        is_foreign_impl(i)
+    });
+    if !foreign.is_empty() {
+        write!(w, "
+            <h2 id='foreign-impls'>Implementations on Foreign Types</h2>
+            <ul class='item-list' id='foreign-impls-list'>
+        ")?;
+        for implementor in foreign {
+            render_implementor(w, implementor, &implementor_dups)?;
         }
+        write!(w, "</ul>")?;
+    }
+
+    write!(w, "
+        <h2 id='implementors'>Implementors</h2>
+        <ul class='item-list' id='implementors-list'>
+    ")?;
+    for implementor in local {
+        render_implementor(w, implementor, &implementor_dups)?;
     }
     write!(w, "</ul>")?;
     write!(w, r#"<script type="text/javascript" async
@@ -2271,6 +2381,36 @@ fn item_trait(w: &mut fmt::Formatter, cx: &Context, it: &clean::Item,
     Ok(())
 }
 
+/// Whether `implementor` is an impl of the crate being documented for a type
+/// defined in another crate, including primitive types.
+fn is_foreign_impl(implementor: &Implementor) -> bool {
+    implementor.def_id.is_local() && match implementor.impl_.for_ {
+        clean::ResolvedPath { did, is_generic: false, .. } |
+        clean::BorrowedRef {
+            type_: box clean::ResolvedPath { did, is_generic: false, .. },
+            ..
+        } => !did.is_local(),
+        ref t => t.primitive_type().is_some(),
+    }
+}
+
+fn render_implementor(w: &mut fmt::Formatter, implementor: &Implementor,
+                      implementor_dups: &FxHashMap<&str, (DefId, bool)>) -> fmt::Result {
+    write!(w, "<li><code>")?;
+    // If there's already another implementor that has the same abbridged name, use the
+    // full path, for example in `std::iter::ExactSizeIterator`
+    let use_absolute = match implementor.impl_.for_ {
+        clean::ResolvedPath { ref path, is_generic: false, .. } |
+        clean::BorrowedRef {
+            type_: box clean::ResolvedPath { ref path, is_generic: false, .. },
+            ..
+        } => implementor_dups[path.last_name()].1,
+        _ => false,
+    };
+    fmt_impl_for_trait_page(&implementor.impl_, w, use_absolute)?;
+    writeln!(w, "</code></li>")
+}
+
 fn naive_assoc_href(it: &clean::Item, link: AssocItemLink) -> String {
     use html::item_type::ItemType::*;
 
@@ -2864,18 +3004,34 @@ fn render_assoc_items(w: &mut fmt::Formatter,
             }).is_some();
             render_deref_methods(w, cx, impl_, containing_item, has_deref_mut)?;
         }
-        write!(w, "<h2 id='implementations'>Trait \
-                   Implementations</h2>")?;
-        for i in &traits {
-            let did = i.trait_did().unwrap();
-            let assoc_link = AssocItemLink::GotoSource(did, &i.inner_impl().provided_trait_methods);
-            render_impl(w, cx, i, assoc_link,
-                        RenderMode::Normal, containing_item.stable_since())?;
+        let (blanket, traits): (Vec<_>, Vec<_>) = traits.into_iter().partition(|i| {
+            i.inner_impl().blanket_impl.is_some()
+        });
+        if !traits.is_empty() {
+            write!(w, "<h2 id='implementations'>Trait \
+                       Implementations</h2>")?;
+            render_trait_impls(w, cx, &traits, containing_item)?;
+        }
+        if !blanket.is_empty() {
+            write!(w, "<h2 id='blanket-implementations'>Blanket \
+                       Implementations</h2>")?;
+            render_trait_impls(w, cx, &blanket, containing_item)?;
         }
     }
     Ok(())
 }
 
+fn render_trait_impls(w: &mut fmt::Formatter, cx: &Context, traits: &[&Impl],
+                      containing_item: &clean::Item) -> fmt::Result {
+    for i in traits {
+        let did = i.trait_did().unwrap();
+        let assoc_link = AssocItemLink::GotoSource(did, &i.inner_impl().provided_trait_methods);
+        render_impl(w, cx, i, assoc_link,
+                    RenderMode::Normal, containing_item.stable_since())?;
+    }
+    Ok(())
+}
+
 fn render_deref_methods(w: &mut fmt::Formatter, cx: &Context, impl_: &Impl,
                         container_item: &clean::Item, deref_mut: bool) -> fmt::Result {
     let deref_type = impl_.inner_impl().trait_.as_ref().unwrap();
@@ -2902,7 +3058,14 @@ fn render_deref_methods(w: &mut fmt::Formatter, cx: &Context, impl_: &Impl,
 fn render_impl(w: &mut fmt::Formatter, cx: &Context, i: &Impl, link: AssocItemLink,
                render_mode: RenderMode, outer_version: Option<&str>) -> fmt::Result {
     if render_mode == RenderMode::Normal {
-        write!(w, "<h3 class='impl'><span class='in-band'><code>{}</code>", i.inner_impl())?;
+        // Blanket impls keep the look of other impls, but can be told apart.
+        let class = if i.inner_impl().blanket_impl.is_some() {
+            "impl blanket-impl"
+        } else {
+            "impl"
+        };
+        write!(w, "<h3 class='{}'><span class='in-band'><code>{}</code>",
+               class, i.inner_impl())?;
         write!(w, "</span><span class='out-of-band'>")?;
         let since = i.impl_item.stability.as_ref().map(|s| &s.since[..]);
         if let Some(l) = (Item { item: &i.impl_item, cx: cx }).src_href() {
@@ -2915,7 +3078,7 @@ fn render_impl(w: &mut fmt::Formatter, cx: &Context, i: &Impl, link: AssocItemLi
         }
         write!(w, "</span>")?;
         write!(w, "</h3>\n")?;
-        if let Some(ref dox) = i.impl_item.doc_value() {
+        if let Some(ref dox) = linked_doc_value(&i.impl_item) {
             write!(w, "<div class='docblock'>{}</div>", Markdown(dox, cx.render_type))?;
         }
     }
@@ -3202,7 +3365,15 @@ fn sidebar_assoc_items(it: &clean::Item) -> String {
                     }
                 }
             }
-            out.push_str("<li><a href=\"#implementations\">Trait Implementations</a></li>");
+            if v.iter().any(|i| {
+                i.inner_impl().trait_.is_some() && i.inner_impl().blanket_impl.is_none()
+            }) {
+                out.push_str("<li><a href=\"#implementations\">Trait Implementations</a></li>");
+            }
+            if v.iter().any(|i| i.inner_impl().blanket_impl.is_some()) {
+                out.push_str("<li><a href=\"#blanket-implementations\">\
+                              Blanket Implementations</a></li>");
+            }
         }
     }
 
@@ -3252,6 +3423,11 @@ fn sidebar_trait(fmt: &mut fmt::Formatter, it: &clean::Item,
 
     sidebar.push_str(&sidebar_assoc_items(it));
 
+    let c = cache();
+    if c.implementors.get(&it.def_id).map_or(false, |i| i.iter().any(is_foreign_impl)) {
+        sidebar.push_str("<li><a href=\"#foreign-impls\">\
+                          Implementations on Foreign Types</a></li>");
+    }
     sidebar.push_str("<li><a href=\"#implementors\">Implementors</a></li>");
 
     write!(fmt, "<div class=\"block items\"><ul>{}</ul></div>", sidebar)
@@ -3429,7 +3605,10 @@ fn get_index_search_type(item: &clean::Item) -> Option<IndexItemFunctionType> {
 }
 
 fn get_index_type(clean_type: &clean::Type) -> Type {
-    Type { name: get_index_type_name(clean_type).map(|s| s.to_ascii_lowercase()) }
+    Type {
+        name: get_index_type_name(clean_type).map(|s| s.to_ascii_lowercase()),
+        generics: get_index_type_generics(clean_type),
+    }
 }
 
 fn get_index_type_name(clean_type: &clean::Type) -> Option<String> {
@@ -3440,12 +3619,38 @@ fn get_index_type_name(clean_type: &clean::Type) -> Option<String> {
         },
         clean::Generic(ref s) => Some(s.clone()),
         clean::Primitive(ref p) => Some(format!("{:?}", p)),
-        clean::BorrowedRef { ref type_, .. } => get_index_type_name(type_),
+        clean::Tuple(..) => Some("tuple".to_owned()),
+        clean::Slice(..) => Some("slice".to_owned()),
+        clean::Array(..) => Some("array".to_owned()),
+        clean::BorrowedRef { ref type_, .. } |
+        clean::RawPointer(_, ref type_) => get_index_type_name(type_),
         // FIXME: add all from clean::Type.
         _ => None
     }
 }
 
+fn get_index_type_generics(clean_type: &clean::Type) -> Vec<Type> {
+    let types: Vec<&clean::Type> = match *clean_type {
+        clean::ResolvedPath { ref path, .. } => {
+            match path.segments[path.segments.len() - 1].params {
+                clean::PathParameters::AngleBracketed { ref types, .. } => {
+                    types.iter().collect()
+                }
+                clean::PathParameters::Parenthesized { .. } => vec![],
+            }
+        }
+        clean::Tuple(ref types) => types.iter().collect(),
+        clean::Slice(ref type_) | clean::Array(ref type_, _) => vec![&**type_],
+        clean::BorrowedRef { ref type_, .. } |
+        clean::RawPointer(_, ref type_) => return get_index_type_generics(type_),
+        _ => vec![],
+    };
+    types.into_iter()
+         .map(get_index_type)
+         .filter(|type_| type_.name.is_some())
+         .collect()
+}
+
 pub fn cache() -> Arc<Cache> {
     CACHE_KEY.with(|c| c.borrow().clone())
 }
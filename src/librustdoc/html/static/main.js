@@ -319,6 +319,114 @@
             document.getElementsByClassName("search-input")[0].value = params.search || '';
         }
 
+        /**
+         * Splits a list of types at each `sep` which is not nested in
+         * brackets, dropping empty elements.
+         */
+        function splitTypes(s, sep) {
+            var parts = [], depth = 0, start = 0;
+            for (var i = 0; i < s.length; ++i) {
+                var c = s.charAt(i);
+                if (c === "<" || c === "(" || c === "[") {
+                    depth += 1;
+                } else if (c === ">" || c === ")" || c === "]") {
+                    depth -= 1;
+                } else if (c === sep && depth === 0) {
+                    parts.push(s.substring(start, i));
+                    start = i + 1;
+                }
+            }
+            parts.push(s.substring(start));
+            return parts.map(function (s) { return s.trim(); })
+                        .filter(function (s) { return s !== "" && s.charAt(0) !== "'"; });
+        }
+
+        /**
+         * Parses a type of a query into the form used by the search index:
+         * references and pointers are dropped, paths are reduced to their last
+         * segment and names are lowercased. Single capital letters like `T`,
+         * as well as `_`, stand for any type.
+         */
+        function parseType(s) {
+            s = s.trim().replace(/^(&\s*('\w+\s+)?(mut\s+)?|\*\s*(const|mut)\s+)*/, "");
+            var last = s.charAt(s.length - 1);
+            if (s.charAt(0) === "(" && last === ")") {
+                return {name: "tuple",
+                        generics: splitTypes(s.substring(1, s.length - 1), ",").map(parseType)};
+            }
+            if (s.charAt(0) === "[" && last === "]") {
+                var elems = splitTypes(s.substring(1, s.length - 1), ";");
+                return {name: elems.length > 1 ? "array" : "slice",
+                        generics: elems.slice(0, 1).map(parseType)};
+            }
+            var generics = [];
+            var lt = s.indexOf("<");
+            if (lt > -1 && last === ">") {
+                generics = splitTypes(s.substring(lt + 1, s.length - 1), ",").map(parseType);
+                s = s.substring(0, lt);
+            }
+            s = s.split("::").pop().trim();
+            return {name: s.toLowerCase(),
+                    generics: generics,
+                    wildcard: s === "_" || /^[A-Z]$/.test(s)};
+        }
+
+        function isUnit(type) {
+            return type.name === "tuple" && !(type.generics && type.generics.length);
+        }
+
+        /**
+         * Whether the type of a query matches a type of the search index. The
+         * generic arguments of the query must match some of those of the type.
+         */
+        function checkType(queryType, type, parent) {
+            if (queryType.wildcard) {
+                return true;
+            }
+            if (!type) {
+                return false;
+            }
+            var name = type.name;
+            // `Self` in inherent methods is the type they are implemented for
+            if (name === "self" && parent && itemTypes[parent.ty] !== "trait") {
+                name = parent.name.toLowerCase();
+            }
+            if (queryType.name !== name) {
+                return false;
+            }
+            var generics = queryType.generics.filter(function (g) { return !g.wildcard; });
+            return checkTypes(generics, type.generics || [], parent, false);
+        }
+
+        /**
+         * Whether each type of a query matches a different type of the search
+         * index, in any order. If `exact`, there must be as many of both.
+         */
+        function checkTypes(queryTypes, types, parent, exact) {
+            if (queryTypes.length > types.length ||
+                (exact && queryTypes.length !== types.length)) {
+                return false;
+            }
+            // match wildcards last so that they do not take the place of
+            // another type
+            var sorted = queryTypes.filter(function (q) { return !q.wildcard; })
+                                   .concat(queryTypes.filter(function (q) { return q.wildcard; }));
+            var used = [];
+            for (var i = 0; i < sorted.length; ++i) {
+                var found = false;
+                for (var j = 0; j < types.length && !found; ++j) {
+                    if (!used[j] && checkType(sorted[i], types[j], parent)) {
+                        used[j] = true;
+                        found = true;
+                    }
+                }
+                if (!found) {
+                    return false;
+                }
+            }
+            return true;
+        }
+
         /**
          * Executes the query and builds an index of results
          * @param  {[Object]} query     [The user query]
@@ -383,30 +491,32 @@
                 }
             // searching by type
             } else if (val.search("->") > -1) {
-                var trimmer = function (s) { return s.trim(); };
-                var parts = val.split("->").map(trimmer);
-                var input = parts[0];
-                // sort inputs so that order does not matter
-                var inputs = input.split(",").map(trimmer).sort().toString();
-                var output = parts[1];
+                // generic parameters like `T` are only told apart from other
+                // types by their case, so parse the query as it was typed
+                var parts = query.query.split("->");
+                var input = parts[0].trim();
+                var output = parts[1].trim();
+                var inputs = input === "*" ? null : splitTypes(input, ",").map(parseType);
+                var outputType = output === "*" ? null : parseType(output);
 
                 for (var i = 0; i < nSearchWords; ++i) {
                     var type = searchIndex[i].type;
                     if (!type) {
                         continue;
                     }
+                    var parent = searchIndex[i].parent;
 
-                    // sort index inputs so that order does not matter
-                    var typeInputs = type.inputs.map(function (input) {
-                        return input.name;
-                    }).sort();
-
+                    // inputs may be given in any order
+                    if (inputs !== null && !checkTypes(inputs, type.inputs, parent, true)) {
+                        continue;
+                    }
                     // allow searching for void (no output) functions as well
-                    var typeOutput = type.output ? type.output.name : "";
-                    if ((inputs === "*" || inputs === typeInputs.toString()) &&
-                        (output === "*" || output == typeOutput)) {
-                        results.push({id: i, index: -1, dontValidate: true});
+                    if (outputType !== null &&
+                        !(isUnit(outputType) && (!type.output || isUnit(type.output))) &&
+                        !checkType(outputType, type.output, parent)) {
+                        continue;
                     }
+                    results.push({id: i, index: -1, dontValidate: true});
                 }
             } else {
                 // gather matching search results up to a certain maximum
@@ -0,0 +1,544 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! JSON output backend (`--output-format json`).
+//!
+//! This writes the cleaned crate, after all passes have run, to
+//! `<output>/<crate>.json`. The document is meant to be consumed by external
+//! tools, so it follows the schema below rather than the internal layout of
+//! the `clean` types. Any incompatible change to the schema must bump
+//! `FORMAT_VERSION`.
+//!
+//! The top-level object has the following fields:
+//!
+//! * `format_version`: the version of this schema, currently 1.
+//! * `crate`: `{ "name", "src", "module" }`, where `module` is the item for
+//!   the root module of the crate.
+//! * `external_crates`: maps the crate number used in ids to
+//!   `{ "name", "src" }`.
+//! * `external_traits`: maps ids to the *trait* objects (see below) of the
+//!   traits from other crates that are implemented or used by this crate.
+//!
+//! Ids are strings of the form `"CRATE:INDEX"`; crate 0 is the documented
+//! crate itself.
+//!
+//! Every item is an object with the fields
+//!
+//! * `id`, `name` (or `null` for impls and the like), `kind`, which is one
+//!   of the names used for the item types in the HTML output (`mod`,
+//!   `struct`, `fn`, `trait`, `impl`, `tymethod`, `method`, `structfield`,
+//!   `variant`, `associatedtype`, ...),
+//! * `span`: `{ "filename", "begin": [line, col], "end": [line, col] }` or
+//!   `null`,
+//! * `visibility`: `"public"`, `"inherited"` or `null`,
+//! * `docs`: the documentation as Markdown, or `null`,
+//! * `attrs`: the other attributes, as source strings,
+//! * `stability`: `{ "level": "stable" | "unstable", "feature", "since",
+//!   "unstable_reason", "issue" }` or `null`,
+//! * `deprecation`: `{ "since", "note" }` or `null`,
+//! * `stripped`: whether the contents of the item were removed by a pass,
+//! * `inner`: an object with the fields specific to `kind`.
+//!
+//! Generics are `{ "lifetimes", "params", "where_predicates" }` and types
+//! are objects tagged with a `kind` field, for example
+//! `{ "kind": "borrowed_ref", "lifetime": null, "mutable": false,
+//! "type": ... }`.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use serialize::json::{Json, ToJson};
+use rustc::hir;
+use rustc::hir::def_id::DefId;
+use rustc::middle::stability;
+use syntax::print::pprust;
+
+use clean;
+use html::item_type::ItemType;
+
+/// The version of the schema described in the module documentation.
+pub const FORMAT_VERSION: u64 = 1;
+
+/// Builds a JSON object out of `"key" => value` pairs.
+macro_rules! object {
+    ($($key:expr => $value:expr),* $(,)*) => {{
+        let mut map = BTreeMap::new();
+        $(map.insert($key.to_string(), $value.to_json());)*
+        Json::Object(map)
+    }}
+}
+
+/// Writes the documentation of `krate` as JSON into the directory `dst`.
+pub fn run(krate: clean::Crate, dst: PathBuf) -> io::Result<()> {
+    let mut external_crates = BTreeMap::new();
+    for &(cnum, ref extern_crate) in &krate.externs {
+        external_crates.insert(cnum.as_u32().to_string(), object! {
+            "name" => extern_crate.name,
+            "src" => extern_crate.src.to_string_lossy().into_owned(),
+        });
+    }
+    let mut external_traits = BTreeMap::new();
+    for (did, trait_) in &krate.external_traits {
+        external_traits.insert(id(*did), trait_.to_json());
+    }
+
+    let root = object! {
+        "format_version" => FORMAT_VERSION,
+        "crate" => object! {
+            "name" => krate.name,
+            "src" => krate.src.to_string_lossy().into_owned(),
+            "module" => krate.module,
+        },
+        "external_crates" => Json::Object(external_crates),
+        "external_traits" => Json::Object(external_traits),
+    };
+
+    fs::create_dir_all(&dst)?;
+    let mut w = BufWriter::new(File::create(dst.join(format!("{}.json", krate.name)))?);
+    write!(w, "{}", root)?;
+    w.flush()
+}
+
+fn id(did: DefId) -> String {
+    format!("{}:{}", did.krate.as_u32(), did.index.as_u32())
+}
+
+fn unsafety(unsafety: hir::Unsafety) -> bool {
+    unsafety == hir::Unsafety::Unsafe
+}
+
+fn constness(constness: hir::Constness) -> bool {
+    constness == hir::Constness::Const
+}
+
+fn mutable(mutability: clean::Mutability) -> bool {
+    mutability == clean::Mutable
+}
+
+impl ToJson for clean::Item {
+    fn to_json(&self) -> Json {
+        let (inner, stripped) = match self.inner {
+            clean::StrippedItem(box ref inner) => (inner, true),
+            ref inner => (inner, false),
+        };
+        let docs = if self.attrs.doc_strings.is_empty() {
+            None
+        } else {
+            Some(self.attrs.doc_strings.join("\n"))
+        };
+        let attrs: Vec<String> = self.attrs.other_attrs.iter()
+                                                       .map(pprust::attribute_to_string)
+                                                       .collect();
+        let visibility = match self.visibility {
+            Some(clean::Public) => "public".to_json(),
+            Some(clean::Inherited) => "inherited".to_json(),
+            None => Json::Null,
+        };
+        object! {
+            "id" => id(self.def_id),
+            "name" => self.name,
+            "kind" => ItemType::from(self).css_class(),
+            "span" => self.source,
+            "visibility" => visibility,
+            "docs" => docs,
+            "attrs" => attrs,
+            "stability" => self.stability,
+            "deprecation" => self.deprecation,
+            "stripped" => stripped,
+            "inner" => inner,
+        }
+    }
+}
+
+impl ToJson for clean::ItemEnum {
+    fn to_json(&self) -> Json {
+        match *self {
+            clean::ExternCrateItem(ref name, ref source) => object! {
+                "name" => name,
+                "source" => source,
+            },
+            clean::ImportItem(clean::Import::Simple(ref name, ref source)) => object! {
+                "name" => name,
+                "glob" => false,
+                "source" => source.path,
+                "id" => source.did.map(id),
+            },
+            clean::ImportItem(clean::Import::Glob(ref source)) => object! {
+                "name" => Json::Null,
+                "glob" => true,
+                "source" => source.path,
+                "id" => source.did.map(id),
+            },
+            clean::StructItem(ref s) => object! {
+                "struct_type" => s.struct_type,
+                "generics" => s.generics,
+                "fields" => s.fields,
+                "fields_stripped" => s.fields_stripped,
+            },
+            clean::UnionItem(ref u) => object! {
+                "struct_type" => u.struct_type,
+                "generics" => u.generics,
+                "fields" => u.fields,
+                "fields_stripped" => u.fields_stripped,
+            },
+            clean::EnumItem(ref e) => object! {
+                "generics" => e.generics,
+                "variants" => e.variants,
+                "variants_stripped" => e.variants_stripped,
+            },
+            clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => object! {
+                "decl" => f.decl,
+                "generics" => f.generics,
+                "unsafe" => unsafety(f.unsafety),
+                "const" => constness(f.constness),
+                "abi" => f.abi.name(),
+            },
+            clean::ModuleItem(ref m) => object! {
+                "is_crate" => m.is_crate,
+                "items" => m.items,
+            },
+            clean::TypedefItem(ref t, _) => object! {
+                "type" => t.type_,
+                "generics" => t.generics,
+            },
+            clean::StaticItem(ref s) | clean::ForeignStaticItem(ref s) => object! {
+                "type" => s.type_,
+                "mutable" => mutable(s.mutability),
+                "expr" => s.expr,
+            },
+            clean::ConstantItem(ref c) => object! {
+                "type" => c.type_,
+                "expr" => c.expr,
+            },
+            clean::TraitItem(ref t) => t.to_json(),
+            clean::ImplItem(ref i) => {
+                let mut provided: Vec<&String> = i.provided_trait_methods.iter().collect();
+                provided.sort();
+                object! {
+                    "unsafe" => unsafety(i.unsafety),
+                    "generics" => i.generics,
+                    "trait" => i.trait_,
+                    "for" => i.for_,
+                    "blanket_impl" => i.blanket_impl,
+                    "items" => i.items,
+                    "negative" => i.polarity == Some(clean::ImplPolarity::Negative),
+                    "provided_trait_methods" => provided.iter().map(|s| s.to_json())
+                                                        .collect::<Vec<_>>(),
+                }
+            }
+            clean::TyMethodItem(ref m) => object! {
+                "decl" => m.decl,
+                "generics" => m.generics,
+                "unsafe" => unsafety(m.unsafety),
+                "const" => false,
+                "abi" => m.abi.name(),
+            },
+            clean::MethodItem(ref m) => object! {
+                "decl" => m.decl,
+                "generics" => m.generics,
+                "unsafe" => unsafety(m.unsafety),
+                "const" => constness(m.constness),
+                "abi" => m.abi.name(),
+            },
+            clean::StructFieldItem(ref ty) => object! {
+                "type" => ty,
+            },
+            clean::VariantItem(ref v) => match v.kind {
+                clean::VariantKind::CLike => object! {
+                    "variant_kind" => "plain",
+                },
+                clean::VariantKind::Tuple(ref types) => object! {
+                    "variant_kind" => "tuple",
+                    "types" => types,
+                },
+                clean::VariantKind::Struct(ref s) => object! {
+                    "variant_kind" => "struct",
+                    "fields" => s.fields,
+                    "fields_stripped" => s.fields_stripped,
+                },
+            },
+            clean::MacroItem(ref m) => object! {
+                "source" => m.source,
+                "imported_from" => m.imported_from,
+            },
+            clean::PrimitiveItem(ref p) => object! {
+                "name" => p.as_str(),
+            },
+            clean::AssociatedConstItem(ref ty, ref default) => object! {
+                "type" => ty,
+                "default" => default,
+            },
+            clean::AssociatedTypeItem(ref bounds, ref default) => object! {
+                "bounds" => bounds,
+                "default" => default,
+            },
+            clean::DefaultImplItem(ref i) => object! {
+                "unsafe" => unsafety(i.unsafety),
+                "trait" => i.trait_,
+            },
+            clean::StrippedItem(box ref inner) => inner.to_json(),
+        }
+    }
+}
+
+impl ToJson for clean::Trait {
+    fn to_json(&self) -> Json {
+        object! {
+            "unsafe" => unsafety(self.unsafety),
+            "generics" => self.generics,
+            "bounds" => self.bounds,
+            "items" => self.items,
+        }
+    }
+}
+
+impl ToJson for clean::Span {
+    fn to_json(&self) -> Json {
+        if self.filename.is_empty() {
+            return Json::Null;
+        }
+        object! {
+            "filename" => self.filename,
+            "begin" => (self.loline, self.locol),
+            "end" => (self.hiline, self.hicol),
+        }
+    }
+}
+
+impl ToJson for clean::Stability {
+    fn to_json(&self) -> Json {
+        let level = match self.level {
+            stability::StabilityLevel::Stable => "stable",
+            stability::StabilityLevel::Unstable => "unstable",
+        };
+        object! {
+            "level" => level,
+            "feature" => self.feature,
+            "since" => self.since,
+            "unstable_reason" => self.unstable_reason,
+            "issue" => self.issue,
+        }
+    }
+}
+
+impl ToJson for clean::Deprecation {
+    fn to_json(&self) -> Json {
+        object! {
+            "since" => self.since,
+            "note" => self.note,
+        }
+    }
+}
+
+impl ToJson for ::doctree::StructType {
+    fn to_json(&self) -> Json {
+        match *self {
+            ::doctree::Plain => "plain",
+            ::doctree::Tuple => "tuple",
+            ::doctree::Unit => "unit",
+        }.to_json()
+    }
+}
+
+impl ToJson for clean::Generics {
+    fn to_json(&self) -> Json {
+        object! {
+            "lifetimes" => self.lifetimes,
+            "params" => self.type_params,
+            "where_predicates" => self.where_predicates,
+        }
+    }
+}
+
+impl ToJson for clean::Lifetime {
+    fn to_json(&self) -> Json {
+        self.get_ref().to_json()
+    }
+}
+
+impl ToJson for clean::TyParam {
+    fn to_json(&self) -> Json {
+        object! {
+            "name" => self.name,
+            "id" => id(self.did),
+            "bounds" => self.bounds,
+            "default" => self.default,
+        }
+    }
+}
+
+impl ToJson for clean::TyParamBound {
+    fn to_json(&self) -> Json {
+        match *self {
+            clean::RegionBound(ref lifetime) => object! {
+                "kind" => "lifetime",
+                "lifetime" => lifetime,
+            },
+            clean::TraitBound(ref poly_trait, modifier) => object! {
+                "kind" => "trait",
+                "trait" => poly_trait.trait_,
+                "lifetimes" => poly_trait.lifetimes,
+                "maybe" => modifier == hir::TraitBoundModifier::Maybe,
+            },
+        }
+    }
+}
+
+impl ToJson for clean::WherePredicate {
+    fn to_json(&self) -> Json {
+        match *self {
+            clean::WherePredicate::BoundPredicate { ref ty, ref bounds } => object! {
+                "kind" => "bound",
+                "type" => ty,
+                "bounds" => bounds,
+            },
+            clean::WherePredicate::RegionPredicate { ref lifetime, ref bounds } => object! {
+                "kind" => "lifetime",
+                "lifetime" => lifetime,
+                "bounds" => bounds,
+            },
+            clean::WherePredicate::EqPredicate { ref lhs, ref rhs } => object! {
+                "kind" => "eq",
+                "lhs" => lhs,
+                "rhs" => rhs,
+            },
+        }
+    }
+}
+
+impl ToJson for clean::FnDecl {
+    fn to_json(&self) -> Json {
+        let inputs: Vec<Json> = self.inputs.values.iter().map(|arg| object! {
+            "name" => arg.name,
+            "type" => arg.type_,
+        }).collect();
+        let output = match self.output {
+            clean::Return(ref ty) => ty.to_json(),
+            clean::DefaultReturn => Json::Null,
+        };
+        object! {
+            "inputs" => inputs,
+            "output" => output,
+            "variadic" => self.variadic,
+        }
+    }
+}
+
+impl ToJson for clean::Path {
+    fn to_json(&self) -> Json {
+        object! {
+            "global" => self.global,
+            "segments" => self.segments,
+        }
+    }
+}
+
+impl ToJson for clean::PathSegment {
+    fn to_json(&self) -> Json {
+        let args = match self.params {
+            clean::PathParameters::AngleBracketed { ref lifetimes, ref types, ref bindings } => {
+                let bindings: Vec<Json> = bindings.iter().map(|b| object! {
+                    "name" => b.name,
+                    "type" => b.ty,
+                }).collect();
+                object! {
+                    "kind" => "angle_bracketed",
+                    "lifetimes" => lifetimes,
+                    "types" => types,
+                    "bindings" => bindings,
+                }
+            }
+            clean::PathParameters::Parenthesized { ref inputs, ref output } => object! {
+                "kind" => "parenthesized",
+                "inputs" => inputs,
+                "output" => output,
+            },
+        };
+        object! {
+            "name" => self.name,
+            "args" => args,
+        }
+    }
+}
+
+impl ToJson for clean::Type {
+    fn to_json(&self) -> Json {
+        match *self {
+            clean::ResolvedPath { ref path, ref typarams, did, is_generic } => object! {
+                "kind" => "resolved_path",
+                "path" => path,
+                "id" => id(did),
+                "bounds" => typarams,
+                "is_generic" => is_generic,
+            },
+            clean::Generic(ref name) => object! {
+                "kind" => "generic",
+                "name" => name,
+            },
+            clean::Primitive(ref p) => object! {
+                "kind" => "primitive",
+                "name" => p.as_str(),
+            },
+            clean::BareFunction(ref f) => object! {
+                "kind" => "function_pointer",
+                "decl" => f.decl,
+                "generics" => f.generics,
+                "unsafe" => unsafety(f.unsafety),
+                "abi" => f.abi.name(),
+            },
+            clean::Tuple(ref types) => object! {
+                "kind" => "tuple",
+                "types" => types,
+            },
+            clean::Slice(ref ty) => object! {
+                "kind" => "slice",
+                "type" => ty,
+            },
+            clean::Array(ref ty, len) => object! {
+                "kind" => "array",
+                "type" => ty,
+                "len" => len,
+            },
+            clean::Never => object! {
+                "kind" => "never",
+            },
+            clean::Unique(ref ty) => object! {
+                "kind" => "box",
+                "type" => ty,
+            },
+            clean::RawPointer(mutability, ref ty) => object! {
+                "kind" => "raw_pointer",
+                "mutable" => mutable(mutability),
+                "type" => ty,
+            },
+            clean::BorrowedRef { ref lifetime, mutability, ref type_ } => object! {
+                "kind" => "borrowed_ref",
+                "lifetime" => lifetime,
+                "mutable" => mutable(mutability),
+                "type" => type_,
+            },
+            clean::QPath { ref name, ref self_type, ref trait_ } => object! {
+                "kind" => "qualified_path",
+                "name" => name,
+                "self_type" => self_type,
+                "trait" => trait_,
+            },
+            clean::Infer => object! {
+                "kind" => "infer",
+            },
+            clean::ImplTrait(ref bounds) => object! {
+                "kind" => "impl_trait",
+                "bounds" => bounds,
+            },
+        }
+    }
+}
@@ -65,6 +65,7 @@ use std::process;
 use std::sync::mpsc::channel;
 
 use externalfiles::ExternalHtml;
+use serialize::json::ToJson;
 use rustc::session::search_paths::SearchPaths;
 use rustc::session::config::{ErrorOutputType, RustcOptGroup, nightly_options,
                              Externs};
@@ -74,8 +75,10 @@ pub mod externalfiles;
 
 pub mod clean;
 pub mod core;
+pub mod coverage;
 pub mod doctree;
 pub mod fold;
+pub mod json;
 pub mod html {
     pub mod highlight;
     pub mod escape;
@@ -132,7 +135,7 @@ pub fn opts() -> Vec<RustcOptGroup> {
         stable(optopt("r", "input-format", "the input type of the specified file",
                       "[rust]")),
         stable(optopt("w", "output-format", "the output type to write",
-                      "[html]")),
+                      "[html|json]")),
         stable(optopt("o", "output", "where to place the output", "PATH")),
         stable(optopt("", "crate-name", "specify the name of this crate", "NAME")),
         stable(optmulti("L", "library-path", "directory to add to crate search path",
@@ -191,6 +194,20 @@ pub fn opts() -> Vec<RustcOptGroup> {
                         "URL")),
         unstable(optflag("", "enable-commonmark", "to enable commonmark doc rendering/testing")),
         unstable(optflag("", "display-warnings", "to print code warnings when testing doc")),
+        unstable(optflag("", "combine-doctests",
+                         "compile the code examples that allow it into a single test binary \
+                          when testing doc")),
+        unstable(optflag("", "show-coverage",
+                         "instead of generating documentation, report how many public items \
+                          are documented and how many have code examples, as a table or, with \
+                          `--output-format json`, as JSON")),
+        unstable(optopt("", "coverage-threshold",
+                        "with --show-coverage, fail if less than PERCENT of the public items \
+                         are documented",
+                        "PERCENT")),
+        unstable(optflag("", "bundle",
+                         "link to every external crate as if it was documented in the same \
+                          output directory, to build a self-contained bundle of several crates")),
     ]
 }
 
@@ -302,15 +319,16 @@ pub fn main_args(args: &[String]) -> isize {
     let playground_url = matches.opt_str("playground-url");
     let maybe_sysroot = matches.opt_str("sysroot").map(PathBuf::from);
     let display_warnings = matches.opt_present("display-warnings");
+    let combine_doctests = matches.opt_present("combine-doctests");
 
     match (should_test, markdown_input) {
         (true, true) => {
             return markdown::test(input, cfgs, libs, externs, test_args, maybe_sysroot, render_type,
-                                  display_warnings)
+                                  display_warnings, combine_doctests)
         }
         (true, false) => {
             return test::run(input, cfgs, libs, externs, test_args, crate_name, maybe_sysroot,
-                             render_type, display_warnings)
+                             render_type, display_warnings, combine_doctests)
         }
         (false, true) => return markdown::render(input,
                                                  output.unwrap_or(PathBuf::from("doc")),
@@ -321,8 +339,34 @@ pub fn main_args(args: &[String]) -> isize {
     }
 
     let output_format = matches.opt_str("w");
+    let show_coverage = matches.opt_present("show-coverage");
+    let bundle = matches.opt_present("bundle");
+    let coverage_threshold = match matches.opt_str("coverage-threshold") {
+        Some(s) => match s.parse::<f64>() {
+            Ok(threshold) if threshold >= 0.0 && threshold <= 100.0 => Some(threshold),
+            _ => {
+                print_error(format!("invalid coverage threshold `{}`, expected a percentage \
+                                     between 0 and 100", s));
+                return 1;
+            }
+        },
+        None => None,
+    };
+    if coverage_threshold.is_some() && !show_coverage {
+        print_error("--coverage-threshold requires --show-coverage");
+        return 1;
+    }
+    if output_format.as_ref().map(|s| &**s) == Some("json") &&
+       !nightly_options::is_unstable_enabled(&matches) {
+        print_error("the `json` output format is unstable, pass `-Z unstable-options` \
+                     to use it");
+        return 1;
+    }
     let res = acquire_input(input, externs, &matches, move |out| {
         let Output { krate, passes, renderinfo } = out;
+        if show_coverage {
+            return show_doc_coverage(&krate, output_format, coverage_threshold);
+        }
         info!("going to format");
         match output_format.as_ref().map(|s| &**s) {
             Some("html") | None => {
@@ -331,7 +375,13 @@ pub fn main_args(args: &[String]) -> isize {
                                   passes.into_iter().collect(),
                                   css_file_extension,
                                   renderinfo,
-                                  render_type)
+                                  render_type,
+                                  bundle)
+                    .expect("failed to generate documentation");
+                0
+            }
+            Some("json") => {
+                json::run(krate, output.unwrap_or(PathBuf::from("doc")))
                     .expect("failed to generate documentation");
                 0
             }
@@ -347,6 +397,33 @@ pub fn main_args(args: &[String]) -> isize {
     })
 }
 
+/// Prints the documentation coverage of `krate`, returning an error code if
+/// it is below `threshold`.
+fn show_doc_coverage(krate: &clean::Crate,
+                     output_format: Option<String>,
+                     threshold: Option<f64>) -> isize {
+    let coverage = coverage::Coverage::new(krate);
+    match output_format.as_ref().map(|s| &**s) {
+        Some("html") | None => coverage.print_table(),
+        Some("json") => println!("{}", coverage.to_json()),
+        Some(s) => {
+            print_error(format!("unknown output format: {}", s));
+            return 1;
+        }
+    }
+    if let Some(threshold) = threshold {
+        let percentage = coverage.total.docs_percentage();
+        if percentage < threshold {
+            writeln!(&mut io::stderr(),
+                     "rustdoc: {:.1}% of the public items are documented, below the \
+                      threshold of {}%",
+                     percentage, threshold).unwrap();
+            return 1;
+        }
+    }
+    0
+}
+
 /// Prints an uniformised error message on the standard error output
 fn print_error<T>(error_message: T) where T: Display {
     writeln!(
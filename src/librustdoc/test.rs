@@ -13,6 +13,7 @@ use std::env;
 use std::ffi::OsString;
 use std::io::prelude::*;
 use std::io;
+use std::mem;
 use std::path::{Path, PathBuf};
 use std::panic::{self, AssertUnwindSafe};
 use std::process::Command;
@@ -60,7 +61,8 @@ pub fn run(input: &str,
            crate_name: Option<String>,
            maybe_sysroot: Option<PathBuf>,
            render_type: RenderType,
-           display_warnings: bool)
+           display_warnings: bool,
+           combine_doctests: bool)
            -> isize {
     let input_path = PathBuf::from(input);
     let input = config::Input::File(input_path.clone());
@@ -111,7 +113,8 @@ pub fn run(input: &str,
                                        maybe_sysroot,
                                        Some(codemap),
                                        None,
-                                       render_type);
+                                       render_type,
+                                       combine_doctests);
 
     {
         let dep_graph = DepGraph::new(false);
@@ -126,6 +129,7 @@ pub fn run(input: &str,
             intravisit::walk_crate(this, krate);
         });
     }
+    collector.add_combined_tests();
 
     test_args.insert(0, "rustdoctest".to_string());
 
@@ -167,6 +171,17 @@ fn scrape_test_config(krate: &::rustc::hir::Crate) -> TestOptions {
     opts
 }
 
+/// An output sink shared between rustc's diagnostic emitter and the panic
+/// output of the current thread.
+struct Sink(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Sink {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        Write::write(&mut *self.0.lock().unwrap(), data)
+    }
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
 fn runtest(test: &str, cratename: &str, cfgs: Vec<String>, libs: SearchPaths,
            externs: Externs,
            should_panic: bool, no_run: bool, as_test_harness: bool,
@@ -207,13 +222,6 @@ fn runtest(test: &str, cratename: &str, cfgs: Vec<String>, libs: SearchPaths,
     //
     // The basic idea is to not use a default Handler for rustc, and then also
     // not print things by default to the actual stderr.
-    struct Sink(Arc<Mutex<Vec<u8>>>);
-    impl Write for Sink {
-        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
-            Write::write(&mut *self.0.lock().unwrap(), data)
-        }
-        fn flush(&mut self) -> io::Result<()> { Ok(()) }
-    }
     struct Bomb(Arc<Mutex<Vec<u8>>>, Box<Write+Send>);
     impl Drop for Bomb {
         fn drop(&mut self) {
@@ -223,7 +231,8 @@ fn runtest(test: &str, cratename: &str, cfgs: Vec<String>, libs: SearchPaths,
     let data = Arc::new(Mutex::new(Vec::new()));
     let codemap = Rc::new(CodeMap::new(sessopts.file_path_mapping()));
     let emitter = errors::emitter::EmitterWriter::new(box Sink(data.clone()),
-                                                      Some(codemap.clone()));
+                                                      Some(codemap.clone()),
+                                                      false);
     let old = io::set_panic(Some(box Sink(data.clone())));
     let _bomb = Bomb(data.clone(), old.unwrap_or(box io::stdout()));
 
@@ -291,20 +300,7 @@ fn runtest(test: &str, cratename: &str, cfgs: Vec<String>, libs: SearchPaths,
     if no_run { return }
 
     // Run the code!
-    //
-    // We're careful to prepend the *target* dylib search path to the child's
-    // environment to ensure that the target loads the right libraries at
-    // runtime. It would be a sad day if the *host* libraries were loaded as a
-    // mistake.
-    let mut cmd = Command::new(&outdir.lock().unwrap().path().join("rust_out"));
-    let var = DynamicLibrary::envvar();
-    let newpath = {
-        let path = env::var_os(var).unwrap_or(OsString::new());
-        let mut path = env::split_paths(&path).collect::<Vec<_>>();
-        path.insert(0, libdir.clone());
-        env::join_paths(path).unwrap()
-    };
-    cmd.env(var, &newpath);
+    let mut cmd = test_command(&outdir.lock().unwrap().path().join("rust_out"), &libdir);
 
     match cmd.output() {
         Err(e) => panic!("couldn't run the test: {}{}", e,
@@ -323,6 +319,184 @@ fn runtest(test: &str, cratename: &str, cfgs: Vec<String>, libs: SearchPaths,
     }
 }
 
+/// Builds the command running a compiled test.
+///
+/// We're careful to prepend the *target* dylib search path to the child's
+/// environment to ensure that the target loads the right libraries at
+/// runtime. It would be a sad day if the *host* libraries were loaded as a
+/// mistake.
+fn test_command(exe: &Path, libdir: &Path) -> Command {
+    let mut cmd = Command::new(exe);
+    let var = DynamicLibrary::envvar();
+    let newpath = {
+        let path = env::var_os(var).unwrap_or(OsString::new());
+        let mut path = env::split_paths(&path).collect::<Vec<_>>();
+        path.insert(0, libdir.to_path_buf());
+        env::join_paths(path).unwrap()
+    };
+    cmd.env(var, &newpath);
+    cmd
+}
+
+/// A code example waiting to be compiled as part of the combined test crate.
+struct CombinedTest {
+    name: String,
+    test: String,
+    should_panic: bool,
+}
+
+/// Whether a code example can be compiled as part of the combined test
+/// crate. Crate attributes, `extern crate` and `fn main` only make sense at
+/// the root of a crate of their own.
+fn can_combine(test: &str) -> bool {
+    let (crate_attrs, _) = partition_source(test);
+    crate_attrs.trim().is_empty() && !test.contains("extern crate") && !test.contains("fn main")
+}
+
+fn combined_test_module(index: usize) -> String {
+    format!("__doctest_{}", index)
+}
+
+/// Generates the source of the combined test crate, in which every example
+/// becomes a `#[test]` function in a module of its own.
+fn make_combined_test(tests: &[CombinedTest], cratename: &str, opts: &TestOptions) -> String {
+    let mut prog = String::new();
+    for attr in &opts.attrs {
+        prog.push_str(&format!("#![{}]\n", attr));
+    }
+    if !opts.no_crate_inject && cratename != "std" &&
+       tests.iter().any(|t| t.test.contains(cratename)) {
+        prog.push_str(&format!("extern crate {};\n", cratename));
+    }
+    for (i, test) in tests.iter().enumerate() {
+        prog.push_str(&format!("\nmod {} {{\n", combined_test_module(i)));
+        prog.push_str("#[allow(unused_imports)]\nuse super::*;\n#[test]\n");
+        if test.should_panic {
+            prog.push_str("#[should_panic]\n");
+        }
+        prog.push_str("fn run() {\n");
+        prog.push_str(test.test.trim());
+        prog.push_str("\n}\n}\n");
+    }
+
+    info!("combined test program: {}", prog);
+
+    prog
+}
+
+/// The crate made of all the examples that can be compiled together, which
+/// is compiled by whichever of its tests runs first.
+struct CombinedDoctests {
+    source: String,
+    cfgs: Vec<String>,
+    libs: SearchPaths,
+    externs: Externs,
+    maybe_sysroot: Option<PathBuf>,
+    /// The output directory and target library directory of the compiled
+    /// crate, or `Some(None)` if it failed to compile.
+    binary: Mutex<Option<Option<(TempDir, PathBuf)>>>,
+}
+
+impl CombinedDoctests {
+    /// Runs the example at `index`, compiling the crate first if needed.
+    /// Returns `false` if the crate does not compile, in which case the
+    /// example has to be tested on its own.
+    fn run(&self, index: usize) -> bool {
+        let (exe, libdir) = {
+            let mut binary = self.binary.lock().unwrap();
+            if binary.is_none() {
+                *binary = Some(self.compile());
+            }
+            match *binary {
+                Some(Some((ref outdir, ref libdir))) => {
+                    (outdir.path().join("rust_out"), libdir.clone())
+                }
+                _ => return false,
+            }
+        };
+
+        let mut cmd = test_command(&exe, &libdir);
+        cmd.arg("--exact").arg(format!("{}::run", combined_test_module(index)));
+        match cmd.output() {
+            Err(e) => panic!("couldn't run the test: {}{}", e,
+                            if e.kind() == io::ErrorKind::PermissionDenied {
+                                " - maybe your tempdir is mounted with noexec?"
+                            } else { "" }),
+            Ok(out) => {
+                let stdout = str::from_utf8(&out.stdout).unwrap_or("");
+                if !out.status.success() || !stdout.contains("running 1 test") {
+                    panic!("test executable failed:\n{}\n{}\n",
+                           stdout,
+                           str::from_utf8(&out.stderr).unwrap_or(""));
+                }
+            }
+        }
+        true
+    }
+
+    fn compile(&self) -> Option<(TempDir, PathBuf)> {
+        let input = config::Input::Str {
+            name: driver::anon_src(),
+            input: self.source.clone(),
+        };
+        let sessopts = config::Options {
+            maybe_sysroot: self.maybe_sysroot.clone().or_else(
+                || Some(env::current_exe().unwrap().parent().unwrap().parent().unwrap()
+                                                   .to_path_buf())),
+            search_paths: self.libs.clone(),
+            crate_types: vec![config::CrateTypeExecutable],
+            output_types: OutputTypes::new(&[(OutputType::Exe, None)]),
+            externs: self.externs.clone(),
+            cg: config::CodegenOptions {
+                prefer_dynamic: true,
+                .. config::basic_codegen_options()
+            },
+            test: true,
+            unstable_features: UnstableFeatures::from_environment(),
+            ..config::basic_options().clone()
+        };
+
+        // Nothing is reported if the combined crate fails to compile: the
+        // examples are then compiled one by one, which gives each failing
+        // example its own diagnostics.
+        let data = Arc::new(Mutex::new(Vec::new()));
+        let codemap = Rc::new(CodeMap::new(sessopts.file_path_mapping()));
+        let emitter = errors::emitter::EmitterWriter::new(box Sink(data.clone()),
+                                                          Some(codemap.clone()),
+                                                          false);
+        let old = io::set_panic(Some(box Sink(data.clone())));
+        let diagnostic_handler = errors::Handler::with_emitter(true, false, box emitter);
+
+        let dep_graph = DepGraph::new(false);
+        let cstore = Rc::new(CStore::new(&dep_graph, box rustc_trans::LlvmMetadataLoader));
+        let mut sess = session::build_session_(
+            sessopts, &dep_graph, None, diagnostic_handler, codemap, cstore.clone(),
+        );
+        rustc_trans::init(&sess);
+        rustc_lint::register_builtins(&mut sess.lint_store.borrow_mut(), Some(&sess));
+        sess.parse_sess.config =
+            config::build_configuration(&sess, config::parse_cfgspecs(self.cfgs.clone()));
+
+        let outdir = TempDir::new("rustdoctest").ok().expect("rustdoc needs a tempdir");
+        let libdir = sess.target_filesearch(PathKind::All).get_lib_path();
+        let out = Some(outdir.path().to_path_buf());
+        let control = driver::CompileController::basic();
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            driver::compile_input(&sess, &cstore, &input, &out, &None, None, &control)
+        }));
+        io::set_panic(old);
+
+        match res {
+            Ok(Ok(())) => Some((outdir, libdir)),
+            _ => {
+                debug!("combined doctests failed to compile:\n{}",
+                       String::from_utf8_lossy(&data.lock().unwrap()));
+                None
+            }
+        }
+    }
+}
+
 pub fn maketest(s: &str, cratename: Option<&str>, dont_insert_main: bool,
                 opts: &TestOptions) -> String {
     let (crate_attrs, everything_else) = partition_source(s);
@@ -405,13 +579,15 @@ pub struct Collector {
     filename: Option<String>,
     // to be removed when hoedown will be removed as well
     pub render_type: RenderType,
+    combine_doctests: bool,
+    combined: Vec<CombinedTest>,
 }
 
 impl Collector {
     pub fn new(cratename: String, cfgs: Vec<String>, libs: SearchPaths, externs: Externs,
                use_headers: bool, opts: TestOptions, maybe_sysroot: Option<PathBuf>,
                codemap: Option<Rc<CodeMap>>, filename: Option<String>,
-               render_type: RenderType) -> Collector {
+               render_type: RenderType, combine_doctests: bool) -> Collector {
         Collector {
             tests: Vec::new(),
             old_tests: HashMap::new(),
@@ -429,6 +605,8 @@ impl Collector {
             codemap: codemap,
             filename: filename,
             render_type: render_type,
+            combine_doctests: combine_doctests,
+            combined: Vec::new(),
         }
     }
 
@@ -486,6 +664,16 @@ impl Collector {
                 return
             }
         }
+        if self.combine_doctests && !should_ignore && !no_run && !as_test_harness &&
+           !compile_fail && error_codes.is_empty() && can_combine(&test) {
+            debug!("Combining test {}: {}", name, test);
+            self.combined.push(CombinedTest {
+                name: name,
+                test: test,
+                should_panic: should_panic,
+            });
+            return
+        }
         let cfgs = self.cfgs.clone();
         let libs = self.libs.clone();
         let externs = self.externs.clone();
@@ -499,6 +687,7 @@ impl Collector {
                 ignore: should_ignore,
                 // compiler failures are test failures
                 should_panic: testing::ShouldPanic::No,
+                timeout: None,
             },
             testfn: testing::DynTestFn(box move |()| {
                 let panic = io::set_panic(None);
@@ -528,6 +717,65 @@ impl Collector {
         });
     }
 
+    /// Registers the tests for the examples set aside by `add_test` to be
+    /// compiled together. This must be called once all the examples have been
+    /// collected.
+    pub fn add_combined_tests(&mut self) {
+        if self.combined.is_empty() {
+            return
+        }
+        let tests = mem::replace(&mut self.combined, Vec::new());
+        let combined = Arc::new(CombinedDoctests {
+            source: make_combined_test(&tests, &self.cratename, &self.opts),
+            cfgs: self.cfgs.clone(),
+            libs: self.libs.clone(),
+            externs: self.externs.clone(),
+            maybe_sysroot: self.maybe_sysroot.clone(),
+            binary: Mutex::new(None),
+        });
+        for (index, CombinedTest { name, test, should_panic }) in tests.into_iter().enumerate() {
+            let combined = combined.clone();
+            let cratename = self.cratename.to_string();
+            let opts = self.opts.clone();
+            self.tests.push(testing::TestDescAndFn {
+                desc: testing::TestDesc {
+                    name: testing::DynTestName(name),
+                    ignore: false,
+                    // compiler failures are test failures
+                    should_panic: testing::ShouldPanic::No,
+                    timeout: None,
+                },
+                testfn: testing::DynTestFn(box move |()| {
+                    let panic = io::set_panic(None);
+                    let print = io::set_print(None);
+                    match {
+                        rustc_driver::in_rustc_thread(move || {
+                            io::set_panic(panic);
+                            io::set_print(print);
+                            if !combined.run(index) {
+                                runtest(&test,
+                                        &cratename,
+                                        combined.cfgs.clone(),
+                                        combined.libs.clone(),
+                                        combined.externs.clone(),
+                                        should_panic,
+                                        false,
+                                        false,
+                                        false,
+                                        Vec::new(),
+                                        &opts,
+                                        combined.maybe_sysroot.clone())
+                            }
+                        })
+                    } {
+                        Ok(()) => (),
+                        Err(err) => panic::resume_unwind(err),
+                    }
+                }),
+            });
+        }
+    }
+
     pub fn get_line(&self) -> usize {
         if let Some(ref codemap) = self.codemap {
             let line = self.position.lo.to_usize();
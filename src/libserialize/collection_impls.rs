@@ -30,13 +30,12 @@ impl<
 
 impl<T:Decodable> Decodable for LinkedList<T> {
     fn decode<D: Decoder>(d: &mut D) -> Result<LinkedList<T>, D::Error> {
-        d.read_seq(|d, len| {
-            let mut list = LinkedList::new();
-            for i in 0..len {
-                list.push_back(d.read_seq_elt(i, |d| Decodable::decode(d))?);
-            }
-            Ok(list)
-        })
+        let mut list = LinkedList::new();
+        d.read_seq_each(|d, i, _| {
+            list.push_back(d.read_seq_elt(i, |d| Decodable::decode(d))?);
+            Ok(())
+        })?;
+        Ok(list)
     }
 }
 
@@ -53,13 +52,12 @@ impl<T: Encodable> Encodable for VecDeque<T> {
 
 impl<T:Decodable> Decodable for VecDeque<T> {
     fn decode<D: Decoder>(d: &mut D) -> Result<VecDeque<T>, D::Error> {
-        d.read_seq(|d, len| {
-            let mut deque: VecDeque<T> = VecDeque::new();
-            for i in 0..len {
-                deque.push_back(d.read_seq_elt(i, |d| Decodable::decode(d))?);
-            }
-            Ok(deque)
-        })
+        let mut deque: VecDeque<T> = VecDeque::new();
+        d.read_seq_each(|d, i, _| {
+            deque.push_back(d.read_seq_elt(i, |d| Decodable::decode(d))?);
+            Ok(())
+        })?;
+        Ok(deque)
     }
 }
 
@@ -85,15 +83,14 @@ impl<
     V: Decodable + PartialEq
 > Decodable for BTreeMap<K, V> {
     fn decode<D: Decoder>(d: &mut D) -> Result<BTreeMap<K, V>, D::Error> {
-        d.read_map(|d, len| {
-            let mut map = BTreeMap::new();
-            for i in 0..len {
-                let key = d.read_map_elt_key(i, |d| Decodable::decode(d))?;
-                let val = d.read_map_elt_val(i, |d| Decodable::decode(d))?;
-                map.insert(key, val);
-            }
-            Ok(map)
-        })
+        let mut map = BTreeMap::new();
+        d.read_map_each(|d, i, _| {
+            let key = d.read_map_elt_key(i, |d| Decodable::decode(d))?;
+            let val = d.read_map_elt_val(i, |d| Decodable::decode(d))?;
+            map.insert(key, val);
+            Ok(())
+        })?;
+        Ok(map)
     }
 }
 
@@ -116,13 +113,12 @@ impl<
     T: Decodable + PartialEq + Ord
 > Decodable for BTreeSet<T> {
     fn decode<D: Decoder>(d: &mut D) -> Result<BTreeSet<T>, D::Error> {
-        d.read_seq(|d, len| {
-            let mut set = BTreeSet::new();
-            for i in 0..len {
-                set.insert(d.read_seq_elt(i, |d| Decodable::decode(d))?);
-            }
-            Ok(set)
-        })
+        let mut set = BTreeSet::new();
+        d.read_seq_each(|d, i, _| {
+            set.insert(d.read_seq_elt(i, |d| Decodable::decode(d))?);
+            Ok(())
+        })?;
+        Ok(set)
     }
 }
 
@@ -150,16 +146,17 @@ impl<K, V, S> Decodable for HashMap<K, V, S>
           S: BuildHasher + Default,
 {
     fn decode<D: Decoder>(d: &mut D) -> Result<HashMap<K, V, S>, D::Error> {
-        d.read_map(|d, len| {
-            let state = Default::default();
-            let mut map = HashMap::with_capacity_and_hasher(len, state);
-            for i in 0..len {
-                let key = d.read_map_elt_key(i, |d| Decodable::decode(d))?;
-                let val = d.read_map_elt_val(i, |d| Decodable::decode(d))?;
-                map.insert(key, val);
+        let mut map = HashMap::with_hasher(Default::default());
+        d.read_map_each(|d, i, len| {
+            if i == 0 {
+                map.reserve(len.unwrap_or(0));
             }
-            Ok(map)
-        })
+            let key = d.read_map_elt_key(i, |d| Decodable::decode(d))?;
+            let val = d.read_map_elt_val(i, |d| Decodable::decode(d))?;
+            map.insert(key, val);
+            Ok(())
+        })?;
+        Ok(map)
     }
 }
 
@@ -184,13 +181,14 @@ impl<T, S> Decodable for HashSet<T, S>
           S: BuildHasher + Default,
 {
     fn decode<D: Decoder>(d: &mut D) -> Result<HashSet<T, S>, D::Error> {
-        d.read_seq(|d, len| {
-            let state = Default::default();
-            let mut set = HashSet::with_capacity_and_hasher(len, state);
-            for i in 0..len {
-                set.insert(d.read_seq_elt(i, |d| Decodable::decode(d))?);
+        let mut set = HashSet::with_hasher(Default::default());
+        d.read_seq_each(|d, i, len| {
+            if i == 0 {
+                set.reserve(len.unwrap_or(0));
             }
-            Ok(set)
-        })
+            set.insert(d.read_seq_elt(i, |d| Decodable::decode(d))?);
+            Ok(())
+        })?;
+        Ok(set)
     }
 }
@@ -2390,6 +2390,623 @@ impl ::Decoder for Decoder {
     }
 }
 
+/// Decodes a value from an `&mut io::Read` as it is read, without building a
+/// `Json` tree first.
+pub fn decode_from_reader<T: ::Decodable>(rdr: &mut Read) -> DecodeResult<T> {
+    let chars = ReaderChars { bytes: io::BufReader::new(rdr).bytes(), error: None };
+    let mut decoder = StreamingDecoder::new(Parser::new(chars));
+    let result = ::Decodable::decode(&mut decoder).and_then(|value| {
+        decoder.finish().map(|()| value)
+    });
+    // Reading stops at the first I/O error or invalid UTF-8 sequence, which
+    // the parser only sees as the end of the stream.
+    match decoder.parser.rdr.error.take() {
+        Some(SyntaxError(code, ..)) => {
+            Err(ParseError(SyntaxError(code, decoder.parser.line, decoder.parser.col)))
+        }
+        Some(e) => Err(ParseError(e)),
+        None => result,
+    }
+}
+
+/// The chars of an `io::Read`, up to the first I/O error or invalid UTF-8
+/// sequence, which is kept in `error`.
+struct ReaderChars<R> {
+    bytes: io::Bytes<R>,
+    error: Option<ParserError>,
+}
+
+impl<R: Read> Iterator for ReaderChars<R> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        if self.error.is_some() {
+            return None;
+        }
+        let mut buf = [0; 4];
+        buf[0] = match self.bytes.next() {
+            Some(Ok(b)) => b,
+            Some(Err(e)) => {
+                self.error = Some(io_error_to_error(e));
+                return None;
+            }
+            None => return None,
+        };
+        let width = match buf[0] {
+            0x00 ... 0x7f => 1,
+            0xc0 ... 0xdf => 2,
+            0xe0 ... 0xef => 3,
+            0xf0 ... 0xf7 => 4,
+            _ => 0,
+        };
+        for i in 1..width {
+            match self.bytes.next() {
+                Some(Ok(b)) => buf[i] = b,
+                Some(Err(e)) => {
+                    self.error = Some(io_error_to_error(e));
+                    return None;
+                }
+                // The missing bytes are left as zeros, which fail validation.
+                None => break,
+            }
+        }
+        match str::from_utf8(&buf[..width]).ok().and_then(|s| s.chars().next()) {
+            Some(c) => Some(c),
+            None => {
+                self.error = Some(SyntaxError(NotUtf8, 0, 0));
+                None
+            }
+        }
+    }
+}
+
+// An event of the streaming parser, along with the key of the object member
+// whose value it starts, if any.
+type Token = (Option<string::String>, JsonEvent);
+
+/// A structure to decode JSON to values in rust directly from the events of a
+/// `Parser`, without building a `Json` tree first.
+///
+/// Values are read in the order the `Decodable` implementations ask for them,
+/// so only what cannot be decoded straight away is kept around: the members
+/// of an object which come before the struct field being read, the fields of
+/// an enum variant which come before its name, and the elements of arrays and
+/// maps read with `read_seq` or `read_map`, whose length has to be known
+/// before the first one is decoded. The collections of the standard library
+/// use `read_seq_each` and `read_map_each` instead, which decode each element
+/// as it is read.
+pub struct StreamingDecoder<T> {
+    parser: Parser<T>,
+    // Tokens to read before asking the parser for more, in reverse order.
+    pending: Vec<Token>,
+    // For each struct being decoded, the members which appeared before the
+    // field being read.
+    fields: Vec<BTreeMap<string::String, Vec<Token>>>,
+}
+
+impl<T: Iterator<Item=char>> StreamingDecoder<T> {
+    /// Creates a new decoder instance for decoding the value the parser reads.
+    pub fn new(parser: Parser<T>) -> StreamingDecoder<T> {
+        StreamingDecoder { parser: parser, pending: Vec::new(), fields: Vec::new() }
+    }
+
+    /// Checks that the decoded value is not followed by anything but
+    /// whitespace.
+    pub fn finish(&mut self) -> DecodeResult<()> {
+        if !self.pending.is_empty() {
+            return self.parser.error(TrailingCharacters).map_err(ParseError);
+        }
+        match self.parser.next() {
+            None => Ok(()),
+            Some(Error(e)) => Err(ParseError(e)),
+            Some(_) => self.parser.error(TrailingCharacters).map_err(ParseError),
+        }
+    }
+
+    fn next_token(&mut self) -> DecodeResult<Token> {
+        if let Some(token) = self.pending.pop() {
+            return Ok(token);
+        }
+        match self.parser.next() {
+            Some(Error(e)) => Err(ParseError(e)),
+            Some(event) => {
+                let key = match event {
+                    ArrayEnd | ObjectEnd => None,
+                    _ => match self.parser.stack().top() {
+                        Some(StackElement::Key(key)) => Some(key.to_owned()),
+                        _ => None,
+                    },
+                };
+                Ok((key, event))
+            }
+            None => self.parser.error(EOFWhileParsingValue).map_err(ParseError),
+        }
+    }
+
+    fn next_event(&mut self) -> DecodeResult<JsonEvent> {
+        self.next_token().map(|(_, event)| event)
+    }
+
+    fn peek(&mut self) -> DecodeResult<&Token> {
+        if self.pending.is_empty() {
+            let token = self.next_token()?;
+            self.pending.push(token);
+        }
+        Ok(self.pending.last().unwrap())
+    }
+
+    fn peek_is(&mut self, event: JsonEvent) -> DecodeResult<bool> {
+        self.peek().map(|&(_, ref next)| *next == event)
+    }
+
+    fn replay(&mut self, tokens: Vec<Token>) {
+        self.pending.extend(tokens.into_iter().rev());
+    }
+
+    // Reads the tokens of the next value, including those of its elements.
+    fn read_value_tokens(&mut self, tokens: &mut Vec<Token>) -> DecodeResult<()> {
+        let mut depth = 0;
+        loop {
+            let token = self.next_token()?;
+            match token.1 {
+                ArrayStart | ObjectStart => depth += 1,
+                ArrayEnd | ObjectEnd => depth -= 1,
+                _ => {}
+            }
+            tokens.push(token);
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_value(&mut self) -> DecodeResult<()> {
+        let mut depth = 0;
+        loop {
+            match self.next_event()? {
+                ArrayStart | ObjectStart => depth += 1,
+                ArrayEnd | ObjectEnd => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_members(&mut self) -> DecodeResult<()> {
+        while !self.peek_is(ObjectEnd)? {
+            self.skip_value()?;
+        }
+        self.next_token().map(|_| ())
+    }
+
+    // Builds the value starting with `event`, to report it in an error.
+    fn build_value(&mut self, event: JsonEvent) -> DecodeResult<Json> {
+        Ok(match event {
+            NullValue => Json::Null,
+            BooleanValue(b) => Json::Boolean(b),
+            I64Value(n) => Json::I64(n),
+            U64Value(n) => Json::U64(n),
+            F64Value(n) => Json::F64(n),
+            StringValue(s) => Json::String(s),
+            ArrayStart => {
+                let mut values = Vec::new();
+                loop {
+                    let event = self.next_event()?;
+                    if event == ArrayEnd {
+                        break;
+                    }
+                    values.push(self.build_value(event)?);
+                }
+                Json::Array(values)
+            }
+            ObjectStart => {
+                let mut values = BTreeMap::new();
+                loop {
+                    let (key, event) = self.next_token()?;
+                    if event == ObjectEnd {
+                        break;
+                    }
+                    values.insert(key.unwrap_or_default(), self.build_value(event)?);
+                }
+                Json::Object(values)
+            }
+            ArrayEnd | ObjectEnd => return self.parser.error(InvalidSyntax).map_err(ParseError),
+            Error(e) => return Err(ParseError(e)),
+        })
+    }
+
+    fn expected(&mut self, expected: &str, event: JsonEvent) -> DecoderError {
+        match self.build_value(event) {
+            Ok(value) => ExpectedError(expected.to_owned(), format!("{}", value)),
+            Err(e) => e,
+        }
+    }
+
+    fn expect_array_start(&mut self) -> DecodeResult<()> {
+        match self.next_event()? {
+            ArrayStart => Ok(()),
+            event => Err(self.expected("Array", event)),
+        }
+    }
+
+    fn expect_array_end(&mut self) -> DecodeResult<()> {
+        match self.next_event()? {
+            ArrayEnd => Ok(()),
+            event => Err(self.expected("end of array", event)),
+        }
+    }
+}
+
+macro_rules! read_event_primitive {
+    ($name:ident, $ty:ty) => {
+        fn $name(&mut self) -> DecodeResult<$ty> {
+            match self.next_event()? {
+                I64Value(f) => Ok(f as $ty),
+                U64Value(f) => Ok(f as $ty),
+                F64Value(f) => Err(ExpectedError("Integer".to_owned(), format!("{}", f))),
+                // Numeric map keys are strings, see `read_primitive`.
+                StringValue(s) => match s.parse().ok() {
+                    Some(f) => Ok(f),
+                    None => Err(ExpectedError("Number".to_owned(), s)),
+                },
+                event => Err(self.expected("Number", event)),
+            }
+        }
+    }
+}
+
+impl<T: Iterator<Item=char>> ::Decoder for StreamingDecoder<T> {
+    type Error = DecoderError;
+
+    fn read_nil(&mut self) -> DecodeResult<()> {
+        match self.next_event()? {
+            NullValue => Ok(()),
+            event => Err(self.expected("Null", event)),
+        }
+    }
+
+    read_event_primitive! { read_usize, usize }
+    read_event_primitive! { read_u8, u8 }
+    read_event_primitive! { read_u16, u16 }
+    read_event_primitive! { read_u32, u32 }
+    read_event_primitive! { read_u64, u64 }
+    read_event_primitive! { read_u128, u128 }
+    read_event_primitive! { read_isize, isize }
+    read_event_primitive! { read_i8, i8 }
+    read_event_primitive! { read_i16, i16 }
+    read_event_primitive! { read_i32, i32 }
+    read_event_primitive! { read_i64, i64 }
+    read_event_primitive! { read_i128, i128 }
+
+    fn read_f32(&mut self) -> DecodeResult<f32> { self.read_f64().map(|x| x as f32) }
+
+    fn read_f64(&mut self) -> DecodeResult<f64> {
+        match self.next_event()? {
+            I64Value(f) => Ok(f as f64),
+            U64Value(f) => Ok(f as f64),
+            F64Value(f) => Ok(f),
+            StringValue(s) => match s.parse().ok() {
+                Some(f) => Ok(f),
+                None => Err(ExpectedError("Number".to_owned(), s)),
+            },
+            NullValue => Ok(f64::NAN),
+            event => Err(self.expected("Number", event)),
+        }
+    }
+
+    fn read_bool(&mut self) -> DecodeResult<bool> {
+        match self.next_event()? {
+            BooleanValue(b) => Ok(b),
+            event => Err(self.expected("Boolean", event)),
+        }
+    }
+
+    fn read_char(&mut self) -> DecodeResult<char> {
+        let s = self.read_str()?;
+        {
+            let mut it = s.chars();
+            match (it.next(), it.next()) {
+                // exactly one character
+                (Some(c), None) => return Ok(c),
+                _ => ()
+            }
+        }
+        Err(ExpectedError("single character string".to_owned(), format!("{}", s)))
+    }
+
+    fn read_str(&mut self) -> DecodeResult<Cow<str>> {
+        match self.next_event()? {
+            StringValue(s) => Ok(Cow::Owned(s)),
+            event => Err(self.expected("String", event)),
+        }
+    }
+
+    fn read_enum<V, F>(&mut self, _name: &str, f: F) -> DecodeResult<V> where
+        F: FnOnce(&mut StreamingDecoder<T>) -> DecodeResult<V>,
+    {
+        f(self)
+    }
+
+    fn read_enum_variant<V, F>(&mut self, names: &[&str],
+                               mut f: F) -> DecodeResult<V>
+        where F: FnMut(&mut StreamingDecoder<T>, usize) -> DecodeResult<V>,
+    {
+        let variant_idx = |name: string::String| {
+            match names.iter().position(|n| *n == &name[..]) {
+                Some(idx) => Ok(idx),
+                None => Err(UnknownVariantError(name)),
+            }
+        };
+        match self.next_event()? {
+            StringValue(s) => return f(self, variant_idx(s)?),
+            ObjectStart => {}
+            event => return Err(self.expected("String or Object", event)),
+        }
+
+        let mut name = None;
+        let mut fields = None;
+        while !self.peek_is(ObjectEnd)? {
+            let key = self.peek()?.0.clone();
+            match key.as_ref().map(|key| &key[..]) {
+                Some("variant") => {
+                    match self.next_event()? {
+                        StringValue(s) => name = Some(s),
+                        event => return Err(self.expected("String", event)),
+                    }
+                }
+                Some("fields") => {
+                    // The fields can be decoded as they are read once the
+                    // variant is known.
+                    if let Some(name) = name.take() {
+                        self.expect_array_start()?;
+                        let value = f(self, variant_idx(name)?)?;
+                        self.expect_array_end()?;
+                        self.skip_members()?;
+                        return Ok(value);
+                    }
+                    let mut tokens = Vec::new();
+                    self.read_value_tokens(&mut tokens)?;
+                    fields = Some(tokens);
+                }
+                _ => self.skip_value()?,
+            }
+        }
+        self.next_token()?;
+
+        let name = match name {
+            Some(name) => name,
+            None => return Err(MissingFieldError("variant".to_owned())),
+        };
+        let fields = match fields {
+            Some(fields) => fields,
+            None => return Err(MissingFieldError("fields".to_owned())),
+        };
+        match fields.first().map(|&(_, ref event)| event.clone()) {
+            Some(ArrayStart) => {}
+            Some(event) => {
+                self.replay(fields);
+                self.next_token()?;
+                return Err(self.expected("Array", event));
+            }
+            None => return self.parser.error(InvalidSyntax).map_err(ParseError),
+        }
+        self.replay(fields);
+        self.next_token()?;
+        let value = f(self, variant_idx(name)?)?;
+        self.expect_array_end()?;
+        Ok(value)
+    }
+
+    fn read_enum_variant_arg<V, F>(&mut self, _idx: usize, f: F) -> DecodeResult<V> where
+        F: FnOnce(&mut StreamingDecoder<T>) -> DecodeResult<V>,
+    {
+        f(self)
+    }
+
+    fn read_enum_struct_variant<V, F>(&mut self, names: &[&str], f: F) -> DecodeResult<V> where
+        F: FnMut(&mut StreamingDecoder<T>, usize) -> DecodeResult<V>,
+    {
+        self.read_enum_variant(names, f)
+    }
+
+
+    fn read_enum_struct_variant_field<V, F>(&mut self,
+                                         _name: &str,
+                                         idx: usize,
+                                         f: F)
+                                         -> DecodeResult<V> where
+        F: FnOnce(&mut StreamingDecoder<T>) -> DecodeResult<V>,
+    {
+        self.read_enum_variant_arg(idx, f)
+    }
+
+    fn read_struct<V, F>(&mut self, _name: &str, _len: usize, f: F) -> DecodeResult<V> where
+        F: FnOnce(&mut StreamingDecoder<T>) -> DecodeResult<V>,
+    {
+        match self.next_event()? {
+            ObjectStart => {}
+            event => return Err(self.expected("Object", event)),
+        }
+        self.fields.push(BTreeMap::new());
+        let value = f(self);
+        self.fields.pop();
+        let value = value?;
+        self.skip_members()?;
+        Ok(value)
+    }
+
+    fn read_struct_field<V, F>(&mut self,
+                               name: &str,
+                               _idx: usize,
+                               f: F)
+                               -> DecodeResult<V> where
+        F: FnOnce(&mut StreamingDecoder<T>) -> DecodeResult<V>,
+    {
+        let buffered = self.fields.last_mut().and_then(|fields| fields.remove(name));
+        if let Some(tokens) = buffered {
+            self.replay(tokens);
+            return f(self);
+        }
+
+        while !self.peek_is(ObjectEnd)? {
+            let key = self.peek()?.0.clone().unwrap_or_default();
+            if key == name {
+                return f(self);
+            }
+            let mut tokens = Vec::new();
+            self.read_value_tokens(&mut tokens)?;
+            if let Some(fields) = self.fields.last_mut() {
+                fields.insert(key, tokens);
+            }
+        }
+
+        // Add a Null and try to parse it as an Option<_>
+        // to get None as a default value.
+        self.pending.push((None, NullValue));
+        match f(self) {
+            Ok(x) => Ok(x),
+            Err(_) => Err(MissingFieldError(name.to_string())),
+        }
+    }
+
+    fn read_tuple<V, F>(&mut self, tuple_len: usize, f: F) -> DecodeResult<V> where
+        F: FnOnce(&mut StreamingDecoder<T>) -> DecodeResult<V>,
+    {
+        self.read_seq(move |d, len| {
+            if len == tuple_len {
+                f(d)
+            } else {
+                Err(ExpectedError(format!("Tuple{}", tuple_len), format!("Tuple{}", len)))
+            }
+        })
+    }
+
+    fn read_tuple_arg<V, F>(&mut self, idx: usize, f: F) -> DecodeResult<V> where
+        F: FnOnce(&mut StreamingDecoder<T>) -> DecodeResult<V>,
+    {
+        self.read_seq_elt(idx, f)
+    }
+
+    fn read_tuple_struct<V, F>(&mut self,
+                               _name: &str,
+                               len: usize,
+                               f: F)
+                               -> DecodeResult<V> where
+        F: FnOnce(&mut StreamingDecoder<T>) -> DecodeResult<V>,
+    {
+        self.read_tuple(len, f)
+    }
+
+    fn read_tuple_struct_arg<V, F>(&mut self,
+                                   idx: usize,
+                                   f: F)
+                                   -> DecodeResult<V> where
+        F: FnOnce(&mut StreamingDecoder<T>) -> DecodeResult<V>,
+    {
+        self.read_tuple_arg(idx, f)
+    }
+
+    fn read_option<V, F>(&mut self, mut f: F) -> DecodeResult<V> where
+        F: FnMut(&mut StreamingDecoder<T>, bool) -> DecodeResult<V>,
+    {
+        if self.peek_is(NullValue)? {
+            self.next_token()?;
+            f(self, false)
+        } else {
+            f(self, true)
+        }
+    }
+
+    fn read_seq<V, F>(&mut self, f: F) -> DecodeResult<V> where
+        F: FnOnce(&mut StreamingDecoder<T>, usize) -> DecodeResult<V>,
+    {
+        self.expect_array_start()?;
+        let mut tokens = Vec::new();
+        let mut len = 0;
+        while !self.peek_is(ArrayEnd)? {
+            self.read_value_tokens(&mut tokens)?;
+            len += 1;
+        }
+        self.next_token()?;
+        self.replay(tokens);
+        f(self, len)
+    }
+
+    fn read_seq_elt<V, F>(&mut self, _idx: usize, f: F) -> DecodeResult<V> where
+        F: FnOnce(&mut StreamingDecoder<T>) -> DecodeResult<V>,
+    {
+        f(self)
+    }
+
+    fn read_seq_each<F>(&mut self, mut f: F) -> DecodeResult<()> where
+        F: FnMut(&mut StreamingDecoder<T>, usize, Option<usize>) -> DecodeResult<()>,
+    {
+        self.expect_array_start()?;
+        let mut idx = 0;
+        while !self.peek_is(ArrayEnd)? {
+            f(self, idx, None)?;
+            idx += 1;
+        }
+        self.next_token().map(|_| ())
+    }
+
+    fn read_map<V, F>(&mut self, f: F) -> DecodeResult<V> where
+        F: FnOnce(&mut StreamingDecoder<T>, usize) -> DecodeResult<V>,
+    {
+        match self.next_event()? {
+            ObjectStart => {}
+            event => return Err(self.expected("Object", event)),
+        }
+        let mut tokens = Vec::new();
+        let mut len = 0;
+        while !self.peek_is(ObjectEnd)? {
+            let key = self.peek()?.0.clone().unwrap_or_default();
+            tokens.push((None, StringValue(key)));
+            self.read_value_tokens(&mut tokens)?;
+            len += 1;
+        }
+        self.next_token()?;
+        self.replay(tokens);
+        f(self, len)
+    }
+
+    fn read_map_elt_key<V, F>(&mut self, _idx: usize, f: F) -> DecodeResult<V> where
+       F: FnOnce(&mut StreamingDecoder<T>) -> DecodeResult<V>,
+    {
+        f(self)
+    }
+
+    fn read_map_elt_val<V, F>(&mut self, _idx: usize, f: F) -> DecodeResult<V> where
+       F: FnOnce(&mut StreamingDecoder<T>) -> DecodeResult<V>,
+    {
+        f(self)
+    }
+
+    fn read_map_each<F>(&mut self, mut f: F) -> DecodeResult<()> where
+        F: FnMut(&mut StreamingDecoder<T>, usize, Option<usize>) -> DecodeResult<()>,
+    {
+        match self.next_event()? {
+            ObjectStart => {}
+            event => return Err(self.expected("Object", event)),
+        }
+        let mut idx = 0;
+        while !self.peek_is(ObjectEnd)? {
+            // The key is read as a string before the value it was peeked with.
+            let key = self.peek()?.0.clone().unwrap_or_default();
+            self.pending.push((None, StringValue(key)));
+            f(self, idx, None)?;
+            idx += 1;
+        }
+        self.next_token().map(|_| ())
+    }
+
+    fn error(&mut self, err: &str) -> DecoderError {
+        ApplicationError(err.to_string())
+    }
+}
+
 /// A trait for converting values to JSON
 pub trait ToJson {
     /// Converts the value of `self` to an instance of JSON
@@ -2613,7 +3230,7 @@ mod tests {
     use super::DecoderError::*;
     use super::JsonEvent::*;
     use super::{Json, from_str, DecodeResult, DecoderError, JsonEvent, Parser,
-                StackElement, Stack, Decoder, Encoder, EncoderError};
+                StackElement, Stack, Decoder, Encoder, EncoderError, StreamingDecoder};
     use std::{i64, u64, f32, f64};
     use std::io::prelude::*;
     use std::collections::BTreeMap;
@@ -3255,7 +3872,108 @@ mod tests {
                 assert_eq!(e, expected);
             }
         }
+        let res: DecodeResult<T> = streaming_decode(to_parse);
+        assert_eq!(res.err(), Some(expected));
     }
+    fn streaming_decode<T: Decodable>(s: &str) -> DecodeResult<T> {
+        let mut decoder = StreamingDecoder::new(Parser::new(s.chars()));
+        let value = Decodable::decode(&mut decoder)?;
+        decoder.finish()?;
+        Ok(value)
+    }
+
+    #[test]
+    fn test_streaming_decode_struct() {
+        let s = "{
+            \"unknown\": [1, {\"b\": 3}],
+            \"inner\": [
+                { \"c\": [\"abc\", \"xyz\"], \"b\": 2, \"a\": null },
+                { \"a\": null, \"b\": 3, \"c\": [] }
+            ]
+        }";
+        let v: Outer = streaming_decode(s).unwrap();
+        assert_eq!(
+            v,
+            Outer {
+                inner: vec![
+                    Inner { a: (), b: 2, c: vec!["abc".to_string(), "xyz".to_string()] },
+                    Inner { a: (), b: 3, c: vec![] },
+                ]
+            }
+        );
+
+        let obj: OptionData = streaming_decode("{}").unwrap();
+        assert_eq!(obj, OptionData { opt: None });
+    }
+
+    #[test]
+    fn test_streaming_decode_enum() {
+        let value: Animal = streaming_decode("\"Dog\"").unwrap();
+        assert_eq!(value, Dog);
+
+        let s = "{\"variant\":\"Frog\",\"fields\":[\"Henry\",349]}";
+        let value: Animal = streaming_decode(s).unwrap();
+        assert_eq!(value, Frog("Henry".to_string(), 349));
+
+        let s = "{\"fields\":[\"Henry\",349],\"variant\":\"Frog\"}";
+        let value: Animal = streaming_decode(s).unwrap();
+        assert_eq!(value, Frog("Henry".to_string(), 349));
+
+        // Extra fields are an error whichever of the members comes first.
+        let s = "{\"variant\":\"Frog\",\"fields\":[\"Henry\",349,[1]]}";
+        assert_eq!(streaming_decode::<Animal>(s),
+                   Err(ExpectedError("end of array".to_string(), "[1]".to_string())));
+        let s = "{\"fields\":[\"Henry\",349,[1]],\"variant\":\"Frog\"}";
+        assert_eq!(streaming_decode::<Animal>(s),
+                   Err(ExpectedError("end of array".to_string(), "[1]".to_string())));
+    }
+
+    #[test]
+    fn test_streaming_decode_map() {
+        let s = "{\"a\": \"Dog\", \"b\": {\"variant\":\"Frog\",\
+                  \"fields\":[\"Henry\", 349]}}";
+        let mut map: BTreeMap<string::String, Animal> = streaming_decode(s).unwrap();
+
+        assert_eq!(map.remove(&"a".to_string()), Some(Dog));
+        assert_eq!(map.remove(&"b".to_string()), Some(Frog("Henry".to_string(), 349)));
+    }
+
+    #[test]
+    fn test_streaming_decode_syntax_errors() {
+        assert_eq!(streaming_decode::<Inner>("{\n  \"a\":\n null, \"b\" 1}"),
+                   Err(ParseError(SyntaxError(ExpectedColon, 3, 13))));
+        assert_eq!(streaming_decode::<Vec<u8>>("[1, 2"),
+                   Err(ParseError(SyntaxError(EOFWhileParsingArray, 1, 6))));
+        assert_eq!(streaming_decode::<Vec<u8>>("[1, 2] 3"),
+                   Err(ParseError(SyntaxError(TrailingCharacters, 1, 8))));
+    }
+
+    #[test]
+    fn test_streaming_decode_elements_as_read() {
+        use std::collections::HashMap;
+
+        // The elements of collections are decoded before the rest of them is
+        // read, so the first bad element is reported rather than the syntax
+        // error following it.
+        assert_eq!(streaming_decode::<Vec<u8>>("[1, true, }"),
+                   Err(ExpectedError("Number".to_string(), "true".to_string())));
+        assert_eq!(streaming_decode::<BTreeMap<string::String, u8>>("{\"a\": 1, \"b\": [] ]"),
+                   Err(ExpectedError("Number".to_string(), "[]".to_string())));
+        assert_eq!(streaming_decode::<HashMap<string::String, Vec<u8>>>("{\"a\": [1, 2]}"),
+                   Ok(vec![("a".to_string(), vec![1, 2])].into_iter().collect()));
+    }
+
+    #[test]
+    fn test_decode_from_reader() {
+        let value: Vec<string::String> =
+            super::decode_from_reader(&mut &b"[\"h\xc3\xa9\", \"b\"]"[..]).unwrap();
+        assert_eq!(value, vec!["h\u{e9}".to_string(), "b".to_string()]);
+
+        let res: DecodeResult<Vec<string::String>> =
+            super::decode_from_reader(&mut &b"[\"a\",\n \"\xff\"]"[..]);
+        assert_eq!(res, Err(ParseError(SyntaxError(NotUtf8, 2, 4))));
+    }
+
     #[test]
     fn test_decode_errors_struct() {
         check_err::<DecodeStruct>("[]", ExpectedError("Object".to_string(), "[]".to_string()));
@@ -246,6 +246,19 @@ pub trait Decoder {
     }
     fn read_seq_elt<T, F>(&mut self, _idx: usize, f: F) -> Result<T, Self::Error>
         where F: FnOnce(&mut Self) -> Result<T, Self::Error> { f(self) }
+    /// Reads a sequence without asking for its length first, for decoders
+    /// which only know it once every element has been read. `f` is called
+    /// for each element with its index and the length, if it is known.
+    fn read_seq_each<F>(&mut self, mut f: F) -> Result<(), Self::Error>
+        where F: FnMut(&mut Self, usize, Option<usize>) -> Result<(), Self::Error>
+    {
+        self.read_seq(|d, len| {
+            for i in 0..len {
+                f(d, i, Some(len))?;
+            }
+            Ok(())
+        })
+    }
 
     fn read_map<T, F>(&mut self, f: F) -> Result<T, Self::Error>
         where F: FnOnce(&mut Self, usize) -> Result<T, Self::Error>
@@ -259,6 +272,18 @@ pub trait Decoder {
     fn read_map_elt_val<T, F>(&mut self, _idx: usize, f: F)
                               -> Result<T, Self::Error>
         where F: FnOnce(&mut Self) -> Result<T, Self::Error> { f(self) }
+    /// Reads a map without asking for its length first, like
+    /// `read_seq_each`.
+    fn read_map_each<F>(&mut self, mut f: F) -> Result<(), Self::Error>
+        where F: FnMut(&mut Self, usize, Option<usize>) -> Result<(), Self::Error>
+    {
+        self.read_map(|d, len| {
+            for i in 0..len {
+                f(d, i, Some(len))?;
+            }
+            Ok(())
+        })
+    }
 
     // Failure
     fn error(&mut self, err: &str) -> Self::Error;
@@ -557,13 +582,15 @@ impl<T:Encodable> Encodable for Vec<T> {
 
 impl<T:Decodable> Decodable for Vec<T> {
     fn decode<D: Decoder>(d: &mut D) -> Result<Vec<T>, D::Error> {
-        d.read_seq(|d, len| {
-            let mut v = Vec::with_capacity(len);
-            for i in 0..len {
-                v.push(d.read_seq_elt(i, |d| Decodable::decode(d))?);
+        let mut v = Vec::new();
+        d.read_seq_each(|d, i, len| {
+            if i == 0 {
+                v.reserve(len.unwrap_or(0));
             }
-            Ok(v)
-        })
+            v.push(d.read_seq_elt(i, |d| Decodable::decode(d))?);
+            Ok(())
+        })?;
+        Ok(v)
     }
 }
 
@@ -584,13 +611,15 @@ impl<T:Decodable+ToOwned> Decodable for Cow<'static, [T]>
 where [T]: ToOwned<Owned = Vec<T>>
 {
     fn decode<D: Decoder>(d: &mut D) -> Result<Cow<'static, [T]>, D::Error> {
-        d.read_seq(|d, len| {
-            let mut v = Vec::with_capacity(len);
-            for i in 0..len {
-                v.push(d.read_seq_elt(i, |d| Decodable::decode(d))?);
+        let mut v = Vec::new();
+        d.read_seq_each(|d, i, len| {
+            if i == 0 {
+                v.reserve(len.unwrap_or(0));
             }
-            Ok(Cow::Owned(v))
-        })
+            v.push(d.read_seq_elt(i, |d| Decodable::decode(d))?);
+            Ok(())
+        })?;
+        Ok(Cow::Owned(v))
     }
 }
 
@@ -904,13 +904,21 @@ pub fn get_exprs_from_tts(cx: &mut ExtCtxt,
     Some(es)
 }
 
+/// Gives the output of a procedural macro the span of its invocation. Spans
+/// which point into `input`, the code the macro was given, keep pointing
+/// there so that errors can be reported on the code they are about.
 pub struct ChangeSpan {
-    pub span: Span
+    pub span: Span,
+    pub input: Span,
 }
 
 impl Folder for ChangeSpan {
-    fn new_span(&mut self, _sp: Span) -> Span {
-        self.span
+    fn new_span(&mut self, sp: Span) -> Span {
+        if self.input.lo <= sp.lo && sp.hi <= self.input.hi && sp.lo < sp.hi {
+            Span { lo: sp.lo, hi: sp.hi, ctxt: self.span.ctxt }
+        } else {
+            self.span
+        }
     }
 
     fn fold_mac(&mut self, mac: ast::Mac) -> ast::Mac {
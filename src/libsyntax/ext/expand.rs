@@ -21,7 +21,7 @@ use ext::placeholders::{placeholder, PlaceholderExpander};
 use feature_gate::{self, Features, is_builtin_attr};
 use fold;
 use fold::*;
-use parse::{filemap_to_stream, ParseSess, DirectoryOwnership, PResult, token};
+use parse::{filemap_to_stream, stream_for_span, ParseSess, DirectoryOwnership, PResult, token};
 use parse::parser::Parser;
 use print::pprust;
 use ptr::P;
@@ -33,6 +33,7 @@ use tokenstream::TokenStream;
 use util::small_vector::SmallVector;
 use visit::Visitor;
 
+use std::cmp;
 use std::collections::HashMap;
 use std::mem;
 use std::path::PathBuf;
@@ -430,8 +431,10 @@ impl<'a, 'b> MacroExpander<'a, 'b> {
                 let item_toks = stream_for_item(&item, self.cx.parse_sess);
 
                 let span = Span { ctxt: self.cx.backtrace(), ..attr.span };
+                let item_span = span_with_attrs(item.span(), item.attrs());
+                let input = Span { lo: cmp::min(attr.span.lo, item_span.lo), ..item_span };
                 let tok_result = mac.expand(self.cx, attr.span, attr.tokens, item_toks);
-                self.parse_expansion(tok_result, kind, &attr.path, span)
+                self.parse_expansion(tok_result, kind, &attr.path, span, input)
             }
             SyntaxExtension::ProcMacroDerive(..) | SyntaxExtension::BuiltinDerive(..) => {
                 self.cx.span_err(attr.span, &format!("`{}` is a derive mode", attr.path));
@@ -542,7 +545,7 @@ impl<'a, 'b> MacroExpander<'a, 'b> {
                 });
 
                 let tok_result = expandfun.expand(self.cx, span, marked_tts);
-                Some(self.parse_expansion(tok_result, kind, path, span))
+                Some(self.parse_expansion(tok_result, kind, path, span, span))
             }
         };
 
@@ -610,7 +613,12 @@ impl<'a, 'b> MacroExpander<'a, 'b> {
         }
     }
 
-    fn parse_expansion(&mut self, toks: TokenStream, kind: ExpansionKind, path: &Path, span: Span)
+    fn parse_expansion(&mut self,
+                       toks: TokenStream,
+                       kind: ExpansionKind,
+                       path: &Path,
+                       span: Span,
+                       input: Span)
                        -> Expansion {
         let mut parser = self.cx.new_parser_from_tts(&toks.into_trees().collect::<Vec<_>>());
         let expansion = match parser.parse_expansion(kind, false) {
@@ -621,8 +629,7 @@ impl<'a, 'b> MacroExpander<'a, 'b> {
             }
         };
         parser.ensure_complete_parse(path, kind.name(), span);
-        // FIXME better span info
-        expansion.fold_with(&mut ChangeSpan { span: span })
+        expansion.fold_with(&mut ChangeSpan { span: span, input: input })
     }
 }
 
@@ -781,13 +788,30 @@ pub fn find_attr_invoc(attrs: &mut Vec<ast::Attribute>) -> Option<ast::Attribute
 // Therefore, we must use the pretty printer (yuck) to turn the AST node into a
 // string, which we then re-tokenise (double yuck), but first we have to patch
 // the pretty-printed string on to the end of the existing codemap (infinity-yuck).
-fn stream_for_item(item: &Annotatable, parse_sess: &ParseSess) -> TokenStream {
+//
+// The tokens which can still be found in the source code of the item get
+// their original spans back, the others get the span of the item.
+pub fn stream_for_item(item: &Annotatable, parse_sess: &ParseSess) -> TokenStream {
     let text = match *item {
         Annotatable::Item(ref i) => pprust::item_to_string(i),
         Annotatable::TraitItem(ref ti) => pprust::trait_item_to_string(ti),
         Annotatable::ImplItem(ref ii) => pprust::impl_item_to_string(ii),
     };
-    string_to_stream(text, parse_sess)
+    let span = span_with_attrs(item.span(), item.attrs());
+    let source = stream_for_span(parse_sess, span);
+    string_to_stream(text, parse_sess).with_spans_from(&source, span)
+}
+
+// The span of an item including its outer attributes.
+pub fn span_with_attrs(span: Span, attrs: &[ast::Attribute]) -> Span {
+    attrs.iter().fold(span, |span, attr| {
+        if attr.style == ast::AttrStyle::Outer && attr.span.lo < span.lo &&
+           attr.span.ctxt == span.ctxt {
+            Span { lo: attr.span.lo, ..span }
+        } else {
+            span
+        }
+    })
 }
 
 fn string_to_stream(text: String, parse_sess: &ParseSess) -> TokenStream {
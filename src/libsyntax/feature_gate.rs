@@ -352,6 +352,13 @@ declare_features! (
 
     // rustc internal
     (active, abi_thiscall, "1.19.0", None),
+
+    // Allows `#[test_timeout = "seconds"]` on tests
+    (active, test_timeout, "1.19.0", None),
+
+    // Used to identify the crate that contains the profiler runtime
+    // rustc internal
+    (active, profiler_runtime, "1.19.0", None),
 );
 
 declare_features! (
@@ -530,6 +537,10 @@ pub const BUILTIN_ATTRIBUTES: &'static [(&'static str, AttributeType, AttributeG
     ("derive", Normal, Ungated),
     ("should_panic", Normal, Ungated),
     ("ignore", Normal, Ungated),
+    ("test_timeout", Normal, Gated(Stability::Unstable,
+                                   "test_timeout",
+                                   "the `#[test_timeout]` attribute is an experimental feature",
+                                   cfg_fn!(test_timeout))),
     ("no_implicit_prelude", Normal, Ungated),
     ("reexport_test_harness_main", Normal, Ungated),
     ("link_args", Normal, Ungated),
@@ -691,6 +702,13 @@ pub const BUILTIN_ATTRIBUTES: &'static [(&'static str, AttributeType, AttributeG
                                               identify crates that contain the runtime of a \
                                               sanitizer and will never be stable",
                                              cfg_fn!(sanitizer_runtime))),
+    ("profiler_runtime", Whitelisted, Gated(Stability::Unstable,
+                                            "profiler_runtime",
+                                            "the `#[profiler_runtime]` attribute is used to \
+                                             identify the `profiler_builtins` crate which \
+                                             contains the profiler runtime and will never be \
+                                             stable",
+                                            cfg_fn!(profiler_runtime))),
 
     ("allow_internal_unstable", Normal, Gated(Stability::Unstable,
                                               "allow_internal_unstable",
@@ -218,6 +218,25 @@ pub fn filemap_to_stream(sess: &ParseSess, filemap: Rc<FileMap>) -> TokenStream
     panictry!(srdr.parse_all_token_trees())
 }
 
+/// Tokenizes the source code `span` points to, keeping the spans of the
+/// tokens. Gives an empty stream if `span` does not point to source code
+/// directly, like the spans created by macro expansion.
+pub fn stream_for_span(sess: &ParseSess, span: Span) -> TokenStream {
+    if span.ctxt != NO_EXPANSION || span.lo >= span.hi ||
+       sess.codemap().lookup_byte_offset(span.lo).fm.src.is_none() {
+        return TokenStream::empty();
+    }
+    let mut srdr = lexer::StringReader::retokenize(sess, span);
+    srdr.real_token();
+    match srdr.parse_all_token_trees() {
+        Ok(stream) => stream,
+        Err(mut e) => {
+            e.cancel();
+            TokenStream::empty()
+        }
+    }
+}
+
 /// Given stream and the `ParseSess`, produce a parser
 pub fn stream_to_parser(sess: &ParseSess, stream: TokenStream) -> Parser {
     Parser::new(sess, stream, None, true, false)
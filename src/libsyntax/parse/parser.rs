@@ -1493,7 +1493,8 @@ impl<'a> Parser<'a> {
                     s.print_bounds(" +", &bounds)?;
                     s.pclose()
                 });
-                err.span_suggestion(sum_span, "try adding parentheses:", sum_with_parens);
+                err.span_applicable_suggestion(sum_span, "try adding parentheses:",
+                                               sum_with_parens);
             }
             TyKind::Ptr(..) | TyKind::BareFn(..) => {
                 err.span_label(sum_span, "perhaps you forgot parentheses?");
@@ -2557,7 +2558,7 @@ impl<'a> Parser<'a> {
                             word(&mut s.s, ".")?;
                             word(&mut s.s, fstr.splitn(2, ".").last().unwrap())
                         });
-                        err.span_suggestion(
+                        err.span_applicable_suggestion(
                             lo.to(self.prev_span),
                             "try parenthesizing the first index",
                             sugg);
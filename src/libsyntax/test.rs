@@ -52,7 +52,8 @@ struct Test {
     path: Vec<Ident> ,
     bench: bool,
     ignore: bool,
-    should_panic: ShouldPanic
+    should_panic: ShouldPanic,
+    timeout: Option<u64>,
 }
 
 struct TestCtxt<'a> {
@@ -133,7 +134,8 @@ impl<'a> fold::Folder for TestHarnessGenerator<'a> {
                         path: self.cx.path.clone(),
                         bench: is_bench_fn(&self.cx, &i),
                         ignore: is_ignored(&i),
-                        should_panic: should_panic(&i, &self.cx)
+                        should_panic: should_panic(&i, &self.cx),
+                        timeout: test_timeout(&i, &self.cx),
                     };
                     self.cx.testfns.push(test);
                     self.tests.push(i.ident);
@@ -426,6 +428,22 @@ fn should_panic(i: &ast::Item, cx: &TestCtxt) -> ShouldPanic {
     }
 }
 
+fn test_timeout(i: &ast::Item, cx: &TestCtxt) -> Option<u64> {
+    i.attrs.iter().find(|attr| attr.check_name("test_timeout")).and_then(|attr| {
+        let secs = attr.value_str().and_then(|s| s.as_str().parse::<u64>().ok());
+        match secs {
+            Some(0) | None => {
+                cx.span_diagnostic.span_err(
+                    attr.span,
+                    "attribute must be of the form: `#[test_timeout = \"seconds\"]` with a \
+                     positive number of seconds");
+                None
+            }
+            secs => secs,
+        }
+    })
+}
+
 /*
 
 We're going to be building a module that looks more or less like:
@@ -669,13 +687,23 @@ fn mk_test_desc_and_fn_rec(cx: &TestCtxt, test: &Test) -> P<ast::Expr> {
         }
     };
 
+    let timeout_expr = match test.timeout {
+        Some(secs) => {
+            let lit = ast::LitKind::Int(secs as u128,
+                                        ast::LitIntType::Unsigned(ast::UintTy::U64));
+            ecx.expr_some(span, ecx.expr_lit(span, lit))
+        }
+        None => ecx.expr_none(span),
+    };
+
     // self::test::TestDesc { ... }
     let desc_expr = ecx.expr_struct(
         span,
         test_path("TestDesc"),
         vec![field("name", name_expr),
              field("ignore", ignore_expr),
-             field("should_panic", fail_expr)]);
+             field("should_panic", fail_expr),
+             field("timeout", timeout_expr)]);
 
 
     let mut visible_path = match cx.toplevel_reexport {
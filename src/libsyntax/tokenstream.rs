@@ -225,6 +225,52 @@ impl TokenStream {
         }
         true
     }
+
+    /// Gives the token trees of this stream the spans of the same trees in
+    /// `source`, which is expected to contain mostly the same tokens in the
+    /// same order, like the source code of a pretty-printed item. Trees are
+    /// matched in order within each delimited sequence; those which cannot be
+    /// found in `source` get the span of the enclosing delimited sequence, or
+    /// `span` at the top level.
+    pub fn with_spans_from(&self, source: &TokenStream, span: Span) -> TokenStream {
+        let source = source.trees().collect::<Vec<_>>();
+        let mut next = 0;
+        self.trees().map(|tree| {
+            let found = source[next..].iter().position(|source_tree| {
+                match (&tree, source_tree) {
+                    (&TokenTree::Token(_, ref tok), &TokenTree::Token(_, ref source_tok)) => {
+                        tok == source_tok
+                    }
+                    (&TokenTree::Delimited(_, ref delimed),
+                     &TokenTree::Delimited(_, ref source_delimed)) => {
+                        delimed.delim == source_delimed.delim
+                    }
+                    _ => false,
+                }
+            });
+            let source_tree = found.map(|i| {
+                next += i + 1;
+                &source[next - 1]
+            });
+            match tree {
+                TokenTree::Token(_, tok) => {
+                    TokenTree::Token(source_tree.map_or(span, |tree| tree.span()), tok)
+                }
+                TokenTree::Delimited(_, delimed) => {
+                    let (span, source_stream) = match source_tree {
+                        Some(&TokenTree::Delimited(span, ref source_delimed)) => {
+                            (span, source_delimed.stream())
+                        }
+                        _ => (span, TokenStream::empty()),
+                    };
+                    TokenTree::Delimited(span, Delimited {
+                        delim: delimed.delim,
+                        tts: delimed.stream().with_spans_from(&source_stream, span).into(),
+                    })
+                }
+            }
+        }).collect()
+    }
 }
 
 pub struct Cursor(CursorKind);
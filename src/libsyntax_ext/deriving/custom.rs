@@ -16,6 +16,7 @@ use syntax::ast::{self, ItemKind, Attribute, Mac};
 use syntax::attr::{mark_used, mark_known};
 use syntax::codemap::Span;
 use syntax::ext::base::*;
+use syntax::ext::expand::span_with_attrs;
 use syntax::fold::Folder;
 use syntax::visit::Visitor;
 
@@ -74,8 +75,9 @@ impl MultiItemModifier for ProcMacroDerive {
         // Mark attributes as known, and used.
         MarkAttrs(&self.attrs).visit_item(&item);
 
+        let input_span = span_with_attrs(item.span, &item.attrs);
         let input = __internal::new_token_stream(ecx.resolver.eliminate_crate_var(item.clone()));
-        let res = __internal::set_parse_sess(&ecx.parse_sess, || {
+        let res = __internal::set_parse_sess(&ecx.parse_sess, span, || {
             let inner = self.inner;
             panic::catch_unwind(panic::AssertUnwindSafe(|| inner(input)))
         });
@@ -97,7 +99,7 @@ impl MultiItemModifier for ProcMacroDerive {
             }
         };
 
-        let new_items = __internal::set_parse_sess(&ecx.parse_sess, || {
+        let new_items = __internal::set_parse_sess(&ecx.parse_sess, span, || {
             match __internal::token_stream_parse_items(stream) {
                 Ok(new_items) => new_items,
                 Err(_) => {
@@ -110,9 +112,10 @@ impl MultiItemModifier for ProcMacroDerive {
         });
 
         // Reassign spans of all expanded items to the input `item`
-        // for better errors here.
+        // for better errors here, unless they already point into it.
         new_items.into_iter().map(|item| {
-            Annotatable::Item(ChangeSpan { span: span }.fold_item(item).expect_one(""))
+            let mut change_span = ChangeSpan { span: span, input: input_span };
+            Annotatable::Item(change_span.fold_item(item).expect_one(""))
         }).collect()
     }
 }
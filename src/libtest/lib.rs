@@ -53,30 +53,38 @@ use self::TestEvent::*;
 use self::NamePadding::*;
 use self::OutputLocation::*;
 
-use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::panic::{self, catch_unwind, AssertUnwindSafe, PanicInfo};
 use std::any::Any;
 use std::cmp;
 use std::collections::BTreeMap;
 use std::env;
+use std::ffi::OsStr;
 use std::fmt;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::prelude::*;
 use std::io;
 use std::iter::repeat;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
 use std::sync::mpsc::{channel, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Instant, Duration};
+use std::time::{Instant, Duration, SystemTime, UNIX_EPOCH};
 
 const TEST_WARN_TIMEOUT_S: u64 = 60;
 
+// Set in the environment of the processes spawned by `--isolate`, telling
+// them to run the single test selected by the filter and giving the file
+// descriptor of the pipe on which to report what happened to it.
+const ISOLATED_TEST_ENV: &'static str = "__RUST_TEST_ISOLATED";
+
 // to be used by rustc to compile tests in libtest
 pub mod test {
     pub use {Bencher, TestName, TestResult, TestDesc, TestDescAndFn, TestOpts, TrFailed,
-             TrFailedMsg, TrIgnored, TrOk, Metric, MetricMap, StaticTestFn, StaticTestName,
-             DynTestName, DynTestFn, run_test, test_main, test_main_static, filter_tests,
-             parse_opts, StaticBenchFn, ShouldPanic, Options};
+             TrFailedMsg, TrTimedOut, TrIgnored, TrOk, Metric, MetricMap, StaticTestFn,
+             StaticTestName, DynTestName, DynTestFn, run_test, run_tests, test_main,
+             test_main_static, filter_tests, parse_opts, StaticBenchFn, ShouldPanic, Options,
+             OutputFormat, TestEvent, shuffle_tests};
 }
 
 pub mod stats;
@@ -189,6 +197,7 @@ impl fmt::Debug for TestFn {
 pub struct Bencher {
     mode: BenchMode,
     summary: Option<stats::Summary>,
+    samples: Vec<f64>,
     pub bytes: u64,
 }
 
@@ -212,6 +221,9 @@ pub struct TestDesc {
     pub name: TestName,
     pub ignore: bool,
     pub should_panic: ShouldPanic,
+    /// Number of seconds after which the test is considered hung and fails,
+    /// overriding the `--test-timeout` given on the command line.
+    pub timeout: Option<u64>,
 }
 
 #[derive(Clone)]
@@ -284,10 +296,12 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Options) {
         if let Err(e) = list_tests_console(&opts, tests) {
             panic!("io error when listing tests: {:?}", e);
         }
+    } else if let Some(report_fd) = env::var_os(ISOLATED_TEST_ENV) {
+        run_isolated_test(&opts, tests, &report_fd)
     } else {
         match run_tests_console(&opts, tests) {
             Ok(true) => {}
-            Ok(false) => std::process::exit(101),
+            Ok(false) => exit(101),
             Err(e) => panic!("io error when running tests: {:?}", e),
         }
     }
@@ -331,6 +345,17 @@ pub enum ColorConfig {
     NeverColor,
 }
 
+/// Format of the messages printed while running the tests.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable output, one line (or one character with `--quiet`)
+    /// per test.
+    Pretty,
+    /// One JSON object per line for every test event, meant to be consumed
+    /// by other programs.
+    Json,
+}
+
 #[derive(Debug)]
 pub struct TestOpts {
     pub list: bool,
@@ -340,10 +365,24 @@ pub struct TestOpts {
     pub run_tests: bool,
     pub bench_benchmarks: bool,
     pub logfile: Option<PathBuf>,
+    pub junit: Option<PathBuf>,
     pub nocapture: bool,
     pub color: ColorConfig,
     pub quiet: bool,
+    pub format: OutputFormat,
     pub test_threads: Option<usize>,
+    pub test_timeout: Option<u64>,
+    pub isolate: bool,
+    /// Seed used to shuffle the order in which the tests are run, if they
+    /// are shuffled at all.
+    pub shuffle_seed: Option<u64>,
+    /// File to save the benchmark results to, for later runs to compare against.
+    pub save_baseline: Option<PathBuf>,
+    /// File with saved benchmark results to compare this run against.
+    pub baseline: Option<PathBuf>,
+    /// Fail the run if a benchmark got slower than its baseline by more than
+    /// this percentage.
+    pub regression_threshold: Option<f64>,
     pub skip: Vec<String>,
     pub options: Options,
 }
@@ -359,10 +398,18 @@ impl TestOpts {
             run_tests: false,
             bench_benchmarks: false,
             logfile: None,
+            junit: None,
             nocapture: false,
             color: AutoColor,
             quiet: false,
+            format: OutputFormat::Pretty,
             test_threads: None,
+            test_timeout: None,
+            isolate: false,
+            shuffle_seed: None,
+            save_baseline: None,
+            baseline: None,
+            regression_threshold: None,
             skip: vec![],
             options: Options::new(),
         }
@@ -380,19 +427,41 @@ fn optgroups() -> Vec<getopts::OptGroup> {
       getopts::optflag("", "list", "List all tests and benchmarks"),
       getopts::optflag("h", "help", "Display this message (longer with --help)"),
       getopts::optopt("", "logfile", "Write logs to the specified file instead \
-                          of stdout", "PATH"),
+                          of stdout, in the format given by --format", "PATH"),
+      getopts::optopt("", "junit", "Write a JUnit XML report of the test run to the \
+                                    specified file", "PATH"),
       getopts::optflag("", "nocapture", "don't capture stdout/stderr of each \
                                          task, allow printing directly"),
       getopts::optopt("", "test-threads", "Number of threads used for running tests \
                                            in parallel", "n_threads"),
+      getopts::optopt("", "test-timeout", "Fail tests which have been running for longer \
+                                           than this many seconds", "SECONDS"),
+      getopts::optflag("", "isolate", "Run each test in a separate process, so that tests \
+                                       which crash or exit the process can't take down the \
+                                       whole test run (Unix only)"),
       getopts::optmulti("", "skip", "Skip tests whose names contain FILTER (this flag can \
                                      be used multiple times)","FILTER"),
+      getopts::optopt("", "save-baseline", "Save the results of the benchmarks under the \
+                                            given name", "NAME"),
+      getopts::optopt("", "baseline", "Compare the results of the benchmarks to the ones \
+                                       saved under the given name", "NAME"),
+      getopts::optopt("", "baseline-dir", "Directory in which the benchmark results are \
+                                           saved (default: .bench-baselines)", "PATH"),
+      getopts::optopt("", "regression-threshold", "Fail if a benchmark is significantly \
+                                                   slower than the --baseline, by more than \
+                                                   the given percentage", "PCT"),
+      getopts::optflag("", "shuffle", "Run tests in a random order"),
+      getopts::optopt("", "shuffle-seed", "Run tests in a random order, determined by the \
+                                           given seed (implies --shuffle)", "SEED"),
       getopts::optflag("q", "quiet", "Display one character per test instead of one line"),
       getopts::optflag("", "exact", "Exactly match filters rather than by substring"),
       getopts::optopt("", "color", "Configure coloring of output:
             auto   = colorize if stdout is a tty and tests are run on serially (default);
             always = always colorize output;
-            never  = never colorize output;", "auto|always|never")]
+            never  = never colorize output;", "auto|always|never"),
+      getopts::optopt("", "format", "Configure formatting of output:
+            pretty = print human-readable output (default);
+            json   = print one JSON object per test event;", "pretty|json")]
 }
 
 fn usage(binary: &str) {
@@ -423,7 +492,10 @@ Test Attributes:
     #[ignore]      - When applied to a function which is already attributed as a
                      test, then the test runner will ignore these tests during
                      normal test runs. Running with --ignored will run these
-                     tests."#,
+                     tests.
+    #[test_timeout = "N"] - This function (also labeled with #[test]) fails
+                     if it has not finished after N seconds, regardless of the
+                     --test-timeout flag."#,
              usage = getopts::usage(&message, &optgroups()));
 }
 
@@ -450,10 +522,16 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
     let quiet = matches.opt_present("quiet");
     let exact = matches.opt_present("exact");
     let list = matches.opt_present("list");
+    let isolate = matches.opt_present("isolate");
+    if isolate && !cfg!(unix) {
+        return Some(Err(format!("--isolate is only supported on Unix")));
+    }
 
     let logfile = matches.opt_str("logfile");
     let logfile = logfile.map(|s| PathBuf::from(&s));
 
+    let junit = matches.opt_str("junit").map(|s| PathBuf::from(&s));
+
     let bench_benchmarks = matches.opt_present("bench");
     let run_tests = !bench_benchmarks || matches.opt_present("test");
 
@@ -479,6 +557,55 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
             None,
     };
 
+    let test_timeout = match matches.opt_str("test-timeout") {
+        Some(secs) =>
+            match secs.parse::<u64>() {
+                Ok(0) =>
+                    return Some(Err(format!("argument for --test-timeout must not be 0"))),
+                Ok(n) => Some(n),
+                Err(e) =>
+                    return Some(Err(format!("argument for --test-timeout must be a number > 0 \
+                                             (error: {})", e)))
+            },
+        None =>
+            None,
+    };
+
+    let shuffle_seed = match matches.opt_str("shuffle-seed") {
+        Some(seed) =>
+            match seed.parse::<u64>() {
+                Ok(seed) => Some(seed),
+                Err(e) =>
+                    return Some(Err(format!("argument for --shuffle-seed must be a number \
+                                             (error: {})", e)))
+            },
+        None if matches.opt_present("shuffle") => Some(random_shuffle_seed()),
+        None => None,
+    };
+
+    let baseline_dir = matches.opt_str("baseline-dir")
+                              .map(PathBuf::from)
+                              .unwrap_or_else(|| PathBuf::from(".bench-baselines"));
+    let baseline_path = |name: String| baseline_dir.join(format!("{}.bench", name));
+    let save_baseline = matches.opt_str("save-baseline").map(&baseline_path);
+    let baseline = matches.opt_str("baseline").map(&baseline_path);
+
+    let regression_threshold = match matches.opt_str("regression-threshold") {
+        Some(_) if baseline.is_none() =>
+            return Some(Err(format!("--regression-threshold requires --baseline"))),
+        Some(pct) =>
+            match pct.parse::<f64>() {
+                Ok(pct) if pct >= 0.0 => Some(pct),
+                Ok(pct) =>
+                    return Some(Err(format!("argument for --regression-threshold must not be \
+                                             negative (was {})", pct))),
+                Err(e) =>
+                    return Some(Err(format!("argument for --regression-threshold must be a \
+                                             number (error: {})", e)))
+            },
+        None => None,
+    };
+
     let color = match matches.opt_str("color").as_ref().map(|s| &**s) {
         Some("auto") | None => AutoColor,
         Some("always") => AlwaysColor,
@@ -491,6 +618,17 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
         }
     };
 
+    let format = match matches.opt_str("format").as_ref().map(|s| &**s) {
+        Some("pretty") | None => OutputFormat::Pretty,
+        Some("json") => OutputFormat::Json,
+
+        Some(v) => {
+            return Some(Err(format!("argument for --format must be pretty or json (was \
+                                     {})",
+                                    v)))
+        }
+    };
+
     let test_opts = TestOpts {
         list: list,
         filter: filter,
@@ -499,10 +637,18 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
         run_tests: run_tests,
         bench_benchmarks: bench_benchmarks,
         logfile: logfile,
+        junit: junit,
         nocapture: nocapture,
         color: color,
         quiet: quiet,
+        format: format,
         test_threads: test_threads,
+        test_timeout: test_timeout,
+        isolate: isolate,
+        shuffle_seed: shuffle_seed,
+        save_baseline: save_baseline,
+        baseline: baseline,
+        regression_threshold: regression_threshold,
         skip: matches.opt_strs("skip"),
         options: Options::new(),
     };
@@ -510,9 +656,41 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
     Some(Ok(test_opts))
 }
 
+fn random_shuffle_seed() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::new(0, 0));
+    let mut rng = SplitMix64(now.as_secs() ^ ((now.subsec_nanos() as u64) << 32));
+    rng.next_u64()
+}
+
+// A small, fast pseudo-random number generator, good enough to shuffle the
+// tests. Unlike a generator seeded by the OS, the sequence it produces only
+// depends on the seed, so that a shuffled order can be replayed.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Shuffles the tests with the Fisher-Yates algorithm. The same seed always
+/// produces the same order for the same (sorted) list of tests.
+pub fn shuffle_tests(seed: u64, tests: &mut [TestDescAndFn]) {
+    let mut rng = SplitMix64(seed);
+    for i in (1..tests.len()).rev() {
+        let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+        tests.swap(i, j);
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub struct BenchSamples {
     ns_iter_summ: stats::Summary,
+    samples: Vec<f64>,
     mb_s: usize,
 }
 
@@ -521,6 +699,7 @@ pub enum TestResult {
     TrOk,
     TrFailed,
     TrFailedMsg(String),
+    TrTimedOut,
     TrIgnored,
     TrMetrics(MetricMap),
     TrBench(BenchSamples),
@@ -533,11 +712,107 @@ enum OutputLocation<T> {
     Raw(T),
 }
 
+// A finished test, kept around until the end of the run so that it can be
+// written out as a JUnit `<testcase>` element.
+struct JunitTestCase {
+    desc: TestDesc,
+    result: TestResult,
+    stdout: Vec<u8>,
+    exec_time: Duration,
+}
+
+// Benchmark results of the current run to be saved with `--save-baseline`,
+// and the ones loaded with `--baseline` to compare them to.
+struct BenchBaselines {
+    old: Option<BTreeMap<String, Vec<f64>>>,
+    new: BTreeMap<String, Vec<f64>>,
+    save_to: Option<PathBuf>,
+    regression_threshold: Option<f64>,
+    regressions: Vec<(String, f64)>,
+}
+
+impl BenchBaselines {
+    fn new(opts: &TestOpts) -> io::Result<BenchBaselines> {
+        let old = match opts.baseline {
+            Some(ref path) => Some(read_bench_baseline(path)?),
+            None => None,
+        };
+        Ok(BenchBaselines {
+            old: old,
+            new: BTreeMap::new(),
+            save_to: opts.save_baseline.clone(),
+            regression_threshold: opts.regression_threshold,
+            regressions: Vec::new(),
+        })
+    }
+
+    fn none() -> BenchBaselines {
+        BenchBaselines {
+            old: None,
+            new: BTreeMap::new(),
+            save_to: None,
+            regression_threshold: None,
+            regressions: Vec::new(),
+        }
+    }
+}
+
+// Baselines are kept as text, with the escaped name of a benchmark and the
+// nanoseconds per iteration of each of its samples on every line.
+const BENCH_BASELINE_HEADER: &'static str = "# libtest benchmark baseline v1";
+
+fn read_bench_baseline(path: &Path) -> io::Result<BTreeMap<String, Vec<f64>>> {
+    let invalid = |msg: &str| {
+        io::Error::new(io::ErrorKind::InvalidData,
+                       format!("invalid benchmark baseline {}: {}", path.display(), msg))
+    };
+
+    let mut contents = String::new();
+    File::open(path).and_then(|mut f| f.read_to_string(&mut contents)).map_err(|e| {
+        io::Error::new(e.kind(),
+                       format!("failed to read benchmark baseline {}: {}", path.display(), e))
+    })?;
+
+    let mut lines = contents.lines();
+    if lines.next() != Some(BENCH_BASELINE_HEADER) {
+        return Err(invalid("unknown format"));
+    }
+    let mut benches = BTreeMap::new();
+    for line in lines {
+        let mut parts = line.splitn(2, '\t');
+        let name = unescape_line(parts.next().unwrap());
+        let samples = parts.next().ok_or_else(|| invalid(line))?;
+        let samples = samples.split(' ')
+                             .map(|s| s.parse::<f64>())
+                             .collect::<Result<Vec<_>, _>>()
+                             .map_err(|_| invalid(line))?;
+        benches.insert(name, samples);
+    }
+    Ok(benches)
+}
+
+fn write_bench_baseline(path: &Path, benches: &BTreeMap<String, Vec<f64>>) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut out = File::create(path)?;
+    writeln!(out, "{}", BENCH_BASELINE_HEADER)?;
+    for (name, samples) in benches {
+        let samples = samples.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        writeln!(out, "{}\t{}", escape_line(name), samples.join(" "))?;
+    }
+    Ok(())
+}
+
 struct ConsoleTestState<T> {
     log_out: Option<File>,
+    junit_out: Option<File>,
+    junit_cases: Vec<JunitTestCase>,
     out: OutputLocation<T>,
     use_color: bool,
     quiet: bool,
+    format: OutputFormat,
+    shuffle_seed: Option<u64>,
     total: usize,
     passed: usize,
     failed: usize,
@@ -548,6 +823,7 @@ struct ConsoleTestState<T> {
     failures: Vec<(TestDesc, Vec<u8>)>,
     not_failures: Vec<(TestDesc, Vec<u8>)>,
     max_name_len: usize, // number of columns to fill when aligning names
+    baselines: BenchBaselines,
     options: Options,
 }
 
@@ -557,6 +833,10 @@ impl<T: Write> ConsoleTestState<T> {
             Some(ref path) => Some(File::create(path)?),
             None => None,
         };
+        let junit_out = match opts.junit {
+            Some(ref path) => Some(File::create(path)?),
+            None => None,
+        };
         let out = match term::stdout() {
             None => Raw(io::stdout()),
             Some(t) => Pretty(t),
@@ -565,8 +845,12 @@ impl<T: Write> ConsoleTestState<T> {
         Ok(ConsoleTestState {
             out: out,
             log_out: log_out,
+            junit_out: junit_out,
+            junit_cases: Vec::new(),
             use_color: use_color(opts),
             quiet: opts.quiet,
+            format: opts.format,
+            shuffle_seed: opts.shuffle_seed,
             total: 0,
             passed: 0,
             failed: 0,
@@ -577,6 +861,7 @@ impl<T: Write> ConsoleTestState<T> {
             failures: Vec::new(),
             not_failures: Vec::new(),
             max_name_len: 0,
+            baselines: BenchBaselines::new(opts)?,
             options: opts.options,
         })
     }
@@ -589,6 +874,10 @@ impl<T: Write> ConsoleTestState<T> {
         self.write_short_result("FAILED", "F", term::color::RED)
     }
 
+    pub fn write_timed_out(&mut self) -> io::Result<()> {
+        self.write_short_result("TIMED OUT", "T", term::color::RED)
+    }
+
     pub fn write_ignored(&mut self) -> io::Result<()> {
         self.write_short_result("ignored", "i", term::color::YELLOW)
     }
@@ -646,16 +935,37 @@ impl<T: Write> ConsoleTestState<T> {
 
     pub fn write_run_start(&mut self, len: usize) -> io::Result<()> {
         self.total = len;
+        if self.format == OutputFormat::Json {
+            let seed = match self.shuffle_seed {
+                Some(seed) => format!(", \"shuffle_seed\": {}", seed),
+                None => String::new(),
+            };
+            return self.write_plain(&format!("{{ \"type\": \"suite\", \"event\": \"started\", \
+                                              \"test_count\": {}{} }}\n",
+                                             len,
+                                             seed));
+        }
         let noun = if len != 1 {
             "tests"
         } else {
             "test"
         };
-        self.write_plain(&format!("\nrunning {} {}\n", len, noun))
+        self.write_plain(&format!("\nrunning {} {}\n", len, noun))?;
+        if let Some(seed) = self.shuffle_seed {
+            self.write_plain(&format!("shuffling tests with seed {} (run again with \
+                                       `--shuffle-seed {}` to reproduce this order)\n",
+                                      seed,
+                                      seed))?;
+        }
+        Ok(())
     }
 
     pub fn write_test_start(&mut self, test: &TestDesc, align: NamePadding) -> io::Result<()> {
-        if self.quiet && align != PadOnRight {
+        if self.format == OutputFormat::Json {
+            self.write_plain(&format!("{{ \"type\": \"test\", \"event\": \"started\", \
+                                       \"name\": \"{}\" }}\n",
+                                      escape_json(test.name.as_slice())))
+        } else if self.quiet && align != PadOnRight {
             Ok(())
         } else {
             let name = test.padded_name(self.max_name_len, align);
@@ -663,10 +973,15 @@ impl<T: Write> ConsoleTestState<T> {
         }
     }
 
-    pub fn write_result(&mut self, result: &TestResult) -> io::Result<()> {
+    pub fn write_result(&mut self, test: &TestDesc, result: &TestResult, stdout: &[u8])
+                        -> io::Result<()> {
+        if self.format == OutputFormat::Json {
+            return self.write_json_result(test, result, stdout);
+        }
         match *result {
             TrOk => self.write_ok(),
             TrFailed | TrFailedMsg(_) => self.write_failed(),
+            TrTimedOut => self.write_timed_out(),
             TrIgnored => self.write_ignored(),
             TrMetrics(ref mm) => {
                 self.write_metric()?;
@@ -679,7 +994,79 @@ impl<T: Write> ConsoleTestState<T> {
         }
     }
 
+    fn write_json_result(&mut self, test: &TestDesc, result: &TestResult, stdout: &[u8])
+                         -> io::Result<()> {
+        let line = json_result(test, result, stdout);
+        self.write_plain(&line)?;
+        self.write_plain("\n")
+    }
+
+    pub fn write_bench_comparison(&mut self, test: &TestDesc, bs: &BenchSamples)
+                                  -> io::Result<()> {
+        if bs.samples.is_empty() {
+            // `iter` was never called, there is nothing to compare
+            return Ok(());
+        }
+        let name = test.name.as_slice();
+        if self.baselines.save_to.is_some() {
+            self.baselines.new.insert(name.to_owned(), bs.samples.clone());
+        }
+
+        let (change, lower, upper) = match self.baselines.old {
+            Some(ref old) => match old.get(name) {
+                Some(old) if !old.is_empty() => {
+                    // 95% confidence interval
+                    stats::relative_change_interval(old, &bs.samples, 1.96)
+                }
+                _ => return Ok(()),
+            },
+            None => return Ok(()),
+        };
+        if !change.is_finite() || !lower.is_finite() || !upper.is_finite() {
+            return Ok(());
+        }
+
+        let verdict = if lower > 0.0 {
+            "regressed"
+        } else if upper < 0.0 {
+            "improved"
+        } else {
+            "unchanged"
+        };
+        if let Some(threshold) = self.baselines.regression_threshold {
+            if lower > 0.0 && change > threshold {
+                self.baselines.regressions.push((name.to_owned(), change));
+            }
+        }
+
+        if self.format == OutputFormat::Json {
+            return self.write_plain(&format!("{{ \"type\": \"bench_change\", \"name\": \"{}\", \
+                                              \"change_pct\": {}, \"lower_pct\": {}, \
+                                              \"upper_pct\": {}, \"verdict\": \"{}\" }}\n",
+                                             escape_json(name),
+                                             change,
+                                             lower,
+                                             upper,
+                                             verdict));
+        }
+        self.write_plain(&format!("     change: {:+.2}% (95% CI: {:+.2}% .. {:+.2}%), ",
+                                  change,
+                                  lower,
+                                  upper))?;
+        match verdict {
+            "regressed" => self.write_pretty(verdict, term::color::RED)?,
+            "improved" => self.write_pretty(verdict, term::color::GREEN)?,
+            _ => self.write_plain("no significant change")?,
+        }
+        self.write_plain("\n")
+    }
+
     pub fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        if self.format == OutputFormat::Json {
+            return self.write_plain(&format!("{{ \"type\": \"test\", \"event\": \"timeout\", \
+                                              \"name\": \"{}\" }}\n",
+                                             escape_json(desc.name.as_slice())));
+        }
         self.write_plain(&format!("test {} has been running for over {} seconds\n",
                                   desc.name,
                                   TEST_WARN_TIMEOUT_S))
@@ -693,13 +1080,19 @@ impl<T: Write> ConsoleTestState<T> {
         }
     }
 
-    pub fn write_log_result(&mut self, test: &TestDesc, result: &TestResult) -> io::Result<()> {
+    pub fn write_log_result(&mut self, test: &TestDesc, result: &TestResult, stdout: &[u8])
+                            -> io::Result<()> {
+        if self.format == OutputFormat::Json {
+            let line = json_result(test, result, stdout);
+            return self.write_log(format!("{}\n", line));
+        }
         self.write_log(
             format!("{} {}\n",
                     match *result {
                         TrOk => "ok".to_owned(),
                         TrFailed => "failed".to_owned(),
                         TrFailedMsg(ref msg) => format!("failed: {}", msg),
+                        TrTimedOut => "timed out".to_owned(),
                         TrIgnored => "ignored".to_owned(),
                         TrMetrics(ref mm) => mm.fmt_metrics(),
                         TrBench(ref bs) => fmt_bench_samples(bs),
@@ -759,16 +1152,150 @@ impl<T: Write> ConsoleTestState<T> {
         Ok(())
     }
 
+    pub fn record_junit_result(&mut self,
+                               test: &TestDesc,
+                               result: &TestResult,
+                               stdout: &[u8],
+                               exec_time: Duration) {
+        if self.junit_out.is_some() {
+            self.junit_cases.push(JunitTestCase {
+                desc: test.clone(),
+                result: result.clone(),
+                stdout: stdout.to_vec(),
+                exec_time: exec_time,
+            });
+        }
+    }
+
+    pub fn write_junit_report(&mut self) -> io::Result<()> {
+        let mut out = match self.junit_out.take() {
+            Some(out) => out,
+            None => return Ok(()),
+        };
+        let suite_name = env::current_exe().ok()
+                                           .and_then(|p| p.file_stem()
+                                                          .map(|s| s.to_string_lossy()
+                                                                    .into_owned()))
+                                           .unwrap_or_else(|| "test".to_owned());
+        self.write_junit_xml(&mut out, &suite_name)?;
+        out.flush()
+    }
+
+    fn write_junit_xml<W: Write>(&self, out: &mut W, suite_name: &str) -> io::Result<()> {
+        let total_time = self.junit_cases.iter()
+                                         .fold(Duration::new(0, 0), |acc, c| acc + c.exec_time);
+
+        write!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n")?;
+        write!(out, "<testsuites>\n")?;
+        write!(out,
+               "<testsuite name=\"{}\" errors=\"0\" failures=\"{}\" skipped=\"{}\" \
+                tests=\"{}\" time=\"{}\">\n",
+               escape_xml(suite_name),
+               self.failed,
+               self.ignored,
+               self.junit_cases.len(),
+               fmt_junit_time(total_time))?;
+        for case in &self.junit_cases {
+            let full_name = case.desc.name.as_slice();
+            let (class_name, name) = match full_name.rfind("::") {
+                Some(i) => (format!("{}::{}", suite_name, &full_name[..i]), &full_name[i + 2..]),
+                None => (suite_name.to_owned(), full_name),
+            };
+            write!(out,
+                   "<testcase classname=\"{}\" name=\"{}\" time=\"{}\"",
+                   escape_xml(&class_name),
+                   escape_xml(name),
+                   fmt_junit_time(case.exec_time))?;
+
+            let mut stdout = String::from_utf8_lossy(&case.stdout).into_owned();
+            match case.result {
+                TrOk | TrMetrics(_) if stdout.is_empty() => {
+                    write!(out, "/>\n")?;
+                    continue;
+                }
+                TrOk | TrMetrics(_) => write!(out, ">\n")?,
+                TrIgnored => write!(out, ">\n<skipped/>\n")?,
+                TrFailed => {
+                    write!(out, ">\n<failure type=\"panic\" message=\"test failed\"/>\n")?
+                }
+                TrFailedMsg(ref msg) => {
+                    write!(out,
+                           ">\n<failure type=\"panic\" message=\"{}\"/>\n",
+                           escape_xml(msg))?
+                }
+                TrTimedOut => {
+                    write!(out, ">\n<failure type=\"timeout\" message=\"test timed out\"/>\n")?
+                }
+                TrBench(ref bs) => {
+                    if !stdout.is_empty() {
+                        stdout.push_str("\n");
+                    }
+                    stdout.push_str(&fmt_bench_samples(bs));
+                    write!(out, ">\n")?
+                }
+            }
+            if !stdout.is_empty() {
+                write!(out, "<system-out>{}</system-out>\n", escape_xml(&stdout))?;
+            }
+            write!(out, "</testcase>\n")?;
+        }
+        write!(out, "</testsuite>\n")?;
+        write!(out, "</testsuites>\n")
+    }
+
+    pub fn write_regressions(&mut self) -> io::Result<()> {
+        let threshold = self.baselines.regression_threshold.unwrap_or(0.0);
+        self.write_plain(&format!("\nbenchmarks slower than the baseline by more than {}%:\n",
+                                  threshold))?;
+        let mut regressions = String::new();
+        for &(ref name, change) in &self.baselines.regressions {
+            regressions.push_str(&format!("    {} ({:+.2}%)\n", name, change));
+        }
+        self.write_plain(&regressions)
+    }
+
     pub fn write_run_finish(&mut self) -> io::Result<bool> {
         assert!(self.passed + self.failed + self.ignored + self.measured == self.total);
 
+        self.write_junit_report()?;
+        // A run without benchmarks, e.g. one whose filter matched none of
+        // them, leaves the saved baseline alone.
+        if let Some(ref path) = self.baselines.save_to {
+            if !self.baselines.new.is_empty() {
+                write_bench_baseline(path, &self.baselines.new)?;
+            }
+        }
+        let success = self.failed == 0 && self.baselines.regressions.is_empty();
+
+        if self.format == OutputFormat::Json {
+            let regressions = match self.baselines.regression_threshold {
+                Some(_) => format!(", \"bench_regressions\": {}",
+                                   self.baselines.regressions.len()),
+                None => String::new(),
+            };
+            let s = format!("{{ \"type\": \"suite\", \"event\": \"{}\", \"passed\": {}, \
+                             \"failed\": {}, \"ignored\": {}, \"measured\": {}, \
+                             \"filtered_out\": {}{} }}\n",
+                            if success { "ok" } else { "failed" },
+                            self.passed,
+                            self.failed,
+                            self.ignored,
+                            self.measured,
+                            self.filtered_out,
+                            regressions);
+            self.write_plain(&s)?;
+            return Ok(success);
+        }
+
         if self.options.display_output {
             self.write_outputs()?;
         }
-        let success = self.failed == 0;
-        if !success {
+        if self.failed != 0 {
             self.write_failures()?;
         }
+        if !self.baselines.regressions.is_empty() {
+            self.write_regressions()?;
+        }
 
         self.write_plain("\ntest result: ")?;
         if success {
@@ -788,6 +1315,139 @@ impl<T: Write> ConsoleTestState<T> {
     }
 }
 
+// The JSON event describing the result of a test
+fn json_result(test: &TestDesc, result: &TestResult, stdout: &[u8]) -> String {
+    let name = escape_json(test.name.as_slice());
+    match *result {
+        TrOk => json_test_event(&name, "ok", None, stdout),
+        TrFailed => json_test_event(&name, "failed", None, stdout),
+        TrFailedMsg(ref msg) => json_test_event(&name, "failed", Some(&msg[..]), stdout),
+        TrTimedOut => json_test_event(&name, "timed_out", None, stdout),
+        TrIgnored => json_test_event(&name, "ignored", None, stdout),
+        TrMetrics(ref mm) => {
+            let MetricMap(ref mm) = *mm;
+            let metrics = mm.iter()
+                            .map(|(k, v)| format!("\"{}\": {{ \"value\": {}, \
+                                                   \"noise\": {} }}",
+                                                  escape_json(k),
+                                                  json_f64(v.value),
+                                                  json_f64(v.noise)))
+                            .collect::<Vec<_>>();
+            format!("{{ \"type\": \"metric\", \"name\": \"{}\", \
+                     \"metrics\": {{ {} }} }}",
+                    name,
+                    metrics.join(", "))
+        }
+        TrBench(ref bs) => {
+            let median = bs.ns_iter_summ.median as usize;
+            let deviation = (bs.ns_iter_summ.max - bs.ns_iter_summ.min) as usize;
+            format!("{{ \"type\": \"bench\", \"name\": \"{}\", \"median\": {}, \
+                     \"deviation\": {}, \"mib_per_second\": {} }}",
+                    name,
+                    median,
+                    deviation,
+                    bs.mb_s)
+        }
+    }
+}
+
+// Format a float as a JSON number, or as `null` for NaN and the infinities,
+// which JSON can't represent
+fn json_f64(v: f64) -> String {
+    if v.is_finite() {
+        v.to_string()
+    } else {
+        "null".to_owned()
+    }
+}
+
+fn json_test_event(name: &str, event: &str, message: Option<&str>, stdout: &[u8]) -> String {
+    let mut line = format!("{{ \"type\": \"test\", \"event\": \"{}\", \"name\": \"{}\"",
+                           event,
+                           name);
+    if let Some(message) = message {
+        line.push_str(&format!(", \"message\": \"{}\"", escape_json(message)));
+    }
+    if !stdout.is_empty() {
+        line.push_str(&format!(", \"stdout\": \"{}\"",
+                               escape_json(&String::from_utf8_lossy(stdout))));
+    }
+    line.push_str(" }");
+    line
+}
+
+// Escape a string so that it can be used as XML text or attribute value
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' | '\r' | '\t' => escaped.push(c),
+            // Other control characters are not allowed in XML 1.0 at all
+            c if (c as u32) < 0x20 => escaped.push('\u{fffd}'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Format a duration as (fractional) seconds, as expected by JUnit consumers
+fn fmt_junit_time(dur: Duration) -> String {
+    format!("{}.{:03}", dur.as_secs(), dur.subsec_nanos() / 1_000_000)
+}
+
+// Escape a string so that it can be embedded in a JSON string literal
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Escape the separators of the line-based formats used for benchmark
+// baselines and isolated test reports
+fn escape_line(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape_line(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match (c, if c == '\\' { chars.next() } else { None }) {
+            ('\\', Some('n')) => unescaped.push('\n'),
+            ('\\', Some('r')) => unescaped.push('\r'),
+            ('\\', Some('t')) => unescaped.push('\t'),
+            ('\\', Some(c)) => unescaped.push(c),
+            (c, _) => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
 // Format a number with thousands separators
 fn fmt_thousands_sep(mut n: usize, sep: char) -> String {
     use std::fmt::Write;
@@ -875,15 +1535,20 @@ pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Res
 // A simple console test runner
 pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Result<bool> {
 
-    fn callback<T: Write>(event: &TestEvent, st: &mut ConsoleTestState<T>) -> io::Result<()> {
+    fn callback<T: Write>(event: &TestEvent,
+                          exec_time: Option<Duration>,
+                          st: &mut ConsoleTestState<T>)
+                          -> io::Result<()> {
         match (*event).clone() {
             TeFiltered(ref filtered_tests) => st.write_run_start(filtered_tests.len()),
             TeFilteredOut(filtered_out) => Ok(st.filtered_out = filtered_out),
             TeWait(ref test, padding) => st.write_test_start(test, padding),
             TeTimeout(ref test) => st.write_timeout(test),
             TeResult(test, result, stdout) => {
-                st.write_log_result(&test, &result)?;
-                st.write_result(&result)?;
+                let exec_time = exec_time.unwrap_or(Duration::new(0, 0));
+                st.record_junit_result(&test, &result, &stdout, exec_time);
+                st.write_log_result(&test, &result, &stdout)?;
+                st.write_result(&test, &result, &stdout)?;
                 match result {
                     TrOk => {
                         st.passed += 1;
@@ -903,6 +1568,7 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
                         st.metrics.insert_metric(test.name.as_slice(),
                                                  bs.ns_iter_summ.median,
                                                  bs.ns_iter_summ.max - bs.ns_iter_summ.min);
+                        st.write_bench_comparison(&test, &bs)?;
                         st.measured += 1
                     }
                     TrFailed => {
@@ -917,6 +1583,12 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
                         );
                         st.failures.push((test, stdout));
                     }
+                    TrTimedOut => {
+                        st.failed += 1;
+                        let mut stdout = stdout;
+                        stdout.extend_from_slice(b"note: test timed out");
+                        st.failures.push((test, stdout));
+                    }
                 }
                 Ok(())
             }
@@ -934,42 +1606,57 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
         let n = t.desc.name.as_slice();
         st.max_name_len = n.len();
     }
-    run_tests(opts, tests, |x| callback(&x, &mut st))?;
+    run_tests_timed(opts, tests, |x, exec_time| callback(&x, exec_time, &mut st))?;
     return st.write_run_finish();
 }
 
-#[test]
-fn should_sort_failures_before_printing_them() {
-    let test_a = TestDesc {
-        name: StaticTestName("a"),
-        ignore: false,
-        should_panic: ShouldPanic::No,
-    };
-
-    let test_b = TestDesc {
-        name: StaticTestName("b"),
-        ignore: false,
-        should_panic: ShouldPanic::No,
-    };
-
-    let mut st = ConsoleTestState {
+// A `ConsoleTestState` writing to memory, for the tests of the output
+#[cfg(test)]
+fn test_console_state(format: OutputFormat) -> ConsoleTestState<Vec<u8>> {
+    ConsoleTestState {
         log_out: None,
+        junit_out: None,
+        junit_cases: Vec::new(),
         out: Raw(Vec::new()),
         use_color: false,
         quiet: false,
+        format: format,
+        shuffle_seed: None,
         total: 0,
         passed: 0,
         failed: 0,
         ignored: 0,
         filtered_out: 0,
         measured: 0,
-        max_name_len: 10,
+        max_name_len: 0,
         metrics: MetricMap::new(),
-        failures: vec![(test_b, Vec::new()), (test_a, Vec::new())],
+        failures: Vec::new(),
         options: Options::new(),
+        baselines: BenchBaselines::none(),
         not_failures: Vec::new(),
+    }
+}
+
+#[test]
+fn should_sort_failures_before_printing_them() {
+    let test_a = TestDesc {
+        name: StaticTestName("a"),
+        ignore: false,
+        should_panic: ShouldPanic::No,
+        timeout: None,
+    };
+
+    let test_b = TestDesc {
+        name: StaticTestName("b"),
+        ignore: false,
+        should_panic: ShouldPanic::No,
+        timeout: None,
     };
 
+    let mut st = test_console_state(OutputFormat::Pretty);
+    st.max_name_len = 10;
+    st.failures = vec![(test_b, Vec::new()), (test_a, Vec::new())];
+
     st.write_failures().unwrap();
     let s = match st.out {
         Raw(ref m) => String::from_utf8_lossy(&m[..]),
@@ -981,6 +1668,129 @@ fn should_sort_failures_before_printing_them() {
     assert!(apos < bpos);
 }
 
+#[test]
+fn json_format_escapes_names_and_output() {
+    let test = TestDesc {
+        name: StaticTestName("a \"quoted\" name"),
+        ignore: false,
+        should_panic: ShouldPanic::No,
+        timeout: None,
+    };
+
+    let mut st = test_console_state(OutputFormat::Json);
+    st.quiet = true;
+
+    st.write_test_start(&test, PadNone).unwrap();
+    st.write_result(&test, &TrFailedMsg("boom".to_string()), b"line 1\nline 2").unwrap();
+    let s = match st.out {
+        Raw(ref m) => String::from_utf8_lossy(&m[..]).into_owned(),
+        Pretty(_) => unreachable!(),
+    };
+
+    let lines = s.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0],
+               r#"{ "type": "test", "event": "started", "name": "a \"quoted\" name" }"#);
+    assert_eq!(lines[1],
+               concat!(r#"{ "type": "test", "event": "failed", "name": "a \"quoted\" name", "#,
+                       r#""message": "boom", "stdout": "line 1\nline 2" }"#));
+}
+
+#[test]
+fn json_format_writes_non_finite_metrics_as_null() {
+    let test = TestDesc {
+        name: StaticTestName("m"),
+        ignore: false,
+        should_panic: ShouldPanic::No,
+        timeout: None,
+    };
+    let mut mm = MetricMap::new();
+    mm.insert_metric("nan", ::std::f64::NAN, 1.5);
+    mm.insert_metric("inf", ::std::f64::INFINITY, ::std::f64::NEG_INFINITY);
+
+    assert_eq!(json_result(&test, &TrMetrics(mm), b""),
+               concat!(r#"{ "type": "metric", "name": "m", "metrics": { "#,
+                       r#""inf": { "value": null, "noise": null }, "#,
+                       r#""nan": { "value": null, "noise": 1.5 } } }"#));
+}
+
+#[test]
+fn junit_report_describes_each_test() {
+    let desc = |name| {
+        TestDesc {
+            name: StaticTestName(name),
+            ignore: false,
+            should_panic: ShouldPanic::No,
+            timeout: None,
+        }
+    };
+
+    let mut st = test_console_state(OutputFormat::Pretty);
+    st.total = 3;
+    st.passed = 1;
+    st.failed = 1;
+    st.ignored = 1;
+    st.junit_cases.push(JunitTestCase {
+        desc: desc("tests::passes"),
+        result: TrOk,
+        stdout: Vec::new(),
+        exec_time: Duration::from_millis(1500),
+    });
+    st.junit_cases.push(JunitTestCase {
+        desc: desc("tests::fails"),
+        result: TrFailedMsg("expected <a> & <b>".to_string()),
+        stdout: b"some output".to_vec(),
+        exec_time: Duration::from_millis(20),
+    });
+    st.junit_cases.push(JunitTestCase {
+        desc: desc("skipped"),
+        result: TrIgnored,
+        stdout: Vec::new(),
+        exec_time: Duration::new(0, 0),
+    });
+
+    let mut out = Vec::new();
+    st.write_junit_xml(&mut out, "suite").unwrap();
+    let s = String::from_utf8(out).unwrap();
+
+    assert!(s.contains(concat!(r#"<testsuite name="suite" errors="0" failures="1" "#,
+                               r#"skipped="1" tests="3" time="1.520">"#)));
+    assert!(s.contains(r#"<testcase classname="suite::tests" name="passes" time="1.500"/>"#));
+    assert!(s.contains(r#"<failure type="panic" message="expected &lt;a&gt; &amp; &lt;b&gt;"/>"#));
+    assert!(s.contains("<system-out>some output</system-out>"));
+    assert!(s.contains(r#"<testcase classname="suite" name="skipped" time="0.000">"#));
+    assert!(s.contains("<skipped/>"));
+}
+
+#[test]
+fn bench_baselines_round_trip() {
+    let path = env::temp_dir().join(format!("libtest-baseline-{}.bench", random_shuffle_seed()));
+    let mut benches = BTreeMap::new();
+    benches.insert("a::bench".to_owned(), vec![1.5, 2.0, 1234567.25]);
+    benches.insert("b".to_owned(), vec![0.1]);
+    benches.insert("c\twith\nseparators\\n".to_owned(), vec![3.0]);
+
+    write_bench_baseline(&path, &benches).unwrap();
+    let read = read_bench_baseline(&path);
+    let _ = fs::remove_file(&path);
+    assert_eq!(read.unwrap(), benches);
+}
+
+#[test]
+fn bench_baseline_is_kept_without_benchmarks() {
+    let path = env::temp_dir().join(format!("libtest-baseline-{}.bench", random_shuffle_seed()));
+    let mut benches = BTreeMap::new();
+    benches.insert("a::bench".to_owned(), vec![1.5]);
+    write_bench_baseline(&path, &benches).unwrap();
+
+    let mut st = test_console_state(OutputFormat::Pretty);
+    st.baselines.save_to = Some(path.clone());
+    st.write_run_finish().unwrap();
+    let read = read_bench_baseline(&path);
+    let _ = fs::remove_file(&path);
+    assert_eq!(read.unwrap(), benches);
+}
+
 fn use_color(opts: &TestOpts) -> bool {
     match opts.color {
         AutoColor => !opts.nocapture && stdout_isatty(),
@@ -1030,6 +1840,15 @@ pub type MonitorMsg = (TestDesc, TestResult, Vec<u8>);
 
 pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F) -> io::Result<()>
     where F: FnMut(TestEvent) -> io::Result<()>
+{
+    run_tests_timed(opts, tests, |event, _| callback(event))
+}
+
+// Like `run_tests`, but also passes how long the test ran for along with each
+// `TeResult`.
+fn run_tests_timed<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
+                      -> io::Result<()>
+    where F: FnMut(TestEvent, Option<Duration>) -> io::Result<()>
 {
     use std::collections::HashMap;
     use std::sync::mpsc::RecvTimeoutError;
@@ -1040,15 +1859,18 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
     if !opts.bench_benchmarks {
         filtered_tests = convert_benchmarks_to_tests(filtered_tests);
     }
+    if let Some(seed) = opts.shuffle_seed {
+        shuffle_tests(seed, &mut filtered_tests);
+    }
 
     let filtered_out = tests_len - filtered_tests.len();
-    callback(TeFilteredOut(filtered_out))?;
+    callback(TeFilteredOut(filtered_out), None)?;
 
     let filtered_descs = filtered_tests.iter()
                                        .map(|t| t.desc.clone())
                                        .collect();
 
-    callback(TeFiltered(filtered_descs))?;
+    callback(TeFiltered(filtered_descs), None)?;
 
     let (filtered_tests, filtered_benchs_and_metrics): (Vec<_>, _) =
         filtered_tests.into_iter().partition(|e| {
@@ -1070,6 +1892,9 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
     let (tx, rx) = channel::<MonitorMsg>();
 
     let mut running_tests: HashMap<TestDesc, Instant> = HashMap::new();
+    let mut deadlines: HashMap<TestDesc, Instant> = HashMap::new();
+    let mut start_times: HashMap<TestDesc, Instant> = HashMap::new();
+    let mut timed_out_tests: Vec<TestDesc> = Vec::new();
 
     fn get_timed_out_tests(running_tests: &mut HashMap<TestDesc, Instant>) -> Vec<TestDesc> {
         let now = Instant::now();
@@ -1099,37 +1924,64 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
                 // We are doing one test at a time so we can print the name
                 // of the test before we run it. Useful for debugging tests
                 // that hang forever.
-                callback(TeWait(test.desc.clone(), test.testfn.padding()))?;
+                callback(TeWait(test.desc.clone(), test.testfn.padding()), None)?;
             }
-            let timeout = Instant::now() + Duration::from_secs(TEST_WARN_TIMEOUT_S);
+            let now = Instant::now();
+            let timeout = now + Duration::from_secs(TEST_WARN_TIMEOUT_S);
             running_tests.insert(test.desc.clone(), timeout);
+            // Isolated tests are killed by the thread waiting on them
+            // instead, which also gets to report their output.
+            if !opts.isolate {
+                if let Some(secs) = test.desc.timeout.or(opts.test_timeout) {
+                    deadlines.insert(test.desc.clone(), now + Duration::from_secs(secs));
+                }
+            }
+            start_times.insert(test.desc.clone(), now);
             run_test(opts, !opts.run_tests, test, tx.clone());
             pending += 1;
         }
 
         let mut res;
         loop {
-            if let Some(timeout) = calc_timeout(&running_tests) {
-                res = rx.recv_timeout(timeout);
-                for test in get_timed_out_tests(&mut running_tests) {
-                    callback(TeTimeout(test))?;
-                }
-                if res != Err(RecvTimeoutError::Timeout) {
-                    break;
-                }
+            if let Some(desc) = timed_out_tests.pop() {
+                // We give up on waiting for a hung test. The thread running
+                // it can't be stopped, so it is left behind and anything it
+                // reports later on is dropped below.
+                res = Ok((desc, TrTimedOut, Vec::new()));
             } else {
-                res = rx.recv().map_err(|_| RecvTimeoutError::Disconnected);
-                break;
+                let timeout = match (calc_timeout(&running_tests), calc_timeout(&deadlines)) {
+                    (Some(warn), Some(deadline)) => Some(cmp::min(warn, deadline)),
+                    (warn, deadline) => warn.or(deadline),
+                };
+                if let Some(timeout) = timeout {
+                    res = rx.recv_timeout(timeout);
+                    for test in get_timed_out_tests(&mut running_tests) {
+                        callback(TeTimeout(test), None)?;
+                    }
+                    timed_out_tests.extend(get_timed_out_tests(&mut deadlines));
+                } else {
+                    res = rx.recv().map_err(|_| RecvTimeoutError::Disconnected);
+                }
+            }
+            match res {
+                Err(RecvTimeoutError::Timeout) => {}
+                // The result of a test which has already been reported
+                Ok((ref desc, _, _)) if !start_times.contains_key(desc) => {}
+                _ => break,
             }
         }
 
         let (desc, result, stdout) = res.unwrap();
         running_tests.remove(&desc);
+        deadlines.remove(&desc);
+        let exec_time = start_times.remove(&desc)
+                                   .map(|start| start.elapsed())
+                                   .unwrap_or(Duration::new(0, 0));
 
         if concurrency != 1 {
-            callback(TeWait(desc.clone(), PadNone))?;
+            callback(TeWait(desc.clone(), PadNone), None)?;
         }
-        callback(TeResult(desc, result, stdout))?;
+        callback(TeResult(desc, result, stdout), Some(exec_time))?;
         pending -= 1;
     }
 
@@ -1137,10 +1989,11 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
         // All benchmarks run at the end, in serial.
         // (this includes metric fns)
         for b in filtered_benchs_and_metrics {
-            callback(TeWait(b.desc.clone(), b.testfn.padding()))?;
+            callback(TeWait(b.desc.clone(), b.testfn.padding()), None)?;
+            let start = Instant::now();
             run_test(opts, false, b, tx.clone());
             let (test, result, stdout) = rx.recv().unwrap();
-            callback(TeResult(test, result, stdout))?;
+            callback(TeResult(test, result, stdout), Some(start.elapsed()))?;
         }
     }
     Ok(())
@@ -1396,9 +2249,11 @@ pub fn run_test(opts: &TestOpts,
                 io::set_panic(panicio);
             };
 
-            let test_result = calc_result(&desc, result);
+            let test_result = calc_result(&desc, result.as_ref().map(|_| ()).map_err(|e| &**e));
             let stdout = data.lock().unwrap().to_vec();
-            monitor_ch.send((desc.clone(), test_result, stdout)).unwrap();
+            // The receiving end is gone if the test timed out and the
+            // harness has already finished.
+            let _ = monitor_ch.send((desc.clone(), test_result, stdout));
         };
 
 
@@ -1417,6 +2272,14 @@ pub fn run_test(opts: &TestOpts,
         }
     }
 
+    let is_test = match testfn {
+        StaticTestFn(..) | DynTestFn(..) => true,
+        _ => false,
+    };
+    if opts.isolate && is_test {
+        return run_test_in_subprocess(opts, desc, monitor_ch);
+    }
+
     match testfn {
         DynBenchFn(bencher) => {
             let bs = ::bench::benchmark(|harness| bencher.run(harness));
@@ -1458,16 +2321,269 @@ fn __rust_begin_short_backtrace<F: FnOnce()>(f: F) {
     f()
 }
 
-fn calc_result(desc: &TestDesc, task_result: Result<(), Box<Any + Send>>) -> TestResult {
+// Runs a test in a new instance of the current executable, which is told to
+// only run that test through the filter and `ISOLATED_TEST_ENV`.
+fn run_test_in_subprocess(opts: &TestOpts, desc: TestDesc, monitor_ch: Sender<MonitorMsg>) {
+    let timeout = desc.timeout.or(opts.test_timeout).map(Duration::from_secs);
+    let nocapture = opts.nocapture;
+    let run_ignored = opts.run_ignored;
+    let thread_name = match desc.name {
+        DynTestName(ref name) => name.clone(),
+        StaticTestName(name) => name.to_owned(),
+    };
+
+    let runtest = move || {
+        let (result, output) = match spawn_isolated_test(&desc, run_ignored, nocapture, timeout) {
+            Ok(res) => res,
+            Err(e) => {
+                (TrFailedMsg(format!("failed to spawn the test process: {}", e)), Vec::new())
+            }
+        };
+        let _ = monitor_ch.send((desc, result, output));
+    };
+
+    thread::Builder::new().name(thread_name).spawn(runtest).unwrap();
+}
+
+fn spawn_isolated_test(desc: &TestDesc,
+                       run_ignored: bool,
+                       nocapture: bool,
+                       timeout: Option<Duration>)
+                       -> io::Result<(TestResult, Vec<u8>)> {
+    let mut cmd = Command::new(env::current_exe()?);
+    cmd.arg("--exact");
+    if run_ignored {
+        cmd.arg("--ignored");
+    }
+    cmd.arg("--").arg(desc.name.as_slice());
+    if nocapture {
+        cmd.stdin(Stdio::null()).stdout(Stdio::inherit()).stderr(Stdio::inherit());
+    } else {
+        cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+    let (report, report_writer) = isolated_test_pipe(&mut cmd)?;
+    let child = cmd.spawn();
+    // Our copy of the write end has to be closed once the child has its own,
+    // or reading the report would never see the end of it.
+    drop(report_writer);
+    let mut child = child?;
+
+    // Drain all pipes while the test runs so that it never blocks on a
+    // full pipe buffer.
+    fn read_all<R: Read + Send + 'static>(r: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut r) = r {
+                let _ = r.read_to_end(&mut buf);
+            }
+            buf
+        })
+    }
+    let stdout = read_all(child.stdout.take());
+    let stderr = read_all(child.stderr.take());
+    let report = read_all(Some(report));
+
+    let status = match timeout {
+        None => Some(child.wait()?),
+        Some(timeout) => {
+            let deadline = Instant::now() + timeout;
+            let mut status = child.try_wait()?;
+            while status.is_none() && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(10));
+                status = child.try_wait()?;
+            }
+            if status.is_none() {
+                // The test is hung, so kill it. It may have exited right
+                // after we last checked, in which case killing it fails.
+                let _ = child.kill();
+                child.wait()?;
+            }
+            status
+        }
+    };
+
+    let mut output = stdout.join().unwrap_or(Vec::new());
+    output.extend(stderr.join().unwrap_or(Vec::new()));
+    let report = report.join().unwrap_or(Vec::new());
+
+    let status = match status {
+        Some(status) => status,
+        None => return Ok((TrTimedOut, output)),
+    };
+
+    // The test either returned or unwound, or, if the process died before it
+    // could say so, was aborted by the last panic it reported, if any.
+    let mut outcome = None;
+    let mut last_panic = None;
+    for line in report.split(|&b| b == b'\n') {
+        let line = String::from_utf8_lossy(line);
+        let (event, msg) = match line.find(' ') {
+            Some(i) => (&line[..i], Some(unescape_line(&line[i + 1..]))),
+            None => (&line[..], None),
+        };
+        match event {
+            "returned" => outcome = Some(Ok(())),
+            "unwound" => outcome = Some(Err(msg)),
+            "panicked" => last_panic = Some(msg),
+            _ => {}
+        }
+    }
+    if outcome.is_none() && killed_by_signal(&status) {
+        outcome = Some(Err(last_panic.unwrap_or(None)));
+    }
+
+    let result = match outcome {
+        Some(Ok(())) => calc_result(desc, Ok(())),
+        Some(Err(msg)) => {
+            let payload: Box<Any + Send> = match msg {
+                Some(msg) => Box::new(msg),
+                None => Box::new(()),
+            };
+            calc_result(desc, Err(&*payload))
+        }
+        None => TrFailedMsg(format!("test process exited unexpectedly ({})", status)),
+    };
+    Ok((result, output))
+}
+
+// Creates the pipe on which an isolated test reports what happened to it,
+// and arranges for `cmd` to pass the write end on to the test process.
+#[cfg(unix)]
+fn isolated_test_pipe(cmd: &mut Command) -> io::Result<(File, File)> {
+    use std::os::unix::io::FromRawFd;
+    use std::os::unix::process::CommandExt;
+
+    let mut fds = [0; 2];
+    unsafe {
+        if libc::pipe(fds.as_mut_ptr()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let pipe = (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1]));
+        // Neither end may leak into processes spawned for other tests.
+        for &fd in &fds {
+            if libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        let writer = fds[1];
+        cmd.env(ISOLATED_TEST_ENV, writer.to_string());
+        cmd.before_exec(move || {
+            if libc::fcntl(writer, libc::F_SETFD, 0) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+        Ok(pipe)
+    }
+}
+
+#[cfg(not(unix))]
+fn isolated_test_pipe(_cmd: &mut Command) -> io::Result<(File, File)> {
+    Err(io::Error::new(io::ErrorKind::Other, "--isolate is only supported on Unix"))
+}
+
+// Opens the write end of the pipe created by `isolated_test_pipe`, making sure
+// that it is not inherited by the processes which the test spawns.
+#[cfg(unix)]
+fn open_isolated_test_report(fd: &OsStr) -> File {
+    use std::os::unix::io::FromRawFd;
+
+    let fd = fd.to_str()
+               .and_then(|fd| fd.parse::<libc::c_int>().ok())
+               .expect("invalid isolated test report descriptor");
+    unsafe {
+        libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+        File::from_raw_fd(fd)
+    }
+}
+
+#[cfg(not(unix))]
+fn open_isolated_test_report(_fd: &OsStr) -> File {
+    panic!("--isolate is only supported on Unix")
+}
+
+#[cfg(unix)]
+fn killed_by_signal(status: &process::ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal().is_some()
+}
+
+#[cfg(not(unix))]
+fn killed_by_signal(_status: &process::ExitStatus) -> bool {
+    false
+}
+
+// Records an event of an isolated test on its report pipe, one per line.
+fn report_isolated_event(report: &Mutex<File>, event: &str, payload: Option<&(Any + Send)>) {
+    let mut line = event.to_string();
+    if let Some(msg) = payload.and_then(panic_message) {
+        line.push(' ');
+        line.push_str(&escape_line(msg));
+    }
+    line.push('\n');
+    let mut report = report.lock().unwrap_or_else(|e| e.into_inner());
+    let _ = report.write_all(line.as_bytes());
+}
+
+// Entry point of a process spawned by `run_test_in_subprocess`.
+fn run_isolated_test(opts: &TestOpts, tests: Vec<TestDescAndFn>, report_fd: &OsStr) -> ! {
+    let report = Arc::new(Mutex::new(open_isolated_test_report(report_fd)));
+    // Processes spawned by the test are not part of the isolated run.
+    env::remove_var(ISOLATED_TEST_ENV);
+
+    let mut tests = filter_tests(opts, tests);
+    if !opts.bench_benchmarks {
+        tests = convert_benchmarks_to_tests(tests);
+    }
+    if tests.len() != 1 {
+        panic!("expected to find exactly one test to run in isolation, found {}", tests.len());
+    }
+
+    let TestDescAndFn { testfn, .. } = tests.pop().unwrap();
+    let testfn: Box<FnBox<()>> = match testfn {
+        StaticTestFn(f) => Box::new(move |()| __rust_begin_short_backtrace(f)),
+        DynTestFn(f) => Box::new(move |()| __rust_begin_short_backtrace(|| f.call_box(()))),
+        _ => panic!("only tests can be run in isolation"),
+    };
+
+    // Every panic is reported as it happens, because the process is gone
+    // before anything unwinds if it aborts on panic. Whether a panic failed
+    // the test is still only decided by how the test itself finished.
+    let default_hook = panic::take_hook();
+    let panic_report = report.clone();
+    panic::set_hook(Box::new(move |info: &PanicInfo| {
+        default_hook(info);
+        report_isolated_event(&panic_report, "panicked", Some(info.payload()));
+    }));
+
+    match catch_unwind(AssertUnwindSafe(|| testfn.call_box(()))) {
+        Ok(()) => report_isolated_event(&report, "returned", None),
+        Err(e) => report_isolated_event(&report, "unwound", Some(&*e)),
+    }
+    exit(0)
+}
+
+// Exits the process like returning from `main` does, so that the `atexit`
+// handler of the profiler runtime writes out the counters of a test binary
+// built with `-Z instrument-coverage`. `process::exit` skips that handler on
+// Windows.
+fn exit(code: i32) -> ! {
+    let _ = io::stdout().flush();
+    unsafe { libc::exit(code) }
+}
+
+fn panic_message(payload: &(Any + Send)) -> Option<&str> {
+    payload.downcast_ref::<String>()
+           .map(|e| &**e)
+           .or_else(|| payload.downcast_ref::<&'static str>().map(|e| *e))
+}
+
+fn calc_result(desc: &TestDesc, task_result: Result<(), &(Any + Send)>) -> TestResult {
     match (&desc.should_panic, task_result) {
         (&ShouldPanic::No, Ok(())) |
         (&ShouldPanic::Yes, Err(_)) => TrOk,
-        (&ShouldPanic::YesWithMessage(msg), Err(ref err)) =>
-            if err.downcast_ref::<String>()
-                  .map(|e| &**e)
-                  .or_else(|| err.downcast_ref::<&'static str>().map(|e| *e))
-                  .map(|e| e.contains(msg))
-                  .unwrap_or(false) {
+        (&ShouldPanic::YesWithMessage(msg), Err(err)) =>
+            if panic_message(err).map(|e| e.contains(msg)).unwrap_or(false) {
                 TrOk
             } else {
                 TrFailedMsg(format!("Panic did not include expected string '{}'", msg))
@@ -1546,7 +2662,9 @@ impl Bencher {
             return;
         }
 
-        self.summary = Some(iter(&mut inner));
+        let (summary, samples) = iter_samples(&mut inner);
+        self.summary = Some(summary);
+        self.samples = samples;
     }
 
     pub fn bench<F>(&mut self, mut f: F) -> Option<stats::Summary>
@@ -1574,6 +2692,14 @@ fn ns_iter_inner<T, F>(inner: &mut F, k: u64) -> u64
 
 pub fn iter<T, F>(inner: &mut F) -> stats::Summary
     where F: FnMut() -> T
+{
+    iter_samples(inner).0
+}
+
+// Like `iter`, but also returns the samples (in ns/iter) the summary was
+// computed from.
+fn iter_samples<T, F>(inner: &mut F) -> (stats::Summary, Vec<f64>)
+    where F: FnMut() -> T
 {
     // Initial bench run to get ballpark figure.
     let ns_single = ns_iter_inner(inner, 1);
@@ -1616,13 +2742,13 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
         // stable median.
         if loop_run > Duration::from_millis(100) && summ.median_abs_dev_pct < 1.0 &&
            summ.median - summ5.median < summ5.median_abs_dev {
-            return summ5;
+            return (summ5, samples.to_vec());
         }
 
         total_run = total_run + loop_run;
         // Longest we ever run for is 3s.
         if total_run > Duration::from_secs(3) {
-            return summ5;
+            return (summ5, samples.to_vec());
         }
 
         // If we overflow here just return the results so far. We check a
@@ -1632,7 +2758,7 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
         n = match n.checked_mul(10) {
             Some(_) => n * 2,
             None => {
-                return summ5;
+                return (summ5, samples.to_vec());
             }
         };
     }
@@ -1649,6 +2775,7 @@ pub mod bench {
         let mut bs = Bencher {
             mode: BenchMode::Auto,
             summary: None,
+            samples: Vec::new(),
             bytes: 0,
         };
 
@@ -1659,6 +2786,7 @@ pub mod bench {
 
                 BenchSamples {
                     ns_iter_summ: ns_iter_summ,
+                    samples: bs.samples,
                     mb_s: mb_s as usize,
                 }
             }
@@ -1668,6 +2796,7 @@ pub mod bench {
                 let samples: &mut [f64] = &mut [0.0_f64; 1];
                 BenchSamples {
                     ns_iter_summ: stats::Summary::new(samples),
+                    samples: Vec::new(),
                     mb_s: 0,
                 }
             }
@@ -1680,6 +2809,7 @@ pub mod bench {
         let mut bs = Bencher {
             mode: BenchMode::Single,
             summary: None,
+            samples: Vec::new(),
             bytes: 0,
         };
         bs.bench(f);
@@ -1688,9 +2818,10 @@ pub mod bench {
 
 #[cfg(test)]
 mod tests {
-    use test::{TrFailed, TrFailedMsg, TrIgnored, TrOk, filter_tests, parse_opts, TestDesc,
-               TestDescAndFn, TestOpts, run_test, MetricMap, StaticTestName, DynTestName,
-               DynTestFn, ShouldPanic};
+    use test::{TrFailed, TrFailedMsg, TrTimedOut, TrIgnored, TrOk, filter_tests, parse_opts,
+               TestDesc, TestDescAndFn, TestOpts, run_test, run_tests, MetricMap,
+               StaticTestName, DynTestName, DynTestFn, ShouldPanic, OutputFormat, TestEvent,
+               shuffle_tests};
     use std::sync::mpsc::channel;
     use bench;
     use Bencher;
@@ -1705,6 +2836,7 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: true,
                 should_panic: ShouldPanic::No,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
@@ -1722,6 +2854,7 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: true,
                 should_panic: ShouldPanic::No,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
@@ -1741,6 +2874,7 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: false,
                 should_panic: ShouldPanic::Yes,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
@@ -1760,6 +2894,7 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: false,
                 should_panic: ShouldPanic::YesWithMessage("error message"),
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
@@ -1781,6 +2916,7 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: false,
                 should_panic: ShouldPanic::YesWithMessage(expected),
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
@@ -1798,6 +2934,7 @@ mod tests {
                 name: StaticTestName("whatever"),
                 ignore: false,
                 should_panic: ShouldPanic::Yes,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
@@ -1807,6 +2944,52 @@ mod tests {
         assert!(res == TrFailed);
     }
 
+    #[test]
+    fn hung_tests_time_out() {
+        let (hang_tx, hang_rx) = channel::<()>();
+        let tests = vec![TestDescAndFn {
+                             desc: TestDesc {
+                                 name: StaticTestName("hangs"),
+                                 ignore: false,
+                                 should_panic: ShouldPanic::No,
+                                 timeout: Some(1),
+                             },
+                             testfn: DynTestFn(Box::new(move |()| {
+                                 let _ = hang_rx.recv();
+                             })),
+                         },
+                         TestDescAndFn {
+                             desc: TestDesc {
+                                 name: StaticTestName("passes"),
+                                 ignore: false,
+                                 should_panic: ShouldPanic::No,
+                                 timeout: None,
+                             },
+                             testfn: DynTestFn(Box::new(move |()| {})),
+                         }];
+        let opts = TestOpts {
+            run_tests: true,
+            test_timeout: Some(60),
+            test_threads: Some(2),
+            ..TestOpts::new()
+        };
+
+        let mut results = Vec::new();
+        run_tests(&opts, tests, |event| {
+            if let TestEvent::TeResult(desc, result, _) = event {
+                results.push((desc.name.to_string(), result));
+            }
+            Ok(())
+        }).unwrap();
+        // Let the hung test finish now that the harness gave up on it
+        drop(hang_tx);
+
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(results.len(), 2);
+        assert!(results[0] == ("hangs".to_string(), TrTimedOut));
+        assert!(results[1] == ("passes".to_string(), TrOk));
+    }
+
     #[test]
     fn parse_ignored_flag() {
         let args = vec!["progname".to_string(), "filter".to_string(), "--ignored".to_string()];
@@ -1817,6 +3000,19 @@ mod tests {
         assert!((opts.run_ignored));
     }
 
+    #[test]
+    fn parse_format_flag() {
+        let args = vec!["progname".to_string(), "--format".to_string(), "json".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_format_flag"),
+        };
+        assert_eq!(opts.format, OutputFormat::Json);
+
+        let args = vec!["progname".to_string(), "--format".to_string(), "xml".to_string()];
+        assert!(parse_opts(&args).unwrap().is_err());
+    }
+
     #[test]
     pub fn filter_for_ignored_option() {
         // When we run ignored tests the test filter should filter out all the
@@ -1831,6 +3027,7 @@ mod tests {
                                  name: StaticTestName("1"),
                                  ignore: true,
                                  should_panic: ShouldPanic::No,
+                                 timeout: None,
                              },
                              testfn: DynTestFn(Box::new(move |()| {})),
                          },
@@ -1839,6 +3036,7 @@ mod tests {
                                  name: StaticTestName("2"),
                                  ignore: false,
                                  should_panic: ShouldPanic::No,
+                                 timeout: None,
                              },
                              testfn: DynTestFn(Box::new(move |()| {})),
                          }];
@@ -1862,6 +3060,7 @@ mod tests {
                     name: StaticTestName(name),
                     ignore: false,
                     should_panic: ShouldPanic::No,
+                    timeout: None,
                 },
                 testfn: DynTestFn(Box::new(move |()| {}))
             })
@@ -1943,6 +3142,7 @@ mod tests {
                         name: DynTestName((*name).clone()),
                         ignore: false,
                         should_panic: ShouldPanic::No,
+                        timeout: None,
                     },
                     testfn: DynTestFn(Box::new(move |()| testfn())),
                 };
@@ -1967,6 +3167,62 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn shuffle_tests_is_reproducible() {
+        fn tests() -> Vec<TestDescAndFn> {
+            (0..20).map(|i| TestDescAndFn {
+                desc: TestDesc {
+                    name: DynTestName(format!("test{:02}", i)),
+                    ignore: false,
+                    should_panic: ShouldPanic::No,
+                    timeout: None,
+                },
+                testfn: DynTestFn(Box::new(move |()| {})),
+            }).collect()
+        }
+        fn names(tests: &[TestDescAndFn]) -> Vec<String> {
+            tests.iter().map(|t| t.desc.name.to_string()).collect()
+        }
+
+        let mut a = tests();
+        let mut b = tests();
+        let mut c = tests();
+        shuffle_tests(42, &mut a);
+        shuffle_tests(42, &mut b);
+        shuffle_tests(43, &mut c);
+        assert_eq!(names(&a), names(&b));
+        assert!(names(&a) != names(&c));
+        assert!(names(&a) != names(&tests()));
+
+        let mut sorted = names(&a);
+        sorted.sort();
+        assert_eq!(sorted, names(&tests()));
+    }
+
+    #[test]
+    fn parse_shuffle_flags() {
+        let args = vec!["progname".to_string(), "--shuffle-seed".to_string(), "7".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_shuffle_flags"),
+        };
+        assert_eq!(opts.shuffle_seed, Some(7));
+
+        let args = vec!["progname".to_string(), "--shuffle".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_shuffle_flags"),
+        };
+        assert!(opts.shuffle_seed.is_some());
+
+        let args = vec!["progname".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_shuffle_flags"),
+        };
+        assert_eq!(opts.shuffle_seed, None);
+    }
+
     #[test]
     pub fn test_metricmap_compare() {
         let mut m1 = MetricMap::new();
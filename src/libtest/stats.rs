@@ -327,12 +327,33 @@ pub fn winsorize(samples: &mut [f64], pct: f64) {
     }
 }
 
+/// Estimate of how much the mean of the `new` samples differs from the mean of the `old` ones,
+/// as a percent of the old mean, together with the lower and upper bounds of its confidence
+/// interval. `z` is the number of standard errors the interval spans on either side of the
+/// estimate, e.g. `1.96` for a 95% confidence level.
+///
+/// The standard error of the difference is computed without assuming that both sample sets have
+/// the same variance (Welch's approximation). The normal distribution is used in place of
+/// Student's t-distribution, which is accurate enough for the 50 samples taken of each
+/// benchmark.
+///
+/// See: https://en.wikipedia.org/wiki/Welch%27s_t-test
+pub fn relative_change_interval(old: &[f64], new: &[f64], z: f64) -> (f64, f64, f64) {
+    let old_mean = old.mean();
+    let diff = new.mean() - old_mean;
+    let std_err = (old.var() / old.len() as f64 + new.var() / new.len() as f64).sqrt();
+    let hundred = 100 as f64;
+    let pct = |x: f64| (x / old_mean) * hundred;
+    (pct(diff), pct(diff - z * std_err), pct(diff + z * std_err))
+}
+
 // Test vectors generated from R, using the script src/etc/stat-test-vectors.r.
 
 #[cfg(test)]
 mod tests {
     use stats::Stats;
     use stats::Summary;
+    use stats::relative_change_interval;
     use std::f64;
     use std::io::prelude::*;
     use std::io;
@@ -868,6 +889,21 @@ mod tests {
         check(val, summ);
     }
 
+    #[test]
+    fn test_relative_change_interval() {
+        let old = [98.0, 99.0, 100.0, 101.0, 102.0];
+        let same = [98.0, 99.0, 100.0, 101.0, 102.0];
+        let (change, lower, upper) = relative_change_interval(&old, &same, 1.96);
+        assert_approx_eq!(change, 0.0);
+        assert!(lower < 0.0 && upper > 0.0);
+
+        let slower = [108.0, 109.0, 110.0, 111.0, 112.0];
+        let (change, lower, upper) = relative_change_interval(&old, &slower, 1.96);
+        assert_approx_eq!(change, 10.0);
+        assert!(lower > 0.0 && lower < change);
+        assert!(upper > change);
+    }
+
     #[test]
     fn test_sum_f64s() {
         assert_eq!([0.5f64, 3.2321f64, 1.5678f64].sum(), 5.2999);
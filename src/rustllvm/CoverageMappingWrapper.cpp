@@ -0,0 +1,110 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#include "rustllvm.h"
+
+#if LLVM_VERSION_GE(4, 0)
+#include "llvm/ProfileData/Coverage/CoverageMapping.h"
+#include "llvm/ProfileData/Coverage/CoverageMappingWriter.h"
+#include "llvm/ProfileData/InstrProf.h"
+#endif
+
+using namespace llvm;
+
+// A region of source code and the counter of how often it ran. The counters
+// of `-Z instrument-coverage` are never combined into expressions, so this is
+// all of `coverage::CounterMappingRegion` that rustc needs.
+struct LLVMRustCounterMappingRegion {
+  unsigned CounterID;
+  unsigned FileID;
+  unsigned LineStart;
+  unsigned ColumnStart;
+  unsigned LineEnd;
+  unsigned ColumnEnd;
+};
+
+#if LLVM_VERSION_GE(4, 0)
+
+using namespace llvm::coverage;
+
+extern "C" void
+LLVMRustCoverageWriteFilenamesSectionToBuffer(const char *const Filenames[],
+                                              size_t FilenamesLen,
+                                              RustStringRef BufferOut) {
+  SmallVector<StringRef, 32> FilenameRefs;
+  for (size_t I = 0; I < FilenamesLen; I++) {
+    FilenameRefs.push_back(StringRef(Filenames[I]));
+  }
+  RawRustStringOstream OS(BufferOut);
+  CoverageFilenamesSectionWriter(FilenameRefs).write(OS);
+}
+
+extern "C" void LLVMRustCoverageWriteMappingToBuffer(
+    const unsigned *VirtualFileMappingIDs, unsigned NumVirtualFileMappingIDs,
+    const LLVMRustCounterMappingRegion *RustMappingRegions,
+    unsigned NumMappingRegions, RustStringRef BufferOut) {
+  SmallVector<CounterMappingRegion, 16> MappingRegions;
+  for (unsigned I = 0; I < NumMappingRegions; I++) {
+    const LLVMRustCounterMappingRegion &Region = RustMappingRegions[I];
+    MappingRegions.push_back(CounterMappingRegion::makeRegion(
+        Counter::getCounter(Region.CounterID), Region.FileID,
+        Region.LineStart, Region.ColumnStart, Region.LineEnd,
+        Region.ColumnEnd));
+  }
+  RawRustStringOstream OS(BufferOut);
+  CoverageMappingWriter Writer(
+      makeArrayRef(VirtualFileMappingIDs, NumVirtualFileMappingIDs),
+      ArrayRef<CounterExpression>(), MappingRegions);
+  Writer.write(OS);
+}
+
+extern "C" LLVMValueRef
+LLVMRustCoverageCreatePGOFuncNameVar(LLVMValueRef F, const char *FuncName) {
+  return wrap(createPGOFuncNameVar(*unwrap<Function>(F), StringRef(FuncName)));
+}
+
+extern "C" uint64_t LLVMRustCoverageComputeHash(const char *Name) {
+  return IndexedInstrProf::ComputeHash(StringRef(Name));
+}
+
+extern "C" uint32_t LLVMRustCoverageMappingVersion() {
+  return CovMapVersion::CurrentVersion;
+}
+
+#else
+
+extern "C" void
+LLVMRustCoverageWriteFilenamesSectionToBuffer(const char *const Filenames[],
+                                              size_t FilenamesLen,
+                                              RustStringRef BufferOut) {
+  report_fatal_error("coverage mapping not available");
+}
+
+extern "C" void LLVMRustCoverageWriteMappingToBuffer(
+    const unsigned *VirtualFileMappingIDs, unsigned NumVirtualFileMappingIDs,
+    const LLVMRustCounterMappingRegion *RustMappingRegions,
+    unsigned NumMappingRegions, RustStringRef BufferOut) {
+  report_fatal_error("coverage mapping not available");
+}
+
+extern "C" LLVMValueRef
+LLVMRustCoverageCreatePGOFuncNameVar(LLVMValueRef F, const char *FuncName) {
+  report_fatal_error("coverage mapping not available");
+}
+
+extern "C" uint64_t LLVMRustCoverageComputeHash(const char *Name) {
+  report_fatal_error("coverage mapping not available");
+}
+
+extern "C" uint32_t LLVMRustCoverageMappingVersion() {
+  report_fatal_error("coverage mapping not available");
+}
+
+#endif
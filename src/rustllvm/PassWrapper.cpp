@@ -23,7 +23,15 @@
 #include "llvm/Transforms/IPO/PassManagerBuilder.h"
 
 #if LLVM_VERSION_GE(4, 0)
+#include "llvm/IR/ModuleSummaryIndex.h"
+#include "llvm/LTO/LTO.h"
 #include "llvm/Transforms/IPO/AlwaysInliner.h"
+#include "llvm/Transforms/IPO/FunctionImport.h"
+#include "llvm/Transforms/Utils/FunctionImportUtils.h"
+#endif
+
+#if LLVM_VERSION_EQ(4, 0)
+#include "llvm/Object/ModuleSummaryIndexObjectFile.h"
 #endif
 
 #include "llvm-c/Transforms/PassManagerBuilder.h"
@@ -395,12 +403,30 @@ extern "C" void LLVMRustAddAnalysisPasses(LLVMTargetMachineRef TM,
 
 extern "C" void LLVMRustConfigurePassManagerBuilder(
     LLVMPassManagerBuilderRef PMBR, LLVMRustCodeGenOptLevel OptLevel,
-    bool MergeFunctions, bool SLPVectorize, bool LoopVectorize) {
+    bool MergeFunctions, bool SLPVectorize, bool LoopVectorize,
+    bool PrepareForThinLTO, const char *PGOGenPath, const char *PGOUsePath) {
   // Ignore mergefunc for now as enabling it causes crashes.
   // unwrap(PMBR)->MergeFunctions = MergeFunctions;
   unwrap(PMBR)->SLPVectorize = SLPVectorize;
   unwrap(PMBR)->OptLevel = fromRust(OptLevel);
   unwrap(PMBR)->LoopVectorize = LoopVectorize;
+
+#if LLVM_VERSION_GE(4, 0)
+  unwrap(PMBR)->PrepareForThinLTO = PrepareForThinLTO;
+
+  if (PGOGenPath) {
+    assert(!PGOUsePath);
+    unwrap(PMBR)->EnablePGOInstrGen = true;
+    unwrap(PMBR)->PGOInstrGen = PGOGenPath;
+  }
+  if (PGOUsePath) {
+    assert(!PGOGenPath);
+    unwrap(PMBR)->PGOInstrUse = PGOUsePath;
+  }
+#else
+  assert(!PrepareForThinLTO && "Should've caught earlier");
+  assert(!PGOGenPath && !PGOUsePath && "Should've caught earlier");
+#endif
 }
 
 // Unfortunately, the LLVM C API doesn't provide a way to set the `LibraryInfo`
@@ -613,3 +639,301 @@ extern "C" void LLVMRustSetModulePIELevel(LLVMModuleRef M) {
   unwrap(M)->setPIELevel(PIELevel::Level::Large);
 #endif
 }
+
+// ThinLTO, as driven by `back::lto`. Like LLVM's `ThinLTOCodeGenerator`, the
+// modules are first summarized one by one, then a global analysis over all the
+// summaries decides what each module imports and which symbols get
+// internalized, after which each module is imported into, optimized and
+// codegened on its own. Most of the code here is adapted from
+// `lib/LTO/ThinLTOCodeGenerator.cpp`, which doesn't expose these steps
+// separately.
+#if LLVM_VERSION_GE(4, 0)
+
+// The global analysis over all the modules taking part in ThinLTO. It is only
+// read once created, so it can be shared by the threads processing modules.
+struct LLVMRustThinLTOData {
+  // The summaries of all the modules, merged into one index.
+  ModuleSummaryIndex Index;
+
+  // The serialized modules, by identifier, to import from. The buffers are
+  // owned by the caller.
+  StringMap<MemoryBufferRef> ModuleMap;
+
+  // The symbols which must be kept around, such as exported ones.
+  DenseSet<GlobalValue::GUID> GUIDPreservedSymbols;
+
+  // What each module imports from the others and exports to them.
+  StringMap<FunctionImporter::ImportMapTy> ImportLists;
+  StringMap<FunctionImporter::ExportSetTy> ExportLists;
+
+  // The summaries of the global values each module defines.
+  StringMap<GVSummaryMapTy> ModuleToDefinedGVSummaries;
+};
+
+struct LLVMRustThinLTOModule {
+  const char *identifier;
+  const char *data;
+  size_t len;
+};
+
+extern "C" bool LLVMRustThinLTOAvailable() { return true; }
+
+// Writes `M` along with its summary, as needed by ThinLTO.
+extern "C" bool LLVMRustWriteThinBitcodeToFile(LLVMModuleRef M,
+                                               const char *Path) {
+  std::error_code EC;
+  raw_fd_ostream OS(Path, EC, sys::fs::F_None);
+  if (EC) {
+    LLVMRustSetLastError(EC.message().c_str());
+    return false;
+  }
+
+  legacy::PassManager PM;
+  PM.add(createWriteThinLTOBitcodePass(OS));
+  PM.run(*unwrap(M));
+  return true;
+}
+
+// Picks the copy of a symbol defined in several modules which the others
+// resolve to, the way a linker would.
+static const GlobalValueSummary *
+getFirstDefinitionForLinker(const GlobalValueSummaryList &GVSummaryList) {
+  auto StrongDefForLinker = llvm::find_if(
+      GVSummaryList, [](const std::unique_ptr<GlobalValueSummary> &Summary) {
+        auto Linkage = Summary->linkage();
+        return !GlobalValue::isAvailableExternallyLinkage(Linkage) &&
+               !GlobalValue::isWeakForLinker(Linkage);
+      });
+  if (StrongDefForLinker != GVSummaryList.end())
+    return StrongDefForLinker->get();
+
+  auto FirstDefForLinker = llvm::find_if(
+      GVSummaryList, [](const std::unique_ptr<GlobalValueSummary> &Summary) {
+        auto Linkage = Summary->linkage();
+        return !GlobalValue::isAvailableExternallyLinkage(Linkage);
+      });
+  if (FirstDefForLinker == GVSummaryList.end())
+    return nullptr;
+  return FirstDefForLinker->get();
+}
+
+extern "C" LLVMRustThinLTOData *
+LLVMRustCreateThinLTOData(LLVMRustThinLTOModule *Modules, unsigned NumModules,
+                          const char **PreservedSymbols,
+                          unsigned NumPreservedSymbols) {
+  auto Ret = llvm::make_unique<LLVMRustThinLTOData>();
+
+  // Merge the summaries of all the modules.
+  for (unsigned I = 0; I < NumModules; I++) {
+    StringRef Buffer(Modules[I].data, Modules[I].len);
+    MemoryBufferRef MemBuffer(Buffer, Modules[I].identifier);
+    Ret->ModuleMap[Modules[I].identifier] = MemBuffer;
+
+    Expected<std::unique_ptr<object::ModuleSummaryIndexObjectFile>> ObjOrErr =
+        object::ModuleSummaryIndexObjectFile::create(MemBuffer);
+    if (!ObjOrErr) {
+      LLVMRustSetLastError(toString(ObjOrErr.takeError()).c_str());
+      return nullptr;
+    }
+    Ret->Index.mergeFrom((*ObjOrErr)->takeIndex(), I);
+  }
+
+  Ret->Index.collectDefinedGVSummariesPerModule(
+      Ret->ModuleToDefinedGVSummaries);
+
+  for (unsigned I = 0; I < NumPreservedSymbols; I++)
+    Ret->GUIDPreservedSymbols.insert(
+        GlobalValue::getGUID(PreservedSymbols[I]));
+
+  // Symbols referenced from a module other than the one defining them can't
+  // be internalized either, whether or not the reference gets imported. This
+  // is what `ThinLTOCodeGenerator::crossReferenceSymbol` is told by linkers.
+  for (auto &I : Ret->Index) {
+    for (auto &Summary : I.second) {
+      const auto &Defined =
+          Ret->ModuleToDefinedGVSummaries[Summary->modulePath()];
+      auto CrossReference = [&](GlobalValue::GUID GUID) {
+        if (!Defined.count(GUID))
+          Ret->GUIDPreservedSymbols.insert(GUID);
+      };
+      for (auto &Ref : Summary->refs())
+        CrossReference(Ref.getGUID());
+      if (auto *FS = dyn_cast<FunctionSummary>(Summary.get()))
+        for (auto &Call : FS->calls())
+          CrossReference(Call.first.getGUID());
+    }
+  }
+
+  // Decide what each module imports, following the call graph.
+  ComputeCrossModuleImport(Ret->Index, Ret->ModuleToDefinedGVSummaries,
+                           Ret->ImportLists, Ret->ExportLists);
+
+  // Resolve the linkonce and weak symbols defined in several modules to a
+  // single prevailing copy.
+  DenseMap<GlobalValue::GUID, const GlobalValueSummary *> PrevailingCopy;
+  for (auto &I : Ret->Index) {
+    if (I.second.size() > 1)
+      PrevailingCopy[I.first] = getFirstDefinitionForLinker(I.second);
+  }
+  auto IsPrevailing = [&](GlobalValue::GUID GUID,
+                          const GlobalValueSummary *S) {
+    const auto &Prevailing = PrevailingCopy.find(GUID);
+    if (Prevailing == PrevailingCopy.end())
+      return true;
+    return Prevailing->second == S;
+  };
+  // The new linkages are recorded in the index, which is where
+  // `thinLTOResolveWeakForLinkerModule` reads them from.
+  auto RecordNewLinkage = [&](StringRef ModuleIdentifier,
+                              GlobalValue::GUID GUID,
+                              GlobalValue::LinkageTypes NewLinkage) {};
+  thinLTOResolveWeakForLinkerInIndex(Ret->Index, IsPrevailing,
+                                     RecordNewLinkage);
+
+  // Internalize everything which isn't exported to another module or
+  // preserved, and promote the internal symbols which are.
+  LLVMRustThinLTOData *Data = Ret.get();
+  auto IsExported = [Data](StringRef ModuleIdentifier,
+                           GlobalValue::GUID GUID) {
+    const auto &ExportList = Data->ExportLists.find(ModuleIdentifier);
+    return (ExportList != Data->ExportLists.end() &&
+            ExportList->second.count(GUID)) ||
+           Data->GUIDPreservedSymbols.count(GUID);
+  };
+  thinLTOInternalizeAndPromoteInIndex(Ret->Index, IsExported);
+
+  return Ret.release();
+}
+
+extern "C" void LLVMRustFreeThinLTOData(LLVMRustThinLTOData *Data) {
+  delete Data;
+}
+
+// The steps below apply the decisions of the global analysis to one module,
+// in the order `ThinLTOCodeGenerator` does.
+
+extern "C" bool LLVMRustPrepareThinLTORename(const LLVMRustThinLTOData *Data,
+                                             LLVMModuleRef M) {
+  if (renameModuleForThinLTO(*unwrap(M), Data->Index)) {
+    LLVMRustSetLastError("renameModuleForThinLTO failed");
+    return false;
+  }
+  return true;
+}
+
+extern "C" bool
+LLVMRustPrepareThinLTOResolveWeak(const LLVMRustThinLTOData *Data,
+                                  LLVMModuleRef M) {
+  Module &Mod = *unwrap(M);
+  const auto &DefinedGlobals =
+      Data->ModuleToDefinedGVSummaries.lookup(Mod.getModuleIdentifier());
+  thinLTOResolveWeakForLinkerModule(Mod, DefinedGlobals);
+  return true;
+}
+
+extern "C" bool
+LLVMRustPrepareThinLTOInternalize(const LLVMRustThinLTOData *Data,
+                                  LLVMModuleRef M) {
+  Module &Mod = *unwrap(M);
+  const auto &DefinedGlobals =
+      Data->ModuleToDefinedGVSummaries.lookup(Mod.getModuleIdentifier());
+  thinLTOInternalizeModule(Mod, DefinedGlobals);
+  return true;
+}
+
+extern "C" bool LLVMRustPrepareThinLTOImport(const LLVMRustThinLTOData *Data,
+                                             LLVMModuleRef M) {
+  Module &Mod = *unwrap(M);
+  const auto &ImportList =
+      Data->ImportLists.lookup(Mod.getModuleIdentifier());
+  auto Loader = [&](StringRef Identifier) {
+    const auto &Memory = Data->ModuleMap.lookup(Identifier);
+    return getLazyBitcodeModule(Memory, Mod.getContext(),
+                                /* ShouldLazyLoadMetadata = */ true);
+  };
+  FunctionImporter Importer(Data->Index, Loader);
+  Expected<bool> Result = Importer.importFunctions(Mod, ImportList);
+  if (!Result) {
+    LLVMRustSetLastError(toString(Result.takeError()).c_str());
+    return false;
+  }
+  return true;
+}
+
+extern "C" LLVMModuleRef
+LLVMRustParseBitcodeForThinLTO(LLVMContextRef C, const char *Data, size_t Len,
+                               const char *Identifier) {
+  MemoryBufferRef Buffer(StringRef(Data, Len), Identifier);
+  Expected<std::unique_ptr<Module>> SrcOrError =
+      parseBitcodeFile(Buffer, *unwrap(C));
+  if (!SrcOrError) {
+    LLVMRustSetLastError(toString(SrcOrError.takeError()).c_str());
+    return nullptr;
+  }
+  return wrap(std::move(*SrcOrError).release());
+}
+
+extern "C" void
+LLVMRustPassManagerBuilderPopulateThinLTOPassManager(
+    LLVMPassManagerBuilderRef PMBR, LLVMPassManagerRef PMR) {
+  unwrap(PMBR)->populateThinLTOPassManager(*unwrap(PMR));
+}
+
+#else
+
+struct LLVMRustThinLTOData {};
+struct LLVMRustThinLTOModule {};
+
+extern "C" bool LLVMRustThinLTOAvailable() { return false; }
+
+extern "C" bool LLVMRustWriteThinBitcodeToFile(LLVMModuleRef M,
+                                               const char *Path) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" LLVMRustThinLTOData *
+LLVMRustCreateThinLTOData(LLVMRustThinLTOModule *Modules, unsigned NumModules,
+                          const char **PreservedSymbols,
+                          unsigned NumPreservedSymbols) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" void LLVMRustFreeThinLTOData(LLVMRustThinLTOData *Data) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" bool LLVMRustPrepareThinLTORename(const LLVMRustThinLTOData *Data,
+                                             LLVMModuleRef M) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" bool
+LLVMRustPrepareThinLTOResolveWeak(const LLVMRustThinLTOData *Data,
+                                  LLVMModuleRef M) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" bool
+LLVMRustPrepareThinLTOInternalize(const LLVMRustThinLTOData *Data,
+                                  LLVMModuleRef M) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" bool LLVMRustPrepareThinLTOImport(const LLVMRustThinLTOData *Data,
+                                             LLVMModuleRef M) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" LLVMModuleRef
+LLVMRustParseBitcodeForThinLTO(LLVMContextRef C, const char *Data, size_t Len,
+                               const char *Identifier) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" void
+LLVMRustPassManagerBuilderPopulateThinLTOPassManager(
+    LLVMPassManagerBuilderRef PMBR, LLVMPassManagerRef PMR) {
+  report_fatal_error("ThinLTO not available");
+}
+
+#endif // LLVM_VERSION_GE(4, 0)
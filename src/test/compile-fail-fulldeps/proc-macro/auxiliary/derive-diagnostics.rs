@@ -0,0 +1,46 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// no-prefer-dynamic
+#![feature(proc_macro)]
+#![crate_type = "proc-macro"]
+
+extern crate proc_macro;
+
+use proc_macro::{TokenStream, TokenNode, Delimiter, Spacing};
+
+fn check_fields(stream: TokenStream) {
+    let trees: Vec<_> = stream.into_iter().collect();
+    for (i, tree) in trees.iter().enumerate() {
+        match tree.kind {
+            TokenNode::Group(Delimiter::None, ref stream) |
+            TokenNode::Group(Delimiter::Brace, ref stream) => check_fields(stream.clone()),
+            TokenNode::Op(':', Spacing::Alone) if i > 0 => {
+                if let TokenNode::Term(name) = trees[i - 1].kind {
+                    let span = trees[i - 1].span;
+                    if name.as_str().starts_with("bad") {
+                        span.error(format!("field `{}` is not allowed", name.as_str()))
+                            .help("rename the field")
+                            .emit();
+                    } else if name.as_str().starts_with("old") {
+                        span.warning(format!("field `{}` is deprecated", name.as_str())).emit();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[proc_macro_derive(CheckFields)]
+pub fn derive_check_fields(input: TokenStream) -> TokenStream {
+    check_fields(input);
+    TokenStream::empty()
+}
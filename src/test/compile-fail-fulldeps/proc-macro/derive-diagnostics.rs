@@ -0,0 +1,27 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:derive-diagnostics.rs
+
+#![feature(proc_macro)]
+
+#[macro_use]
+extern crate derive_diagnostics;
+
+#[derive(CheckFields)]
+struct A {
+    good: u8,
+    bad: u8, //~ ERROR field `bad` is not allowed
+    old_field: u8, //~ WARN field `old_field` is deprecated
+    /// Documented.
+    bad_too: u8, //~ ERROR field `bad_too` is not allowed
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: --test
+
+#![feature(test_timeout)]
+
+#[test]
+#[test_timeout = "soon"]
+//~^ ERROR attribute must be of the form: `#[test_timeout = "seconds"]`
+fn test1() {}
+
+#[test]
+#[test_timeout = "0"]
+//~^ ERROR attribute must be of the form: `#[test_timeout = "seconds"]`
+fn test2() {}
+
+#[test]
+#[test_timeout]
+//~^ ERROR attribute must be of the form: `#[test_timeout = "seconds"]`
+fn test3() {}
@@ -0,0 +1,40 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: --test
+// run-flags: --isolate
+// ignore-emscripten no processes
+// ignore-windows --isolate is only supported on Unix
+
+// error-pattern:test result: FAILED. 1 passed; 3 failed
+// check-stdout
+
+use std::process;
+use std::thread;
+
+#[test]
+fn aborts() {
+    process::abort();
+}
+
+#[test]
+fn exits() {
+    process::exit(0);
+}
+
+#[test]
+fn passes() {}
+
+// Only a panic of the test itself satisfies `#[should_panic]`
+#[test]
+#[should_panic]
+fn helper_thread_panics() {
+    let _ = thread::spawn(|| panic!("helper")).join();
+}
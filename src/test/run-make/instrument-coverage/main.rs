@@ -0,0 +1,27 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn double(x: usize) -> usize {
+    x * 2
+}
+
+fn report(total: usize) {
+    println!("{}", total);
+}
+
+fn main() {
+    let mut total = 0;
+    for i in 0..3 {
+        total += double(i);
+    }
+    if std::env::args().count() > 1 {
+        report(total);
+    }
+}
@@ -0,0 +1,25 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[inline(never)]
+fn classify(n: usize) -> &'static str {
+    if n % 10 == 0 {
+        "round"
+    } else {
+        "other"
+    }
+}
+
+fn main() {
+    let round = (0..1000).filter(|&n| classify(n) == "round").count();
+    if round != 100 {
+        std::process::exit(1);
+    }
+}
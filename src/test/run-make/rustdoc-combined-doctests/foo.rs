@@ -0,0 +1,49 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_type = "lib"]
+
+/// ```
+/// assert_eq!(foo::add(1, 2), 3);
+/// ```
+///
+/// ```
+/// struct Local;
+/// let _ = Local;
+/// assert_eq!(foo::add(0, 0), 0);
+/// ```
+///
+/// ```should_panic
+/// assert_eq!(foo::add(1, 1), 3);
+/// ```
+pub fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+/// ```
+/// extern crate foo;
+/// fn main() {
+///     assert_eq!(foo::add(2, 2), 4);
+/// }
+/// ```
+///
+/// ```compile_fail
+/// let x: u32 = "not a number";
+/// ```
+///
+/// ```ignore
+/// this is not even Rust
+/// ```
+pub fn separate() {}
+
+/// ```
+/// assert_eq!(foo::add(1, 1), 3);
+/// ```
+pub fn fails() {}
@@ -0,0 +1,29 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_type = "lib"]
+
+/// Frobnicates things.
+pub struct Frobnicator<T: Clone> {
+    pub inner: T,
+}
+
+impl<T: Clone> Frobnicator<T> {
+    pub fn frobnicate(&self) -> T {
+        self.inner.clone()
+    }
+
+    #[deprecated(since = "1.0.0", note = "use `frobnicate` instead")]
+    pub fn old_frobnicate(&self) -> T {
+        self.frobnicate()
+    }
+}
+
+struct Hidden;
@@ -0,0 +1,110 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// no-prefer-dynamic
+#![feature(proc_macro)]
+#![crate_type = "proc-macro"]
+
+extern crate proc_macro;
+
+use proc_macro::{TokenStream, TokenTree, TokenNode, Delimiter, Spacing, Term, Literal};
+
+// The trees of `stream`, with the invisible groups of already parsed code
+// flattened.
+fn flatten(stream: TokenStream) -> Vec<TokenTree> {
+    let mut trees = vec![];
+    for tree in stream {
+        match tree.kind {
+            TokenNode::Group(Delimiter::None, stream) => trees.extend(flatten(stream)),
+            _ => trees.push(tree),
+        }
+    }
+    trees
+}
+
+#[proc_macro]
+pub fn check_trees(input: TokenStream) -> TokenStream {
+    let trees: Vec<TokenNode> = input.into_iter().map(|tree| tree.kind).collect();
+    assert_eq!(trees.len(), 8, "{:?}", trees);
+
+    match trees[0] {
+        TokenNode::Term(term) => assert_eq!(term.as_str(), "foo"),
+        ref tree => panic!("expected a term, found {:?}", tree),
+    }
+    match (&trees[1], &trees[2]) {
+        (&TokenNode::Op(':', Spacing::Joint), &TokenNode::Op(':', Spacing::Alone)) => {}
+        trees => panic!("expected `::`, found {:?}", trees),
+    }
+    match trees[4] {
+        TokenNode::Op('+', Spacing::Alone) => {}
+        ref tree => panic!("expected `+`, found {:?}", tree),
+    }
+    match trees[5] {
+        TokenNode::Literal(ref lit) => assert_eq!(lit.to_string(), "\"s\""),
+        ref tree => panic!("expected a literal, found {:?}", tree),
+    }
+    match trees[6] {
+        TokenNode::Group(Delimiter::Bracket, ref stream) => {
+            assert_eq!(stream.clone().into_iter().count(), 3);
+        }
+        ref tree => panic!("expected a group, found {:?}", tree),
+    }
+    match trees[7] {
+        TokenNode::Term(term) => assert_eq!(term.as_str(), "'a"),
+        ref tree => panic!("expected a lifetime, found {:?}", tree),
+    }
+
+    TokenStream::empty()
+}
+
+// Expands `name` to `fn name() -> u32 { 42 }`.
+#[proc_macro]
+pub fn make_fn(input: TokenStream) -> TokenStream {
+    let name = input.into_iter().next().unwrap();
+    let body = TokenStream::from(TokenNode::Literal(Literal::integer(42)));
+    vec![
+        TokenNode::Term(Term::intern("fn")).into(),
+        name,
+        TokenNode::Group(Delimiter::Parenthesis, TokenStream::empty()).into(),
+        TokenNode::Op('-', Spacing::Joint).into(),
+        TokenNode::Op('>', Spacing::Alone).into(),
+        TokenNode::Term(Term::intern("u32")).into(),
+        TokenNode::Group(Delimiter::Brace, body).into(),
+    ].into_iter().collect::<TokenStream>()
+}
+
+// Implements `FieldNames::field_names` for a struct with named fields.
+#[proc_macro_derive(FieldNames)]
+pub fn derive_field_names(input: TokenStream) -> TokenStream {
+    let trees = flatten(input);
+    let name = trees.iter().skip_while(|tree| match tree.kind {
+        TokenNode::Term(term) => term.as_str() != "struct",
+        _ => true,
+    }).nth(1).unwrap().clone();
+    let fields = trees.iter().filter_map(|tree| match tree.kind {
+        TokenNode::Group(Delimiter::Brace, ref stream) => Some(stream.clone()),
+        _ => None,
+    }).next().unwrap();
+
+    let fields = flatten(fields);
+    let mut names = vec![];
+    for (i, tree) in fields.iter().enumerate() {
+        if let TokenNode::Op(':', Spacing::Alone) = tree.kind {
+            if let TokenNode::Term(term) = fields[i - 1].kind {
+                names.push(TokenStream::from(TokenNode::Literal(Literal::string(term.as_str()))));
+                names.push(TokenNode::Op(',', Spacing::Alone).into());
+            }
+        }
+    }
+    let names = TokenNode::Group(Delimiter::Bracket, names.into_iter().collect());
+
+    format!("impl FieldNames for {} {{ fn field_names() -> &'static [&'static str] {{ &{} }} }}",
+            name, names).parse().unwrap()
+}
@@ -0,0 +1,38 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:token-trees.rs
+
+#![feature(proc_macro)]
+
+extern crate token_trees;
+
+use token_trees::{check_trees, make_fn, FieldNames};
+
+trait FieldNames {
+    fn field_names() -> &'static [&'static str];
+}
+
+/// A struct with a doc comment.
+#[derive(FieldNames)]
+struct A {
+    /// The first field.
+    foo: u8,
+    bar: Vec<Vec<u8>>,
+}
+
+check_trees!(foo::bar + "s" [x, y] 'a);
+
+make_fn!(answer);
+
+fn main() {
+    assert_eq!(A::field_names(), ["foo", "bar"]);
+    assert_eq!(answer(), 42);
+}
@@ -0,0 +1,62 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: --test
+// run-flags: --isolate
+// ignore-emscripten no processes
+// ignore-windows --isolate is only supported on Unix
+
+use std::env;
+use std::panic;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::thread;
+
+static RUNS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+// Each test runs in a fresh process, so it never sees what the others did
+#[test]
+fn first() {
+    assert_eq!(RUNS.fetch_add(1, Ordering::SeqCst), 0);
+}
+
+#[test]
+fn second() {
+    assert_eq!(RUNS.fetch_add(1, Ordering::SeqCst), 0);
+}
+
+#[test]
+#[should_panic(expected = "boom")]
+fn panics() {
+    panic!("boom");
+}
+
+// A process aborting counts as the test panicking
+#[test]
+#[should_panic]
+fn aborts() {
+    process::abort();
+}
+
+// Panics which the test handles itself don't decide its outcome
+#[test]
+fn joins_panicked_thread() {
+    assert!(thread::spawn(|| panic!("helper")).join().is_err());
+}
+
+#[test]
+fn catches_unwind() {
+    assert!(panic::catch_unwind(|| panic!("caught")).is_err());
+}
+
+#[test]
+fn does_not_leak_isolation_env() {
+    assert!(env::var_os("__RUST_TEST_ISOLATED").is_none());
+}
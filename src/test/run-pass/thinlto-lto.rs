@@ -0,0 +1,30 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Check that `-C lto -Z thinlto` works across crates, with the upstream
+// bitcode written without `-Z thinlto`, and with several codegen units.
+
+// aux-build:sepcomp_lib.rs
+// compile-flags: -C lto -Z thinlto -C codegen-units=2 -O
+// min-llvm-version 4.0
+// no-prefer-dynamic
+// ignore-android FIXME #18800
+
+extern crate sepcomp_lib;
+use sepcomp_lib::a::one;
+use sepcomp_lib::b::two;
+use sepcomp_lib::c::three;
+
+fn main() {
+    assert_eq!(one(), 1);
+    assert_eq!(two(), 2);
+    assert_eq!(three(), 3);
+    assert_eq!(vec![one(), two(), three()].iter().sum::<usize>(), 6);
+}
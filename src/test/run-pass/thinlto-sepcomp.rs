@@ -0,0 +1,49 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Check that `-Z thinlto` works within a crate split into several codegen
+// units, including for functions imported into other units and statics.
+
+// compile-flags: -Z thinlto -C codegen-units=4 -O
+// min-llvm-version 4.0
+
+static mut COUNT: usize = 0;
+
+fn one() -> usize { 1 }
+
+mod a {
+    pub fn two() -> usize {
+        ::one() + ::one()
+    }
+
+    #[inline(never)]
+    pub fn bump() {
+        unsafe { ::COUNT += 1; }
+    }
+}
+
+mod b {
+    pub fn three() -> usize {
+        ::one() + ::a::two()
+    }
+
+    pub fn generic<T: Clone>(t: &T) -> T {
+        ::a::bump();
+        t.clone()
+    }
+}
+
+fn main() {
+    assert_eq!(one(), 1);
+    assert_eq!(a::two(), 2);
+    assert_eq!(b::three(), 3);
+    assert_eq!(b::generic(&"four"), "four");
+    assert_eq!(unsafe { COUNT }, 1);
+}
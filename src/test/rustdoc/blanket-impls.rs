@@ -0,0 +1,45 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_name = "foo"]
+
+pub trait Greet {
+    fn greet(&self);
+}
+
+impl<T: Clone> Greet for T {
+    fn greet(&self) {}
+}
+
+// @has foo/struct.Cloneable.html
+// @has - '//h2[@id="blanket-implementations"]' 'Blanket Implementations'
+// @has - '//h3[@class="impl blanket-impl"]//code' 'impl<T: Clone> Greet for T'
+// @has - '//h3[@class="impl blanket-impl"]//code' 'impl<T> From<T> for T'
+// @count - '//*[@class="impl"]' 1
+#[derive(Clone)]
+pub struct Cloneable;
+
+// @has foo/struct.NotCloneable.html
+// @!has - '//h3[@class="impl blanket-impl"]//code' 'Greet'
+// @count - '//*[@class="impl"]' 0
+pub struct NotCloneable;
+
+// @has foo/trait.Greet.html
+// @has - '//ul[@id="implementors-list"]//code' 'impl<T: Clone> Greet for T'
+// @!has - '//ul[@id="implementors-list"]//code' 'Cloneable'
+
+pub trait Named {}
+
+// @has foo/trait.Named.html
+// @has - '//h2[@id="foreign-impls"]' 'Implementations on Foreign Types'
+// @has - '//ul[@id="foreign-impls-list"]//code' 'impl Named for String'
+// @has - '//ul[@id="implementors-list"]//code' 'impl Named for NotCloneable'
+impl Named for String {}
+impl Named for NotCloneable {}
@@ -0,0 +1,71 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// @has intra_links/index.html
+// @has - '//a/@href' '../intra_links/struct.ThisType.html'
+// @has - '//a/@href' '../intra_links/struct.ThisType.html#method.this_method'
+// @has - '//a/@href' '../intra_links/enum.ThisEnum.html#variant.ThisVariant'
+// @has - '//a/@href' '../intra_links/trait.ThisTrait.html#tymethod.this_method'
+// @has - '//a/@href' '../intra_links/fn.this_function.html'
+// @has - '//a/@href' '../intra_links/macro.this_macro.html'
+// @has - '//a/@href' '../intra_links/inner/struct.Inner.html'
+// @has - '//a/@href' 'http://example.com/'
+// @has - '//a/@href' '../intra_links/trait.ThisTrait.html'
+// @has - '//a/@href' 'foo.html'
+// @has - '//a[@href="../intra_links/enum.ThisEnum.html"]' 'nested'
+//! Links to [`ThisType`], [ThisType::this_method], [ThisEnum::ThisVariant],
+//! [the trait method](ThisTrait::this_method), [this_function()],
+//! [this_macro!] and [`crate::inner::Inner`].
+//!
+//! [ThisType] has a definition, so it is [left alone][ThisType].
+//!
+//! Links can span [several
+//! lines](ThisTrait), have [the [nested] enum](ThisEnum) in their text,
+//! or be [relative URLs](foo.html).
+//!
+//! [ThisType]: http://example.com/
+
+#[macro_export]
+macro_rules! this_macro {
+    () => {};
+}
+
+pub struct ThisType;
+
+impl ThisType {
+    pub fn this_method() {}
+}
+
+pub enum ThisEnum {
+    ThisVariant,
+}
+
+pub trait ThisTrait {
+    fn this_method();
+}
+
+pub fn this_function() {}
+
+// Summaries link the same way as the full docs, and the search index only
+// keeps the text of links.
+// @has intra_links/inner/index.html
+// @has - '//*[@class="docblock-short"]//a/@href' '../../intra_links/struct.ThisType.html'
+// @has search-index.js 'Links to super::ThisType and `Other::field`.'
+pub mod inner {
+    // @has intra_links/inner/struct.Inner.html
+    // @has - '//a/@href' '../../intra_links/struct.ThisType.html'
+    // @has - '//a/@href' '../../intra_links/inner/struct.Other.html#structfield.field'
+    /// Links to [super::ThisType] and [`Other::field`].
+    pub struct Inner;
+
+    pub struct Other {
+        pub field: u32,
+    }
+}
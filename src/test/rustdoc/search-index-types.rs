@@ -0,0 +1,23 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// ignore-tidy-linelength
+
+#![crate_name = "foo"]
+
+// @has 'search-index.js' '{"inputs":[{"generics":[{"name":"string"}],"name":"vec"}],"output":{"generics":[{"name":"usize"}],"name":"option"}}'
+pub fn longest(strings: &Vec<String>) -> Option<usize> {
+    strings.iter().map(|s| s.len()).max()
+}
+
+// @has 'search-index.js' '{"inputs":[{"generics":[{"name":"u8"}],"name":"slice"},{"name":"str"}],"output":{"generics":[{"name":"u8"},{"name":"str"}],"name":"tuple"}}'
+pub fn pair<'a>(bytes: &'a [u8], s: &'a str) -> (&'a u8, &'a str) {
+    (&bytes[0], s)
+}
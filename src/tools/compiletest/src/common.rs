@@ -12,6 +12,7 @@ pub use self::Mode::*;
 use std::fmt;
 use std::str::FromStr;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
 use test::ColorConfig;
 
@@ -135,6 +136,10 @@ pub struct Config {
     // Write out a parseable log of tests that were run
     pub logfile: Option<PathBuf>,
 
+    // Only run the tests in this `(index, count)` shard of the suite, with
+    // `index` counting from 1
+    pub shard: Option<(usize, usize)>,
+
     // A command line to prefix program execution with,
     // for running under valgrind
     pub runtool: Option<String>,
@@ -187,6 +192,14 @@ pub struct Config {
     // Print one character per test instead of one line
     pub quiet: bool,
 
+    // Overwrite the expected output of ui and mir-opt tests with their
+    // actual output instead of failing
+    pub bless: bool,
+
+    // Expected-output files rewritten by `bless`, shared between all test
+    // threads so they can be listed once the whole suite has run
+    pub blessed_files: Arc<Mutex<Vec<(&'static str, PathBuf)>>>,
+
     // Whether to use colors in test.
     pub color: ColorConfig,
 
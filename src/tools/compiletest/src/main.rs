@@ -33,6 +33,7 @@ use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::{Arc, Mutex};
 use filetime::FileTime;
 use getopts::{optopt, optflag, reqopt};
 use common::Config;
@@ -50,11 +51,21 @@ pub mod runtest;
 pub mod common;
 pub mod errors;
 mod raise_fd_limit;
+mod shard;
 
 fn main() {
     env_logger::init().unwrap();
 
-    let config = parse_config(env::args().collect());
+    let args: Vec<String> = env::args().collect();
+    if args.len() > 1 && args[1] == "--merge-shard-logs" {
+        match shard::merge_shard_logs(&args[2..]) {
+            Ok(true) => return,
+            Ok(false) => panic!("Some tests failed"),
+            Err(e) => panic!("failed to merge shard logs: {}", e),
+        }
+    }
+
+    let config = parse_config(args);
 
     if config.valgrind_path.is_none() && config.force_valgrind {
         panic!("Can't find Valgrind to run Valgrind tests");
@@ -90,8 +101,11 @@ pub fn parse_config(args: Vec<String> ) -> Config {
           optopt("", "target-rustcflags", "flags to pass to rustc for target", "FLAGS"),
           optflag("", "verbose", "run tests verbosely, showing all output"),
           optflag("", "quiet", "print one character per test instead of one line"),
+          optflag("", "bless", "overwrite the expected output of ui and mir-opt tests"),
           optopt("", "color", "coloring: auto, always, never", "WHEN"),
           optopt("", "logfile", "file to log test execution to", "FILE"),
+          optopt("", "shard", "only run the INDEX-th of COUNT equal parts of the suite",
+                 "INDEX/COUNT"),
           optopt("", "target", "the target to build for", "TARGET"),
           optopt("", "host", "the host to build for", "HOST"),
           optopt("", "gdb", "path to GDB to use for GDB debuginfo tests", "PATH"),
@@ -112,7 +126,8 @@ pub fn parse_config(args: Vec<String> ) -> Config {
 
     let (argv0, args_) = args.split_first().unwrap();
     if args.len() == 1 || args[1] == "-h" || args[1] == "--help" {
-        let message = format!("Usage: {} [OPTIONS] [TESTNAME...]", argv0);
+        let message = format!("Usage: {} [OPTIONS] [TESTNAME...]\n       \
+                               {} --merge-shard-logs LOGFILE...", argv0, argv0);
         println!("{}", getopts::usage(&message, &groups));
         println!("");
         panic!()
@@ -155,6 +170,10 @@ pub fn parse_config(args: Vec<String> ) -> Config {
         Some(x) => panic!("argument for --color must be auto, always, or never, but found `{}`", x),
     };
 
+    let shard = matches.opt_str("shard").map(|s| {
+        shard::parse_shard(&s).unwrap_or_else(|e| panic!("{}", e))
+    });
+
     Config {
         compile_lib_path: make_absolute(opt_path(matches, "compile-lib-path")),
         run_lib_path: make_absolute(opt_path(matches, "run-lib-path")),
@@ -173,6 +192,7 @@ pub fn parse_config(args: Vec<String> ) -> Config {
         filter: matches.free.first().cloned(),
         filter_exact: matches.opt_present("exact"),
         logfile: matches.opt_str("logfile").map(|s| PathBuf::from(&s)),
+        shard: shard,
         runtool: matches.opt_str("runtool"),
         host_rustcflags: matches.opt_str("host-rustcflags"),
         target_rustcflags: matches.opt_str("target-rustcflags"),
@@ -193,6 +213,8 @@ pub fn parse_config(args: Vec<String> ) -> Config {
         lldb_python_dir: matches.opt_str("lldb-python-dir"),
         verbose: matches.opt_present("verbose"),
         quiet: matches.opt_present("quiet"),
+        bless: matches.opt_present("bless"),
+        blessed_files: Arc::new(Mutex::new(Vec::new())),
         color: color,
         remote_test_client: matches.opt_str("remote-test-client").map(PathBuf::from),
 
@@ -222,6 +244,9 @@ pub fn log_config(config: &Config) {
                                    .as_ref()
                                    .map(|re| re.to_owned()))));
     logv(c, format!("filter_exact: {}", config.filter_exact));
+    logv(c, format!("shard: {}",
+                    opt_str(&config.shard
+                                   .map(|(index, count)| format!("{}/{}", index, count)))));
     logv(c, format!("runtool: {}", opt_str(&config.runtool)));
     logv(c, format!("host-rustcflags: {}",
                     opt_str(&config.host_rustcflags)));
@@ -237,6 +262,7 @@ pub fn log_config(config: &Config) {
                     config.adb_device_status));
     logv(c, format!("verbose: {}", config.verbose));
     logv(c, format!("quiet: {}", config.quiet));
+    logv(c, format!("bless: {}", config.bless));
     logv(c, format!("\n"));
 }
 
@@ -319,6 +345,7 @@ pub fn run_tests(config: &Config) {
     env::set_var("TARGET", &config.target);
 
     let res = test::run_tests_console(&opts, tests.into_iter().collect());
+    print_blessed_files(config);
     match res {
         Ok(true) => {}
         Ok(false) => panic!("Some tests failed"),
@@ -328,13 +355,28 @@ pub fn run_tests(config: &Config) {
     }
 }
 
+fn print_blessed_files(config: &Config) {
+    let mut blessed = config.blessed_files.lock().unwrap();
+    if blessed.is_empty() {
+        return
+    }
+    blessed.sort_by(|a, b| a.1.cmp(&b.1));
+    println!("blessed {} expected output file(s):", blessed.len());
+    for &(action, ref path) in blessed.iter() {
+        println!("    {} {}", action, path.display());
+    }
+    println!("");
+}
+
 pub fn test_opts(config: &Config) -> test::TestOpts {
     test::TestOpts {
         filter: config.filter.clone(),
         filter_exact: config.filter_exact,
         run_ignored: config.run_ignored,
         quiet: config.quiet,
+        format: test::OutputFormat::Pretty,
         logfile: config.logfile.clone(),
+        junit: None,
         run_tests: true,
         bench_benchmarks: true,
         nocapture: match env::var("RUST_TEST_NOCAPTURE") {
@@ -343,6 +385,12 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         },
         color: config.color,
         test_threads: None,
+        test_timeout: None,
+        isolate: false,
+        shuffle_seed: None,
+        save_baseline: None,
+        baseline: None,
+        regression_threshold: None,
         skip: vec![],
         list: false,
         options: test::Options::new(),
@@ -359,7 +407,10 @@ pub fn make_tests(config: &Config) -> Vec<test::TestDescAndFn> {
                            &PathBuf::new(),
                            &mut tests)
         .unwrap();
-    tests
+    match config.shard {
+        Some(shard) => shard::select_shard(tests, shard),
+        None => tests,
+    }
 }
 
 fn collect_tests_from_dir(config: &Config,
@@ -475,6 +526,7 @@ pub fn make_test(config: &Config, testpaths: &TestPaths) -> test::TestDescAndFn
             name: make_test_name(config, testpaths),
             ignore: ignore,
             should_panic: should_panic,
+            timeout: None,
         },
         testfn: make_test_closure(config, testpaths),
     }
@@ -30,6 +30,7 @@ use std::path::{Path, PathBuf};
 use std::process::{Command, Output, ExitStatus};
 use std::str;
 use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use extract_gdb_version;
 
@@ -2243,7 +2244,8 @@ actual:\n\
         errors += self.compare_output("stderr", &normalized_stderr, &expected_stderr);
 
         if errors > 0 {
-            println!("To update references, run this command from build directory:");
+            println!("To update references, rerun the tests with `--bless` or \
+                      run this command from build directory:");
             let relative_path_to_file =
                 self.testpaths.relative_dir
                               .join(self.testpaths.file.file_name().unwrap());
@@ -2289,6 +2291,7 @@ actual:\n\
             let tests_text_str = String::from(tests_text);
             let mut curr_test : Option<&str> = None;
             let mut curr_test_contents = Vec::new();
+            let mut blessed_tests = HashMap::new();
             for l in tests_text_str.lines() {
                 debug!("line: {:?}", l);
                 if l.starts_with("// START ") {
@@ -2299,7 +2302,14 @@ actual:\n\
                     if Some(t) != curr_test {
                         panic!("mismatched START END test name");
                     }
-                    self.compare_mir_test_output(curr_test.unwrap(), &curr_test_contents);
+                    let test_name = curr_test.unwrap();
+                    if let Err(msg) = self.compare_mir_test_output(test_name,
+                                                                   &curr_test_contents) {
+                        if !self.config.bless {
+                            panic!("{}", msg);
+                        }
+                        blessed_tests.insert(test_name, self.load_mir_dump(test_name));
+                    }
                     curr_test = None;
                     curr_test_contents.clear();
                 } else if l.is_empty() {
@@ -2309,6 +2319,10 @@ actual:\n\
                     curr_test_contents.push(test_content);
                 }
             }
+            if !blessed_tests.is_empty() {
+                let new_contents = blessed_mir_test_file(&test_file_contents, &blessed_tests);
+                self.bless_file(&self.testpaths.file, &new_contents);
+            }
         }
     }
 
@@ -2324,17 +2338,23 @@ actual:\n\
         }
     }
 
-    fn compare_mir_test_output(&self, test_name: &str, expected_content: &Vec<&str>) {
+    fn load_mir_dump(&self, test_name: &str) -> String {
         let mut output_file = PathBuf::new();
         output_file.push(self.get_mir_dump_dir());
         output_file.push(test_name);
         debug!("comparing the contests of: {:?}", output_file);
-        debug!("with: {:?}", expected_content);
         self.check_mir_test_timestamp(test_name, &output_file);
 
         let mut dumped_file = fs::File::open(output_file.clone()).unwrap();
         let mut dumped_string = String::new();
         dumped_file.read_to_string(&mut dumped_string).unwrap();
+        dumped_string
+    }
+
+    fn compare_mir_test_output(&self, test_name: &str, expected_content: &Vec<&str>)
+                               -> Result<(), String> {
+        let dumped_string = self.load_mir_dump(test_name);
+        debug!("with: {:?}", expected_content);
         let mut dumped_lines = dumped_string.lines().filter(|l| !l.is_empty());
         let mut expected_lines = expected_content.iter().filter(|l| !l.is_empty());
 
@@ -2361,15 +2381,16 @@ actual:\n\
                                                  .filter(|l| !l.is_empty())
                                                  .collect::<Vec<_>>()
                                                  .join("\n");
-                panic!("ran out of mir dump output to match against.\n\
-                        Did not find expected line: {:?}\n\
-                        Expected:\n{}\n\
-                        Actual:\n{}",
-                        expected_line,
-                        expected_content.join("\n"),
-                        normalize_all);
+                return Err(format!("ran out of mir dump output to match against.\n\
+                                    Did not find expected line: {:?}\n\
+                                    Expected:\n{}\n\
+                                    Actual:\n{}",
+                                   expected_line,
+                                   expected_content.join("\n"),
+                                   normalize_all));
             }
         }
+        Ok(())
     }
 
     fn get_mir_dump_dir(&self) -> PathBuf {
@@ -2419,6 +2440,11 @@ actual:\n\
             return 0;
         }
 
+        if self.config.bless {
+            self.bless_file(&self.expected_output_path(kind), actual);
+            return 0;
+        }
+
         println!("normalized {}:\n{}\n", kind, actual);
         println!("expected {}:\n{}\n", kind, expected);
         println!("diff of {}:\n", kind);
@@ -2444,6 +2470,15 @@ actual:\n\
         println!("Actual {} saved to {}", kind, output_file.display());
         1
     }
+
+    fn bless_file(&self, path: &Path, contents: &str) {
+        match bless_expected_output(path, contents) {
+            Ok(action) => {
+                self.config.blessed_files.lock().unwrap().push((action, path.to_path_buf()));
+            }
+            Err(e) => self.fatal(&format!("failed to bless `{}`: {}", path.display(), e)),
+        }
+    }
 }
 
 struct ProcArgs {
@@ -2498,3 +2533,100 @@ fn nocomment_mir_line(line: &str) -> &str {
         line
     }
 }
+
+/// Replaces the expected output in `path` with `actual`, returning what was
+/// done to the file. An empty expectation is the same as no expectation at
+/// all, so the file is deleted instead of being left empty.
+fn bless_expected_output(path: &Path, actual: &str) -> io::Result<&'static str> {
+    if actual.is_empty() {
+        fs::remove_file(path)?;
+        return Ok("deleted");
+    }
+    let action = if path.exists() { "updated" } else { "created" };
+    File::create(path)?.write_all(actual.as_bytes())?;
+    Ok(action)
+}
+
+/// Rewrites the `// START`/`// END` blocks of the tests in `blessed_tests`
+/// with their actual MIR dump, leaving the rest of the test file as is.
+fn blessed_mir_test_file(contents: &str, blessed_tests: &HashMap<&str, String>) -> String {
+    let mut new_contents = String::new();
+    let mut in_tests = false;
+    let mut skipping = false;
+    for l in contents.lines() {
+        if !in_tests {
+            in_tests = l.contains("// END RUST SOURCE");
+        } else if l.starts_with("// START ") {
+            let (_, t) = l.split_at("// START ".len());
+            if let Some(dump) = blessed_tests.get(t) {
+                new_contents.push_str(l);
+                new_contents.push('\n');
+                for dump_line in dump.lines()
+                                     .map(nocomment_mir_line)
+                                     .filter(|l| !l.is_empty()) {
+                    new_contents.push_str("// ");
+                    new_contents.push_str(dump_line);
+                    new_contents.push('\n');
+                }
+                skipping = true;
+                continue;
+            }
+        } else if l.starts_with("// END") {
+            skipping = false;
+        } else if skipping {
+            continue;
+        }
+        new_contents.push_str(l);
+        new_contents.push('\n');
+    }
+    new_contents
+}
+
+#[test]
+fn test_bless_expected_output() {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().subsec_nanos();
+    let dir = env::temp_dir().join(format!("compiletest-bless-{}", nanos));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("test.stderr");
+    let read = |path: &Path| {
+        let mut s = String::new();
+        File::open(path).unwrap().read_to_string(&mut s).unwrap();
+        s
+    };
+
+    assert_eq!(bless_expected_output(&path, "error: old\n").unwrap(), "created");
+    assert_eq!(read(&path), "error: old\n");
+    assert_eq!(bless_expected_output(&path, "error: new\n").unwrap(), "updated");
+    assert_eq!(read(&path), "error: new\n");
+    // Stale expected output is removed once the test stops printing anything
+    assert_eq!(bless_expected_output(&path, "").unwrap(), "deleted");
+    assert!(!path.exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_blessed_mir_test_file() {
+    let contents = "fn main() {}\n\
+                    // END RUST SOURCE\n\
+                    // START rustc.node4.Foo.after.mir\n\
+                    // old line\n\
+                    // END rustc.node4.Foo.after.mir\n\
+                    // START rustc.node4.Bar.after.mir\n\
+                    // kept line\n\
+                    // END rustc.node4.Bar.after.mir\n";
+    let mut blessed_tests = HashMap::new();
+    blessed_tests.insert("rustc.node4.Foo.after.mir",
+                         "bb0: {\n    return; // scope 0\n\n}\n".to_owned());
+    assert_eq!(blessed_mir_test_file(contents, &blessed_tests),
+               "fn main() {}\n\
+                // END RUST SOURCE\n\
+                // START rustc.node4.Foo.after.mir\n\
+                // bb0: {\n\
+                //     return;\n\
+                // }\n\
+                // END rustc.node4.Foo.after.mir\n\
+                // START rustc.node4.Bar.after.mir\n\
+                // kept line\n\
+                // END rustc.node4.Bar.after.mir\n");
+}
@@ -0,0 +1,188 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Splitting a test suite between several machines.
+//!
+//! Each machine runs `compiletest --shard INDEX/COUNT --logfile FILE`, and
+//! the resulting log files are combined afterwards with
+//! `compiletest --merge-shard-logs FILE...`.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use test;
+
+/// Parses a shard specification of the form `INDEX/COUNT`, where `INDEX`
+/// counts from 1.
+pub fn parse_shard(s: &str) -> Result<(usize, usize), String> {
+    let err = || format!("argument for --shard must be of the form INDEX/COUNT \
+                          with 1 <= INDEX <= COUNT, but found `{}`", s);
+    let mut parts = s.splitn(2, '/');
+    let index = parts.next().and_then(|p| p.trim().parse::<usize>().ok());
+    let count = parts.next().and_then(|p| p.trim().parse::<usize>().ok());
+    match (index, count) {
+        (Some(index), Some(count)) if index >= 1 && index <= count => Ok((index, count)),
+        _ => Err(err()),
+    }
+}
+
+/// Keeps only the tests belonging to shard `index` of `count`.
+///
+/// Tests are ordered by name before being dealt out round-robin, so every
+/// machine computes the same partition from the same source tree regardless
+/// of the order in which the file system lists directories.
+pub fn select_shard(tests: Vec<test::TestDescAndFn>,
+                    (index, count): (usize, usize))
+                    -> Vec<test::TestDescAndFn> {
+    let mut tests: Vec<_> = tests.into_iter().map(|t| (t.desc.name.to_string(), t)).collect();
+    tests.sort_by(|a, b| a.0.cmp(&b.0));
+    tests.into_iter()
+         .enumerate()
+         .filter(|&(i, _)| i % count == index - 1)
+         .map(|(_, (_, t))| t)
+         .collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Outcome {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+#[derive(Default)]
+struct MergedResults {
+    outcomes: BTreeMap<String, Outcome>,
+    duplicates: Vec<String>,
+    measured: usize,
+}
+
+impl MergedResults {
+    fn add(&mut self, name: &str, outcome: Outcome) {
+        let previous = self.outcomes.insert(name.to_owned(), outcome);
+        if let Some(previous) = previous {
+            self.duplicates.push(name.to_owned());
+            // A failure in any shard sticks.
+            if previous == Outcome::Failed {
+                self.outcomes.insert(name.to_owned(), previous);
+            }
+        }
+    }
+
+    fn count(&self, outcome: Outcome) -> usize {
+        self.outcomes.values().filter(|&&o| o == outcome).count()
+    }
+
+    fn failures(&self) -> Vec<&str> {
+        self.outcomes.iter()
+            .filter(|&(_, &o)| o == Outcome::Failed)
+            .map(|(name, _)| &name[..])
+            .collect()
+    }
+}
+
+/// Splits a line of a libtest log file into its status and test name.
+///
+/// compiletest names its tests `[mode] path`, which is what lets us find the
+/// start of the name even when the status contains a free-form failure
+/// message.
+fn parse_log_line(line: &str) -> Option<(&str, &str)> {
+    line.rfind(" [").map(|i| (&line[..i], &line[i + 1..]))
+}
+
+fn merge_log<R: BufRead>(results: &mut MergedResults, log: R) -> io::Result<()> {
+    for line in log.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue
+        }
+        let (status, name) = match parse_log_line(&line) {
+            Some(pair) => pair,
+            None => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          format!("malformed log line `{}`", line)))
+            }
+        };
+        let outcome = if status == "ok" {
+            Outcome::Passed
+        } else if status == "ignored" {
+            Outcome::Ignored
+        } else if status == "failed" || status.starts_with("failed: ") ||
+                  status == "timed out" {
+            Outcome::Failed
+        } else {
+            results.measured += 1;
+            continue
+        };
+        results.add(name, outcome);
+    }
+    Ok(())
+}
+
+/// Combines the `--logfile` outputs of several shards into a single summary,
+/// returning whether every test passed.
+pub fn merge_shard_logs(paths: &[String]) -> io::Result<bool> {
+    let mut results = MergedResults::default();
+    for path in paths {
+        let file = File::open(Path::new(path)).map_err(|e| {
+            io::Error::new(e.kind(), format!("failed to open `{}`: {}", path, e))
+        })?;
+        merge_log(&mut results, BufReader::new(file))?;
+    }
+
+    let failures = results.failures();
+    if !results.duplicates.is_empty() {
+        println!("\ntests reported by more than one shard:");
+        for name in &results.duplicates {
+            println!("    {}", name);
+        }
+    }
+    if !failures.is_empty() {
+        println!("\nfailures:");
+        for name in &failures {
+            println!("    {}", name);
+        }
+    }
+    let success = failures.is_empty() && results.duplicates.is_empty();
+    println!("\ntest result: {}. {} passed; {} failed; {} ignored; {} measured; \
+              merged from {} shard logs\n",
+             if success { "ok" } else { "FAILED" },
+             results.count(Outcome::Passed),
+             failures.len(),
+             results.count(Outcome::Ignored),
+             results.measured,
+             paths.len());
+    Ok(success)
+}
+
+#[test]
+fn test_parse_shard() {
+    assert_eq!(parse_shard("1/1"), Ok((1, 1)));
+    assert_eq!(parse_shard("3/4"), Ok((3, 4)));
+    assert!(parse_shard("0/4").is_err());
+    assert!(parse_shard("5/4").is_err());
+    assert!(parse_shard("4").is_err());
+    assert!(parse_shard("a/b").is_err());
+}
+
+#[test]
+fn test_merge_logs() {
+    let shard1 = "ok [ui] ui/a.rs\nfailed: line 3 [ui] ui/b.rs\n";
+    let shard2 = "ignored [ui] ui/c.rs\nok [ui] ui/d.rs\nfailed [ui] ui/a.rs\n";
+    let mut results = MergedResults::default();
+    merge_log(&mut results, shard1.as_bytes()).unwrap();
+    merge_log(&mut results, shard2.as_bytes()).unwrap();
+    assert_eq!(results.count(Outcome::Passed), 1);
+    assert_eq!(results.count(Outcome::Ignored), 1);
+    assert_eq!(results.failures(), vec!["[ui] ui/a.rs", "[ui] ui/b.rs"]);
+    assert_eq!(results.duplicates, vec!["[ui] ui/a.rs".to_owned()]);
+}